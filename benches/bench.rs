@@ -1,13 +1,21 @@
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
 use matchit::Router;
+use std::collections::{BTreeMap, BTreeSet};
+use trie_rs::aho::{AhoCorasick, MatchKind};
+use trie_rs::art::ArtMap;
+use trie_rs::darts::DoubleArrayTrie;
+use trie_rs::dawg::Dawg;
 use trie_rs::path::PathTrie;
+use trie_rs::radix::RadixNode;
+use trie_rs::trie::TrieNode;
+use trie_rs::TrieExt;
 
 fn path_trie_bench(c: &mut Criterion) {
     let mut trie = PathTrie::new();
     let mut matchit = Router::new();
 
     for s in &ROUTES {
-        trie.insert(s, true);
+        trie.insert(s, true).unwrap();
     }
 
     for s in ROUTES {
@@ -47,7 +55,326 @@ fn path_trie_bench(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, path_trie_bench);
+/// Random keys of 4 to 11 bytes spread over the whole byte range, so that
+/// inner nodes near the root see a wide fan-out.
+fn byte_keys(n: usize) -> Vec<Vec<u8>> {
+    let mut rng = 0x2545_f491_4f6c_dd1du64;
+    (0..n)
+        .map(|_| {
+            let state = lcg(&mut rng);
+            let len = 4 + (state >> 61) as usize;
+            (0..len).map(|i| (state >> (i * 7)) as u8).collect()
+        })
+        .collect()
+}
+
+fn art_bench(c: &mut Criterion) {
+    let keys = byte_keys(10_000);
+
+    let art: ArtMap<usize> = keys.iter().enumerate().map(|(i, k)| (k, i)).collect();
+    let radix: RadixNode<u8, usize> = keys.iter().enumerate().map(|(i, k)| (k, i)).collect();
+    let btree: BTreeMap<_, _> = keys.iter().enumerate().map(|(i, k)| (k, i)).collect();
+
+    c.bench_function("art-get", |b| {
+        b.iter(|| {
+            for key in &keys {
+                let _ = art.get(black_box(key));
+            }
+        })
+    });
+
+    c.bench_function("radix-get", |b| {
+        b.iter(|| {
+            for key in &keys {
+                let _ = radix.get(black_box(key));
+            }
+        })
+    });
+
+    c.bench_function("btreemap-get", |b| {
+        b.iter(|| {
+            for key in &keys {
+                let _ = btree.get(black_box(key));
+            }
+        })
+    });
+
+    c.bench_function("art-insert", |b| {
+        b.iter(|| keys.iter().map(|k| (k, 0)).collect::<ArtMap<_>>())
+    });
+
+    c.bench_function("radix-insert", |b| {
+        b.iter(|| keys.iter().map(|k| (k, 0)).collect::<RadixNode<u8, _>>())
+    });
+
+    c.bench_function("btreemap-insert", |b| {
+        b.iter(|| keys.iter().map(|k| (k, 0)).collect::<BTreeMap<_, _>>())
+    });
+}
+
+fn radix_fanout_bench(c: &mut Criterion) {
+    // every node below the root branches 256 ways
+    let keys: Vec<[u8; 2]> = (0..=u8::MAX)
+        .flat_map(|a| (0..=u8::MAX).map(move |b| [a, b]))
+        .collect();
+    let radix: RadixNode<u8, usize> = keys.iter().enumerate().map(|(i, k)| (k, i)).collect();
+    let art: ArtMap<usize> = keys.iter().enumerate().map(|(i, k)| (k, i)).collect();
+
+    c.bench_function("radix-fanout-get", |b| {
+        b.iter(|| {
+            for key in &keys {
+                let _ = radix.get(black_box(key));
+            }
+        })
+    });
+
+    c.bench_function("art-fanout-get", |b| {
+        b.iter(|| {
+            for key in &keys {
+                let _ = art.get(black_box(key));
+            }
+        })
+    });
+
+    c.bench_function("radix-fanout-insert", |b| {
+        b.iter(|| keys.iter().map(|k| (k, 0)).collect::<RadixNode<u8, _>>())
+    });
+
+    c.bench_function("radix-fanout-remove", |b| {
+        b.iter_batched(
+            || radix.clone(),
+            |mut radix| {
+                for key in &keys {
+                    radix.remove(key, false);
+                }
+            },
+            BatchSize::LargeInput,
+        )
+    });
+}
+
+fn aho_bench(c: &mut Criterion) {
+    let mut rng = 0x9e37_79b9_7f4a_7c15u64;
+    let mut word = |len: usize| -> Vec<u8> {
+        let state = lcg(&mut rng);
+        (0..len)
+            .map(|i| b'a' + ((state >> (i * 4)) % 16) as u8)
+            .collect()
+    };
+
+    let keywords: Vec<_> = (0..2000).map(|i| word(5 + i % 6)).collect();
+    let lines: Vec<_> = (0..200).map(|_| word(16).repeat(6)).collect();
+    let ac: AhoCorasick<usize> = keywords.iter().zip(0..).collect();
+
+    c.bench_function("aho-find-iter", |b| {
+        b.iter(|| {
+            lines
+                .iter()
+                .map(|line| ac.find_iter(black_box(line)).count())
+                .sum::<usize>()
+        })
+    });
+
+    c.bench_function("aho-leftmost-longest", |b| {
+        b.iter(|| {
+            lines
+                .iter()
+                .map(|line| {
+                    ac.leftmost_iter(black_box(line), MatchKind::LeftmostLongest)
+                        .count()
+                })
+                .sum::<usize>()
+        })
+    });
+
+    c.bench_function("naive-find-iter", |b| {
+        b.iter(|| {
+            let mut count = 0;
+            for line in &lines {
+                for keyword in &keywords {
+                    let windows = black_box(line).windows(keyword.len());
+                    count += windows.filter(|w| w == keyword).count();
+                }
+            }
+            count
+        })
+    });
+}
+
+fn radix_fuzzy_bench(c: &mut Criterion) {
+    let mut rng = 0x853c_49e6_748f_ea9bu64;
+    let mut word = || -> Vec<u8> {
+        let state = lcg(&mut rng);
+        let len = 5 + (state >> 61) as usize;
+        (0..len)
+            .map(|i| b'a' + ((state >> (i * 5)) % 26) as u8)
+            .collect()
+    };
+
+    let words: Vec<_> = (0..20_000).map(|_| word()).collect();
+    let queries: Vec<_> = (0..20).map(|_| word()).collect();
+    let radix: RadixNode<u8, usize> = words.iter().zip(0..).collect();
+
+    c.bench_function("radix-fuzzy-2", |b| {
+        b.iter(|| {
+            for query in &queries {
+                let _ = radix.fuzzy(black_box(query), 2);
+            }
+        })
+    });
+
+    c.bench_function("naive-fuzzy-2", |b| {
+        b.iter(|| {
+            for query in &queries {
+                let _ = words
+                    .iter()
+                    .filter(|w| levenshtein(black_box(query), w) <= 2)
+                    .count();
+            }
+        })
+    });
+}
+
+fn double_array_bench(c: &mut Criterion) {
+    let mut rng = 0x2545_f491_4f6c_dd1du64;
+    let mut word = || -> Vec<u8> {
+        let state = lcg(&mut rng);
+        let len = 2 + (state >> 61) as usize;
+        (0..len)
+            .map(|i| b'a' + ((state >> (i * 5)) % 26) as u8)
+            .collect()
+    };
+
+    let words: BTreeMap<Vec<u8>, usize> = (0..50_000).map(|_| word()).zip(0..).collect();
+    let queries: Vec<_> = words.keys().step_by(10).cloned().collect();
+    let text: Vec<u8> = (0..200).flat_map(|_| word()).collect();
+
+    let darts = DoubleArrayTrie::from_sorted(words.clone()).unwrap();
+    let trie: TrieNode<u8, usize> = words.clone().into_iter().collect();
+    let radix: RadixNode<u8, usize> = words.clone().into_iter().collect();
+
+    c.bench_function("double-array-get", |b| {
+        b.iter(|| {
+            for key in &queries {
+                let _ = darts.get(black_box(key));
+            }
+        })
+    });
+
+    c.bench_function("trie-node-get", |b| {
+        b.iter(|| {
+            for key in &queries {
+                let _ = trie.get(black_box(key));
+            }
+        })
+    });
+
+    c.bench_function("radix-node-get", |b| {
+        b.iter(|| {
+            for key in &queries {
+                let _ = radix.get(black_box(key));
+            }
+        })
+    });
+
+    c.bench_function("double-array-prefixes", |b| {
+        b.iter(|| {
+            (0..text.len())
+                .map(|i| darts.common_prefix_search(black_box(&text[i..])).count())
+                .sum::<usize>()
+        })
+    });
+}
+
+fn dawg_bench(c: &mut Criterion) {
+    const ENDINGS: [&str; 8] = ["", "s", "ing", "ings", "ed", "er", "tion", "tions"];
+
+    let mut rng = 7u64;
+    let mut stems = BTreeSet::new();
+    while stems.len() < 5_000 {
+        let state = lcg(&mut rng);
+        let len = 3 + (state >> 61) as usize;
+        let stem: Vec<u8> = (0..len)
+            .map(|i| b'a' + ((state >> (i * 5 + 3)) % 26) as u8)
+            .collect();
+        stems.insert(stem);
+    }
+
+    // each stem takes six of the eight endings
+    let mut words = BTreeSet::new();
+    for (i, stem) in stems.iter().enumerate() {
+        for (j, ending) in ENDINGS.iter().enumerate() {
+            if (i * 7 + j * 3) % 4 != 0 {
+                words.insert([stem.as_slice(), ending.as_bytes()].concat());
+            }
+        }
+    }
+
+    let dawg = Dawg::from_sorted(&words).unwrap();
+    let trie: TrieNode<u8, ()> = words.iter().map(|k| (k, ())).collect();
+    let queries: Vec<_> = words.iter().step_by(10).collect();
+
+    let usage = dawg.memory_usage();
+    let stats = trie.stats();
+    println!("{} words", words.len());
+    println!(
+        "dawg                    states: {:>6}    heap_bytes: {:>9}",
+        usage.nodes, usage.arena_bytes
+    );
+    println!(
+        "trie-node               nodes:  {:>6}    heap_bytes: {:>9}",
+        stats.nodes, stats.heap_bytes
+    );
+
+    c.bench_function("dawg-contains", |b| {
+        b.iter(|| {
+            for key in &queries {
+                let _ = dawg.contains(black_box(key.as_slice()));
+            }
+        })
+    });
+
+    c.bench_function("trie-node-contains", |b| {
+        b.iter(|| {
+            for key in &queries {
+                let _ = trie.contains_key(black_box(*key));
+            }
+        })
+    });
+}
+
+/// Advances a linear congruential generator, returning its new state.
+fn lcg(state: &mut u64) -> u64 {
+    *state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+    *state
+}
+
+fn levenshtein(a: &[u8], b: &[u8]) -> usize {
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, x) in a.iter().enumerate() {
+        let mut diag = row[0];
+        row[0] = i + 1;
+        for (j, y) in b.iter().enumerate() {
+            let next = (row[j + 1] + 1)
+                .min(row[j] + 1)
+                .min(diag + usize::from(x != y));
+            diag = row[j + 1];
+            row[j + 1] = next;
+        }
+    }
+    row[b.len()]
+}
+
+criterion_group!(
+    benches,
+    path_trie_bench,
+    art_bench,
+    radix_fanout_bench,
+    aho_bench,
+    radix_fuzzy_bench,
+    double_array_bench,
+    dawg_bench
+);
 criterion_main!(benches);
 
 pub const ROUTES: [&str; 315] = [
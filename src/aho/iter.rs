@@ -0,0 +1,119 @@
+use super::{AhoCorasick, Match, MatchKind, NIL, ROOT};
+
+/// Iterator over every match in a haystack, overlapping ones included.
+pub struct FindIter<'a, 'h, V> {
+    ac: &'a AhoCorasick<V>,
+    haystack: &'h [u8],
+    pos: usize,
+    state: u32,
+    /// Next state on the dictionary chain still to report at `pos`.
+    out: u32,
+}
+
+impl<'a, 'h, V> FindIter<'a, 'h, V> {
+    pub(crate) fn new(ac: &'a AhoCorasick<V>, haystack: &'h [u8]) -> Self {
+        Self {
+            ac,
+            haystack,
+            pos: 0,
+            state: ROOT,
+            out: NIL,
+        }
+    }
+}
+
+impl<'a, 'h, V> Iterator for FindIter<'a, 'h, V> {
+    type Item = Match<'a, V>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.out != NIL {
+                let out = self.out;
+                self.out = self.ac.states[out as usize].dict;
+                return Some(self.ac.matched(out, self.pos));
+            }
+
+            let &byte = self.haystack.get(self.pos)?;
+            self.state = self.ac.step(self.state, byte);
+            self.pos += 1;
+            self.out = self.ac.output(self.state);
+        }
+    }
+}
+
+/// Iterator over non-overlapping leftmost matches in a haystack.
+pub struct LeftmostIter<'a, 'h, V> {
+    ac: &'a AhoCorasick<V>,
+    haystack: &'h [u8],
+    pos: usize,
+    kind: MatchKind,
+}
+
+impl<'a, 'h, V> LeftmostIter<'a, 'h, V> {
+    pub(crate) fn new(ac: &'a AhoCorasick<V>, haystack: &'h [u8], kind: MatchKind) -> Self {
+        Self {
+            ac,
+            haystack,
+            pos: 0,
+            kind,
+        }
+    }
+
+    /// Returns `true` if `a` should be reported instead of `b`.
+    fn better(&self, a: &Match<'_, V>, b: &Match<'_, V>) -> bool {
+        if a.start != b.start {
+            return a.start < b.start;
+        }
+
+        match self.kind {
+            MatchKind::LeftmostFirst => a.pattern < b.pattern,
+            MatchKind::LeftmostLongest => a.end > b.end,
+        }
+    }
+}
+
+impl<'a, 'h, V> Iterator for LeftmostIter<'a, 'h, V> {
+    type Item = Match<'a, V>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let ac = self.ac;
+        let mut state = ROOT;
+        let mut best: Option<Match<'a, V>> = None;
+
+        for (pos, &byte) in self.haystack.iter().enumerate().skip(self.pos) {
+            state = ac.step(state, byte);
+            let end = pos + 1;
+
+            let mut out = ac.output(state);
+            while out != NIL {
+                let m = ac.matched(out, end);
+                let better = match &best {
+                    Some(b) => self.better(&m, b),
+                    None => true,
+                };
+                if better {
+                    best = Some(m);
+                }
+                out = ac.states[out as usize].dict;
+            }
+
+            // a match found later starts no earlier than the longest suffix
+            // still being tracked, so none can beat one starting before it
+            let reach = end - ac.states[state as usize].depth as usize;
+            if best.as_ref().is_some_and(|b| b.start < reach) {
+                break;
+            }
+        }
+
+        match best {
+            Some(best) => {
+                self.pos = best.end;
+                Some(best)
+            }
+            None => {
+                self.pos = self.haystack.len();
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,271 @@
+#[cfg(test)]
+mod tests;
+
+mod iter;
+
+pub use iter::{FindIter, LeftmostIter};
+
+use crate::trie::TrieNode;
+use std::collections::VecDeque;
+
+/// Marks a missing pattern or dictionary link.
+const NIL: u32 = u32::MAX;
+
+/// The root state, where every search starts.
+const ROOT: u32 = 0;
+
+/// An Aho-Corasick automaton finding every occurrence of a set of byte
+/// patterns in one pass over a haystack.
+///
+/// States form a trie of the patterns. Each state has a failure link to the
+/// state of its longest proper suffix that is also in the trie, and a
+/// dictionary link to the nearest state on that failure chain that ends a
+/// pattern, so all patterns ending at a position are listed without walking
+/// failure links that lead to none.
+///
+/// Patterns are numbered in the order they are added, which sets their
+/// priority under [`MatchKind::LeftmostFirst`]. The empty pattern is ignored,
+/// as it would match at every position.
+#[derive(Debug, Clone)]
+pub struct AhoCorasick<V> {
+    states: Vec<State>,
+    /// Transitions out of the root, indexed by byte.
+    root: Box<[u32; 256]>,
+    /// Length and value of each pattern.
+    patterns: Vec<(usize, V)>,
+}
+
+#[derive(Debug, Clone)]
+struct State {
+    /// Transitions sorted by byte.
+    next: Vec<(u8, u32)>,
+    fail: u32,
+    /// Nearest state on the failure chain that ends a pattern.
+    dict: u32,
+    /// Pattern ending at this state.
+    pattern: u32,
+    depth: u32,
+}
+
+impl State {
+    fn new(depth: u32) -> Self {
+        Self {
+            next: Vec::new(),
+            fail: ROOT,
+            dict: NIL,
+            pattern: NIL,
+            depth,
+        }
+    }
+}
+
+/// How [`AhoCorasick::leftmost_iter`] picks one match among those starting
+/// at the leftmost position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MatchKind {
+    /// Prefer the pattern added first, like alternation in a regex.
+    LeftmostFirst,
+    /// Prefer the longest pattern.
+    LeftmostLongest,
+}
+
+/// An occurrence of a pattern in a haystack.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct Match<'a, V> {
+    /// Index of the pattern, in the order patterns were added.
+    pub pattern: usize,
+    /// Offset of the first byte of the match.
+    pub start: usize,
+    /// Offset just past the last byte of the match.
+    pub end: usize,
+    pub value: &'a V,
+}
+
+impl<V> Clone for Match<'_, V> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<V> Copy for Match<'_, V> {}
+
+impl<V> AhoCorasick<V> {
+    /// Compiles the automaton for `patterns`. A pattern given more than once
+    /// keeps its first index but takes the last value.
+    pub fn new<I, Q>(patterns: I) -> Self
+    where
+        I: IntoIterator<Item = (Q, V)>,
+        Q: AsRef<[u8]>,
+    {
+        let mut ac = Self {
+            states: vec![State::new(0)],
+            root: Box::new([ROOT; 256]),
+            patterns: Vec::new(),
+        };
+
+        for (key, value) in patterns {
+            ac.add(key.as_ref(), value);
+        }
+
+        ac.link();
+        ac
+    }
+
+    /// Returns the number of distinct patterns.
+    pub fn len(&self) -> usize {
+        self.patterns.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Returns the value of pattern `index`.
+    pub fn value(&self, index: usize) -> Option<&V> {
+        self.patterns.get(index).map(|(_, value)| value)
+    }
+
+    /// Iterates over every occurrence of every pattern, overlapping ones
+    /// included. Matches are ordered by end offset, longest first among
+    /// those ending together.
+    pub fn find_iter<'h>(&self, haystack: &'h [u8]) -> FindIter<'_, 'h, V> {
+        FindIter::new(self, haystack)
+    }
+
+    /// Iterates over non-overlapping matches, scanning left to right: each
+    /// match is the one starting leftmost among those after the previous
+    /// match, with ties broken by `kind`.
+    pub fn leftmost_iter<'h>(
+        &self,
+        haystack: &'h [u8],
+        kind: MatchKind,
+    ) -> LeftmostIter<'_, 'h, V> {
+        LeftmostIter::new(self, haystack, kind)
+    }
+
+    /// Returns the first match `leftmost_iter` would yield.
+    pub fn find(&self, haystack: &[u8], kind: MatchKind) -> Option<Match<'_, V>> {
+        self.leftmost_iter(haystack, kind).next()
+    }
+
+    /// Returns `true` if any pattern occurs in `haystack`.
+    pub fn is_match(&self, haystack: &[u8]) -> bool {
+        self.find_iter(haystack).next().is_some()
+    }
+
+    fn add(&mut self, key: &[u8], value: V) {
+        if key.is_empty() {
+            return;
+        }
+
+        let mut state = ROOT;
+        for &byte in key {
+            let next = &self.states[state as usize].next;
+            state = match next.binary_search_by_key(&byte, |&(b, _)| b) {
+                Ok(idx) => next[idx].1,
+                Err(idx) => {
+                    let new = self.states.len() as u32;
+                    let depth = self.states[state as usize].depth + 1;
+                    self.states[state as usize].next.insert(idx, (byte, new));
+                    self.states.push(State::new(depth));
+                    new
+                }
+            };
+        }
+
+        let state = &mut self.states[state as usize];
+        match state.pattern {
+            NIL => {
+                state.pattern = self.patterns.len() as u32;
+                self.patterns.push((key.len(), value));
+            }
+            idx => self.patterns[idx as usize].1 = value,
+        }
+    }
+
+    /// Sets the failure and dictionary links breadth first, so the links of
+    /// every shallower state are in place when a state is reached.
+    fn link(&mut self) {
+        for &(byte, next) in &self.states[ROOT as usize].next {
+            self.root[byte as usize] = next;
+        }
+
+        let mut queue: VecDeque<u32> = self.states[ROOT as usize]
+            .next
+            .iter()
+            .map(|&(_, next)| next)
+            .collect();
+
+        while let Some(state) = queue.pop_front() {
+            for i in 0..self.states[state as usize].next.len() {
+                let (byte, child) = self.states[state as usize].next[i];
+                let fail = self.step(self.states[state as usize].fail, byte);
+
+                let target = &self.states[fail as usize];
+                let dict = match target.pattern {
+                    NIL => target.dict,
+                    _ => fail,
+                };
+
+                let child_state = &mut self.states[child as usize];
+                child_state.fail = fail;
+                child_state.dict = dict;
+                queue.push_back(child);
+            }
+        }
+    }
+
+    /// Follows the transition on `byte`, falling back along failure links
+    /// until one exists or the root is reached.
+    fn step(&self, mut state: u32, byte: u8) -> u32 {
+        loop {
+            if state == ROOT {
+                return self.root[byte as usize];
+            }
+
+            let s = &self.states[state as usize];
+            if let Ok(idx) = s.next.binary_search_by_key(&byte, |&(b, _)| b) {
+                return s.next[idx].1;
+            }
+            state = s.fail;
+        }
+    }
+
+    /// Returns the first state on the failure chain of `state`, itself
+    /// included, that ends a pattern.
+    fn output(&self, state: u32) -> u32 {
+        let s = &self.states[state as usize];
+        match s.pattern {
+            NIL => s.dict,
+            _ => state,
+        }
+    }
+
+    /// Builds the match of the pattern ending at `state` just before `end`.
+    fn matched(&self, state: u32, end: usize) -> Match<'_, V> {
+        let pattern = self.states[state as usize].pattern as usize;
+        let (len, value) = &self.patterns[pattern];
+        Match {
+            pattern,
+            start: end - len,
+            end,
+            value,
+        }
+    }
+}
+
+/// Compiles the keys of `trie`, numbered in key order.
+impl<V> From<TrieNode<u8, V>> for AhoCorasick<V> {
+    fn from(trie: TrieNode<u8, V>) -> Self {
+        Self::new(trie)
+    }
+}
+
+impl<V, Q> FromIterator<(Q, V)> for AhoCorasick<V>
+where
+    Q: AsRef<[u8]>,
+{
+    fn from_iter<I: IntoIterator<Item = (Q, V)>>(iter: I) -> Self {
+        Self::new(iter)
+    }
+}
@@ -0,0 +1,144 @@
+use crate::aho::{AhoCorasick, MatchKind};
+use crate::test_util::words;
+use crate::trie::TrieNode;
+use crate::TrieExt;
+
+/// Every (pattern, start, end) found by trying each pattern at each offset.
+fn naive(patterns: &[Vec<u8>], haystack: &[u8]) -> Vec<(usize, usize, usize)> {
+    let mut found = Vec::new();
+    for (i, p) in patterns.iter().enumerate() {
+        for start in 0..haystack.len() {
+            if haystack[start..].starts_with(p) {
+                found.push((i, start, start + p.len()));
+            }
+        }
+    }
+    found
+}
+
+fn naive_leftmost(
+    patterns: &[Vec<u8>],
+    haystack: &[u8],
+    kind: MatchKind,
+) -> Vec<(usize, usize, usize)> {
+    let all = naive(patterns, haystack);
+    let mut found = Vec::new();
+    let mut at = 0;
+
+    loop {
+        let candidates = all.iter().filter(|m| m.1 >= at);
+        let best = match kind {
+            MatchKind::LeftmostFirst => candidates.min_by_key(|m| (m.1, m.0)),
+            MatchKind::LeftmostLongest => candidates.min_by_key(|m| (m.1, usize::MAX - m.2)),
+        };
+        match best {
+            Some(&m) => {
+                found.push(m);
+                at = m.2;
+            }
+            None => return found,
+        }
+    }
+}
+
+#[test]
+fn aho_overlapping() {
+    let patterns = ["he", "she", "his", "hers"];
+    let ac: AhoCorasick<usize> = patterns.iter().zip(0..).collect();
+
+    let found: Vec<_> = ac
+        .find_iter(b"ushers")
+        .map(|m| (*m.value, m.start, m.end))
+        .collect();
+    assert_eq!(found, vec![(1, 1, 4), (0, 2, 4), (3, 2, 6)]);
+
+    assert!(ac.is_match(b"this"));
+    assert!(!ac.is_match(b"hallo"));
+    assert_eq!(ac.len(), 4);
+    assert_eq!(ac.value(2), Some(&2));
+}
+
+#[test]
+fn aho_leftmost() {
+    let ac = AhoCorasick::new([("Samwise", 0), ("Sam", 1)]);
+    let m = ac.find(b"Samwise", MatchKind::LeftmostFirst).unwrap();
+    assert_eq!((m.start, m.end, *m.value), (0, 7, 0));
+
+    let ac = AhoCorasick::new([("Sam", 0), ("Samwise", 1)]);
+    let m = ac.find(b"Samwise", MatchKind::LeftmostFirst).unwrap();
+    assert_eq!((m.start, m.end, *m.value), (0, 3, 0));
+    let m = ac.find(b"Samwise", MatchKind::LeftmostLongest).unwrap();
+    assert_eq!((m.start, m.end, *m.value), (0, 7, 1));
+
+    // a later, longer match must not hide one starting further left
+    let ac = AhoCorasick::new([("bcd", 0), ("abcdef", 1), ("cd", 2)]);
+    let found: Vec<_> = ac
+        .leftmost_iter(b"abcdexbcd", MatchKind::LeftmostLongest)
+        .map(|m| (m.start, m.end))
+        .collect();
+    assert_eq!(found, vec![(1, 4), (6, 9)]);
+}
+
+#[test]
+fn aho_random() {
+    for seed in 0..20 {
+        let patterns = words(seed, 30, 1..=8, b'a'..=b'c');
+        let ac: AhoCorasick<()> = patterns.iter().map(|p| (p, ())).collect();
+
+        // duplicates keep the index they were first given
+        let first = |p: &Vec<u8>| patterns.iter().position(|q| q == p).unwrap();
+        let unique: Vec<_> = patterns
+            .iter()
+            .enumerate()
+            .filter(|&(i, p)| first(p) == i)
+            .map(|(_, p)| p.clone())
+            .collect();
+        assert_eq!(ac.len(), unique.len());
+
+        for haystack in words(seed + 100, 10, 20..=27, b'a'..=b'c') {
+            let mut found: Vec<_> = ac
+                .find_iter(&haystack)
+                .map(|m| (m.pattern, m.start, m.end))
+                .collect();
+            // by end offset, longest first
+            assert!(found
+                .windows(2)
+                .all(|w| (w[0].2, w[0].1) < (w[1].2, w[1].1)));
+            found.sort();
+            assert_eq!(found, naive(&unique, &haystack));
+
+            for kind in [MatchKind::LeftmostFirst, MatchKind::LeftmostLongest] {
+                let found: Vec<_> = ac
+                    .leftmost_iter(&haystack, kind)
+                    .map(|m| (m.pattern, m.start, m.end))
+                    .collect();
+                assert_eq!(found, naive_leftmost(&unique, &haystack, kind));
+            }
+        }
+    }
+}
+
+#[test]
+fn aho_from_trie() {
+    let mut trie = TrieNode::new(0u8);
+    trie.insert(b"error", "E").unwrap();
+    trie.insert(b"err", "e").unwrap();
+    trie.insert(b"warn", "W").unwrap();
+    trie.insert(b"", "empty").unwrap();
+
+    let ac = AhoCorasick::from(trie);
+    assert_eq!(ac.len(), 3);
+
+    // patterns are numbered in key order: "err", "error", "warn"
+    let line = b"warning: error 42";
+    let values: Vec<_> = ac.find_iter(line).map(|m| *m.value).collect();
+    assert_eq!(values, vec!["W", "e", "E"]);
+
+    let m = ac.find(line, MatchKind::LeftmostFirst).unwrap();
+    assert_eq!((m.pattern, m.start), (2, 0));
+    let values: Vec<_> = ac
+        .leftmost_iter(&line[4..], MatchKind::LeftmostFirst)
+        .map(|m| *m.value)
+        .collect();
+    assert_eq!(values, vec!["e"]);
+}
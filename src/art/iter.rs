@@ -0,0 +1,54 @@
+use super::node::Node;
+
+/// Borrowing iterator over the entries of an [`ArtMap`](super::ArtMap), in
+/// key order.
+pub struct Iter<'a, V> {
+    stack: Vec<(Vec<u8>, &'a Node<V>)>,
+    remaining: usize,
+}
+
+impl<'a, V> Iter<'a, V> {
+    pub(super) fn new(root: Option<&'a Node<V>>, len: usize) -> Self {
+        Self {
+            stack: root.map(|node| (Vec::new(), node)).into_iter().collect(),
+            remaining: len,
+        }
+    }
+}
+
+impl<'a, V> Iterator for Iter<'a, V> {
+    type Item = (Vec<u8>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((mut key, node)) = self.stack.pop() {
+            let inner = match node {
+                Node::Leaf(leaf) => {
+                    self.remaining -= 1;
+                    return Some((leaf.key.to_vec(), &leaf.value));
+                }
+                Node::Inner(inner) => inner,
+            };
+
+            key.extend_from_slice(&inner.prefix);
+
+            for (byte, child) in inner.children.iter().rev() {
+                let mut path = key.clone();
+                path.push(byte);
+                self.stack.push((path, child));
+            }
+
+            if let Some(value) = &inner.value {
+                self.remaining -= 1;
+                return Some((key, value));
+            }
+        }
+
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<V> ExactSizeIterator for Iter<'_, V> {}
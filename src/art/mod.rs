@@ -0,0 +1,358 @@
+#[cfg(test)]
+mod tests;
+
+mod iter;
+mod node;
+
+pub use iter::Iter;
+
+use crate::{TrieError, TrieExt};
+use node::{Inner, Leaf, Node};
+use std::borrow::Borrow;
+use std::mem;
+use std::ops::Index;
+
+/// An adaptive radix tree over byte keys.
+///
+/// Inner nodes switch between 4, 16, 48 and 256 child layouts as their
+/// fan-out changes, shared key bytes are compressed into a per-node prefix,
+/// and a subtree holding a single key is stored as one leaf.
+#[derive(Debug, Clone)]
+pub struct ArtMap<V> {
+    root: Option<Node<V>>,
+    len: usize,
+}
+
+impl<V> Default for ArtMap<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> ArtMap<V> {
+    pub fn new() -> Self {
+        Self { root: None, len: 0 }
+    }
+
+    /// Iterates over every stored key and value in key order.
+    pub fn iter(&self) -> Iter<'_, V> {
+        Iter::new(self.root.as_ref(), self.len)
+    }
+
+    fn insert_at(slot: &mut Option<Node<V>>, key: &[u8], depth: usize, value: V) -> Option<V> {
+        let inner = match slot {
+            None => {
+                *slot = Some(Node::leaf(key, value));
+                return None;
+            }
+            Some(Node::Leaf(leaf)) if *leaf.key == *key => {
+                return Some(mem::replace(&mut leaf.value, value));
+            }
+            Some(Node::Leaf(leaf)) => {
+                // both keys go below a new node holding their common bytes
+                let end = depth + longest_match(&leaf.key[depth..], &key[depth..]);
+                let mut split = Inner::new(&key[depth..end]);
+                split.adopt(Leaf::new(key, value), end);
+
+                if let Some(Node::Leaf(old)) = slot.take() {
+                    split.adopt(old, end);
+                }
+
+                *slot = Some(Node::Inner(Box::new(split)));
+                return None;
+            }
+            Some(Node::Inner(inner)) => inner,
+        };
+
+        let common = longest_match(&inner.prefix, &key[depth..]);
+
+        if common < inner.prefix.len() {
+            let mut split = Box::new(Inner::new(&inner.prefix[..common]));
+            split.adopt(Leaf::new(key, value), depth + common);
+
+            let mut old = mem::replace(inner, split);
+            let byte = old.prefix[common];
+            old.prefix.drain(..=common);
+            inner.children.insert(byte, Node::Inner(old));
+
+            return None;
+        }
+
+        let depth = depth + common;
+
+        let byte = match key.get(depth) {
+            Some(&byte) => byte,
+            None => return inner.value.replace(value),
+        };
+
+        match inner.children.slot_mut(byte) {
+            Some(child) => Self::insert_at(child, key, depth + 1, value),
+            None => {
+                inner.children.insert(byte, Node::leaf(key, value));
+                None
+            }
+        }
+    }
+
+    fn remove_at(slot: &mut Option<Node<V>>, key: &[u8], depth: usize) -> Option<V> {
+        let inner = match slot.as_mut()? {
+            Node::Leaf(leaf) if *leaf.key == *key => {
+                return match slot.take() {
+                    Some(Node::Leaf(leaf)) => Some(leaf.value),
+                    _ => None,
+                };
+            }
+            Node::Leaf(_) => return None,
+            Node::Inner(inner) => inner,
+        };
+
+        if !has_prefix(key, depth, &inner.prefix) {
+            return None;
+        }
+
+        let end = depth + inner.prefix.len();
+
+        let value = match key.get(end) {
+            None => inner.value.take()?,
+            Some(&byte) => {
+                let child = inner.children.slot_mut(byte)?;
+                let value = Self::remove_at(child, key, end + 1)?;
+                if child.is_none() {
+                    inner.children.remove(byte);
+                }
+                value
+            }
+        };
+
+        Self::repair(slot, &key[..end]);
+        Some(value)
+    }
+
+    /// Detaches the subtree holding every key that starts with `key`. An
+    /// inner node taken from below the root gets the path above it prepended
+    /// to its prefix, so it can serve as the root of its own tree.
+    fn detach(slot: &mut Option<Node<V>>, key: &[u8], depth: usize) -> Option<Node<V>> {
+        let inner = match slot.as_mut()? {
+            Node::Leaf(leaf) if leaf.key.starts_with(key) => return slot.take(),
+            Node::Leaf(_) => return None,
+            Node::Inner(inner) => inner,
+        };
+
+        let rest = &key[depth..];
+        let common = longest_match(&inner.prefix, rest);
+
+        if common == rest.len() {
+            let mut node = slot.take();
+            if let Some(Node::Inner(inner)) = &mut node {
+                inner.prefix.splice(0..0, key[..depth].iter().copied());
+            }
+            return node;
+        }
+
+        if common < inner.prefix.len() {
+            return None;
+        }
+
+        let end = depth + common;
+        let byte = key[end];
+
+        let child = inner.children.slot_mut(byte)?;
+        let node = Self::detach(child, key, end + 1)?;
+        if child.is_none() {
+            inner.children.remove(byte);
+        }
+
+        Self::repair(slot, &key[..end]);
+        Some(node)
+    }
+
+    /// Restores the tree invariants for an inner node that lost a value or a
+    /// child: a node left with only a value becomes a leaf, and a value-less
+    /// node with a single child is merged into that child.
+    fn repair(slot: &mut Option<Node<V>>, path: &[u8]) {
+        let inner = match slot {
+            Some(Node::Inner(inner)) => inner,
+            _ => return,
+        };
+
+        match (inner.value.is_some(), inner.children.len()) {
+            (false, 0) => *slot = None,
+            (true, 0) => {
+                if let Some(value) = inner.value.take() {
+                    *slot = Some(Node::leaf(path, value));
+                }
+            }
+            (false, 1) => {
+                let byte = match inner.children.iter().next() {
+                    Some((byte, _)) => byte,
+                    None => return,
+                };
+
+                let mut prefix = mem::take(&mut inner.prefix);
+                *slot = match inner.children.remove(byte) {
+                    Some(Node::Inner(mut child)) => {
+                        prefix.push(byte);
+                        prefix.append(&mut child.prefix);
+                        child.prefix = prefix;
+                        Some(Node::Inner(child))
+                    }
+                    leaf => leaf,
+                };
+            }
+            _ => (),
+        }
+    }
+}
+
+impl<V> TrieExt<u8, V> for ArtMap<V> {
+    fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        Q: Borrow<[u8]>,
+    {
+        let key = key.borrow();
+        let mut node = self.root.as_ref()?;
+        let mut depth = 0;
+
+        loop {
+            let inner = match node {
+                Node::Leaf(leaf) => return (*leaf.key == *key).then_some(&leaf.value),
+                Node::Inner(inner) => inner,
+            };
+
+            if !has_prefix(key, depth, &inner.prefix) {
+                return None;
+            }
+
+            depth += inner.prefix.len();
+
+            match key.get(depth) {
+                Some(&byte) => node = inner.children.get(byte)?,
+                None => return inner.value.as_ref(),
+            }
+
+            depth += 1;
+        }
+    }
+
+    fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        Q: Borrow<[u8]>,
+    {
+        let key = key.borrow();
+        let mut node = self.root.as_mut()?;
+        let mut depth = 0;
+
+        loop {
+            let inner = match node {
+                Node::Leaf(leaf) => return (*leaf.key == *key).then_some(&mut leaf.value),
+                Node::Inner(inner) => inner,
+            };
+
+            if !has_prefix(key, depth, &inner.prefix) {
+                return None;
+            }
+
+            depth += inner.prefix.len();
+
+            match key.get(depth) {
+                Some(&byte) => node = inner.children.get_mut(byte)?,
+                None => return inner.value.as_mut(),
+            }
+
+            depth += 1;
+        }
+    }
+
+    fn insert<Q>(&mut self, key: &Q, value: V) -> Result<Option<V>, TrieError>
+    where
+        Q: Borrow<[u8]> + ?Sized,
+    {
+        let old = Self::insert_at(&mut self.root, key.borrow(), 0, value);
+        if old.is_none() {
+            self.len += 1;
+        }
+        Ok(old)
+    }
+
+    /// Removes `key` and returns it in a map of its own. With `prune`, every
+    /// key starting with `key` is removed and returned instead.
+    fn remove<Q>(&mut self, key: &Q, prune: bool) -> Option<Self>
+    where
+        Q: Borrow<[u8]>,
+    {
+        let key = key.borrow();
+
+        let root = match prune {
+            true => Self::detach(&mut self.root, key, 0)?,
+            false => Node::leaf(key, Self::remove_at(&mut self.root, key, 0)?),
+        };
+
+        let len = root.count();
+        self.len -= len;
+
+        Some(Self {
+            root: Some(root),
+            len,
+        })
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn clear(&mut self) {
+        self.root = None;
+        self.len = 0;
+    }
+}
+
+impl<V, Q> FromIterator<(Q, V)> for ArtMap<V>
+where
+    Q: AsRef<[u8]>,
+{
+    fn from_iter<I: IntoIterator<Item = (Q, V)>>(iter: I) -> Self {
+        let mut art = ArtMap::new();
+        art.extend(iter);
+        art
+    }
+}
+
+impl<V, Q> Extend<(Q, V)> for ArtMap<V>
+where
+    Q: AsRef<[u8]>,
+{
+    fn extend<I: IntoIterator<Item = (Q, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            // inserting into an adaptive radix tree cannot fail
+            let _ = self.insert(key.as_ref(), value);
+        }
+    }
+}
+
+impl<'a, V> IntoIterator for &'a ArtMap<V> {
+    type Item = (Vec<u8>, &'a V);
+    type IntoIter = Iter<'a, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<V> Index<&[u8]> for ArtMap<V> {
+    type Output = V;
+
+    fn index(&self, key: &[u8]) -> &V {
+        self.get(&key).expect("key not found in trie")
+    }
+}
+
+fn longest_match(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b).take_while(|(x, y)| x == y).count()
+}
+
+/// Whether `key` goes on with `prefix` from `depth`. Most inner nodes branch
+/// right away, so an empty prefix is ruled out before comparing slices, which
+/// costs a `memcmp` call even when there is nothing to compare.
+fn has_prefix(key: &[u8], depth: usize, prefix: &[u8]) -> bool {
+    prefix.is_empty() || key[depth..].starts_with(prefix)
+}
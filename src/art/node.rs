@@ -0,0 +1,395 @@
+use std::mem;
+
+/// A slot in the tree: either a leaf holding a complete key, or an inner node
+/// that branches on one byte after its compressed prefix.
+#[derive(Debug, Clone)]
+pub(super) enum Node<V> {
+    Leaf(Leaf<V>),
+    Inner(Box<Inner<V>>),
+}
+
+/// Leaves keep the whole key, so a subtree holding a single key collapses
+/// into one leaf no matter how long the key is (lazy expansion). They sit in
+/// their parent's child slot rather than behind a box of their own, so
+/// reaching the key of a leaf costs a single pointer.
+#[derive(Debug, Clone)]
+pub(super) struct Leaf<V> {
+    pub(super) key: Box<[u8]>,
+    pub(super) value: V,
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct Inner<V> {
+    /// Bytes shared by every key below this node, skipped before branching.
+    pub(super) prefix: Vec<u8>,
+    /// Value of the key that ends right after `prefix`.
+    pub(super) value: Option<V>,
+    pub(super) children: Children<V>,
+}
+
+/// Child tables sized to the fan-out; a node grows into the next layout when
+/// full and shrinks back once it has become sparse enough.
+#[derive(Debug, Clone)]
+pub(super) enum Children<V> {
+    N4(Sorted<V, 4>),
+    N16(Box<Sorted<V, 16>>),
+    N48(Box<Indexed<V>>),
+    N256(Box<Direct<V>>),
+}
+
+/// Node4 and Node16: parallel arrays of sorted key bytes and children.
+#[derive(Debug, Clone)]
+pub(super) struct Sorted<V, const N: usize> {
+    len: usize,
+    keys: [u8; N],
+    nodes: [Option<Node<V>>; N],
+}
+
+/// Node48: a 256-entry table mapping a byte to `slot + 1`, zero meaning absent.
+#[derive(Debug, Clone)]
+pub(super) struct Indexed<V> {
+    len: usize,
+    index: [u8; 256],
+    nodes: [Option<Node<V>>; 48],
+}
+
+/// Node256: one slot per possible byte.
+#[derive(Debug, Clone)]
+pub(super) struct Direct<V> {
+    len: usize,
+    nodes: [Option<Node<V>>; 256],
+}
+
+impl<V> Node<V> {
+    pub(super) fn leaf(key: &[u8], value: V) -> Self {
+        Node::Leaf(Leaf::new(key, value))
+    }
+
+    /// Counts the values stored in this subtree.
+    pub(super) fn count(&self) -> usize {
+        match self {
+            Node::Leaf(_) => 1,
+            Node::Inner(inner) => {
+                let count = usize::from(inner.value.is_some());
+                inner
+                    .children
+                    .iter()
+                    .fold(count, |acc, (_, n)| acc + n.count())
+            }
+        }
+    }
+}
+
+impl<V> Leaf<V> {
+    pub(super) fn new(key: &[u8], value: V) -> Self {
+        Self {
+            key: key.into(),
+            value,
+        }
+    }
+}
+
+impl<V> Inner<V> {
+    pub(super) fn new(prefix: &[u8]) -> Self {
+        Self {
+            prefix: prefix.to_vec(),
+            value: None,
+            children: Children::N4(Sorted::new()),
+        }
+    }
+
+    /// Places a leaf whose key matches this node's path up to `depth`.
+    pub(super) fn adopt(&mut self, leaf: Leaf<V>, depth: usize) {
+        match leaf.key.get(depth) {
+            Some(&byte) => self.children.insert(byte, Node::Leaf(leaf)),
+            None => self.value = Some(leaf.value),
+        }
+    }
+}
+
+impl<V> Children<V> {
+    pub(super) fn len(&self) -> usize {
+        match self {
+            Children::N4(n) => n.len,
+            Children::N16(n) => n.len,
+            Children::N48(n) => n.len,
+            Children::N256(n) => n.len,
+        }
+    }
+
+    pub(super) fn get(&self, byte: u8) -> Option<&Node<V>> {
+        let slot = match self {
+            Children::N4(n) => &n.nodes[n.find(byte)?],
+            Children::N16(n) => &n.nodes[n.find(byte)?],
+            Children::N48(n) => &n.nodes[n.find(byte)?],
+            Children::N256(n) => &n.nodes[byte as usize],
+        };
+        slot.as_ref()
+    }
+
+    pub(super) fn get_mut(&mut self, byte: u8) -> Option<&mut Node<V>> {
+        self.slot_mut(byte)?.as_mut()
+    }
+
+    /// Returns the slot holding the child for `byte`. The tree code may empty
+    /// the slot, after which it must call [`Children::remove`] for `byte`.
+    pub(super) fn slot_mut(&mut self, byte: u8) -> Option<&mut Option<Node<V>>> {
+        let slot = match self {
+            Children::N4(n) => &mut n.nodes[n.find(byte)?],
+            Children::N16(n) => &mut n.nodes[n.find(byte)?],
+            Children::N48(n) => &mut n.nodes[n.find(byte)?],
+            Children::N256(n) => &mut n.nodes[byte as usize],
+        };
+        slot.is_some().then_some(slot)
+    }
+
+    /// Adds a child for a byte that has none yet, growing the layout if full.
+    pub(super) fn insert(&mut self, byte: u8, node: Node<V>) {
+        self.grow();
+
+        match self {
+            Children::N4(n) => n.insert(byte, node),
+            Children::N16(n) => n.insert(byte, node),
+            Children::N48(n) => n.insert(byte, node),
+            Children::N256(n) => n.insert(byte, node),
+        }
+    }
+
+    /// Removes the child for `byte`, which must be present although its slot
+    /// may already have been emptied through [`Children::slot_mut`].
+    pub(super) fn remove(&mut self, byte: u8) -> Option<Node<V>> {
+        let node = match self {
+            Children::N4(n) => n.remove(byte),
+            Children::N16(n) => n.remove(byte),
+            Children::N48(n) => n.remove(byte),
+            Children::N256(n) => n.remove(byte),
+        };
+
+        self.shrink();
+        node
+    }
+
+    /// Iterates over the children in byte order.
+    pub(super) fn iter(&self) -> ChildIter<'_, V> {
+        let back = match self {
+            Children::N4(n) => n.len,
+            Children::N16(n) => n.len,
+            _ => 256,
+        };
+
+        ChildIter {
+            children: self,
+            front: 0,
+            back,
+        }
+    }
+
+    fn grow(&mut self) {
+        *self = match self {
+            Children::N4(n) if n.len == 4 => Children::N16(Box::new(n.take_all().collect())),
+            Children::N16(n) if n.len == 16 => Children::N48(Box::new(n.take_all().collect())),
+            Children::N48(n) if n.len == 48 => Children::N256(Box::new(n.take_all().collect())),
+            _ => return,
+        };
+    }
+
+    fn shrink(&mut self) {
+        // shrink below the growth thresholds so that alternating inserts and
+        // removals around a boundary do not convert the node every time
+        *self = match self {
+            Children::N16(n) if n.len <= 3 => Children::N4(n.take_all().collect()),
+            Children::N48(n) if n.len <= 12 => Children::N16(Box::new(n.take_all().collect())),
+            Children::N256(n) if n.len <= 37 => Children::N48(Box::new(n.take_all().collect())),
+            _ => return,
+        };
+    }
+}
+
+impl<V, const N: usize> Sorted<V, N> {
+    fn new() -> Self {
+        Self {
+            len: 0,
+            keys: [0; N],
+            nodes: std::array::from_fn(|_| None),
+        }
+    }
+
+    fn find(&self, byte: u8) -> Option<usize> {
+        self.keys[..self.len].binary_search(&byte).ok()
+    }
+
+    fn insert(&mut self, byte: u8, node: Node<V>) {
+        let pos = self.keys[..self.len].partition_point(|&k| k < byte);
+
+        // the free slot at `len` rotates into place
+        self.keys[pos..=self.len].rotate_right(1);
+        self.nodes[pos..=self.len].rotate_right(1);
+        self.keys[pos] = byte;
+        self.nodes[pos] = Some(node);
+        self.len += 1;
+    }
+
+    fn remove(&mut self, byte: u8) -> Option<Node<V>> {
+        let pos = self.find(byte)?;
+        let node = self.nodes[pos].take();
+
+        self.keys[pos..self.len].rotate_left(1);
+        self.nodes[pos..self.len].rotate_left(1);
+        self.len -= 1;
+
+        node
+    }
+
+    fn take_all(&mut self) -> impl Iterator<Item = (u8, Node<V>)> + '_ {
+        let len = mem::take(&mut self.len);
+        self.keys[..len]
+            .iter()
+            .zip(&mut self.nodes[..len])
+            .filter_map(|(&byte, node)| Some((byte, node.take()?)))
+    }
+}
+
+impl<V, const N: usize> FromIterator<(u8, Node<V>)> for Sorted<V, N> {
+    fn from_iter<I: IntoIterator<Item = (u8, Node<V>)>>(iter: I) -> Self {
+        let mut sorted = Sorted::new();
+        for (byte, node) in iter {
+            sorted.insert(byte, node);
+        }
+        sorted
+    }
+}
+
+impl<V> Indexed<V> {
+    fn find(&self, byte: u8) -> Option<usize> {
+        match self.index[byte as usize] {
+            0 => None,
+            slot => Some(slot as usize - 1),
+        }
+    }
+
+    fn insert(&mut self, byte: u8, node: Node<V>) {
+        // removals leave holes, so the first free slot is not always `len`;
+        // `Children::grow` makes sure there is one
+        let slot = match self.nodes.iter().position(Option::is_none) {
+            Some(slot) => slot,
+            None => return,
+        };
+
+        self.nodes[slot] = Some(node);
+        self.index[byte as usize] = slot as u8 + 1;
+        self.len += 1;
+    }
+
+    fn remove(&mut self, byte: u8) -> Option<Node<V>> {
+        let slot = self.find(byte)?;
+
+        self.index[byte as usize] = 0;
+        self.len -= 1;
+
+        self.nodes[slot].take()
+    }
+
+    fn take_all(&mut self) -> impl Iterator<Item = (u8, Node<V>)> + '_ {
+        self.len = 0;
+        let Self { index, nodes, .. } = self;
+
+        (0..=u8::MAX).filter_map(move |byte| match mem::take(&mut index[byte as usize]) {
+            0 => None,
+            slot => Some((byte, nodes[slot as usize - 1].take()?)),
+        })
+    }
+}
+
+impl<V> FromIterator<(u8, Node<V>)> for Indexed<V> {
+    fn from_iter<I: IntoIterator<Item = (u8, Node<V>)>>(iter: I) -> Self {
+        let mut indexed = Indexed {
+            len: 0,
+            index: [0; 256],
+            nodes: std::array::from_fn(|_| None),
+        };
+        for (byte, node) in iter {
+            indexed.insert(byte, node);
+        }
+        indexed
+    }
+}
+
+impl<V> Direct<V> {
+    fn insert(&mut self, byte: u8, node: Node<V>) {
+        self.nodes[byte as usize] = Some(node);
+        self.len += 1;
+    }
+
+    fn remove(&mut self, byte: u8) -> Option<Node<V>> {
+        // the slot may have been emptied already, but the entry was present
+        self.len -= 1;
+        self.nodes[byte as usize].take()
+    }
+
+    fn take_all(&mut self) -> impl Iterator<Item = (u8, Node<V>)> + '_ {
+        self.len = 0;
+        self.nodes
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(byte, node)| Some((byte as u8, node.take()?)))
+    }
+}
+
+impl<V> FromIterator<(u8, Node<V>)> for Direct<V> {
+    fn from_iter<I: IntoIterator<Item = (u8, Node<V>)>>(iter: I) -> Self {
+        let mut direct = Direct {
+            len: 0,
+            nodes: std::array::from_fn(|_| None),
+        };
+        for (byte, node) in iter {
+            direct.insert(byte, node);
+        }
+        direct
+    }
+}
+
+/// Iterates over the children of one node in byte order, from either end.
+pub(super) struct ChildIter<'a, V> {
+    children: &'a Children<V>,
+    front: usize,
+    back: usize,
+}
+
+impl<'a, V> ChildIter<'a, V> {
+    /// Returns the child at `pos`, which is a slot for the sorted layouts and
+    /// a byte value for the indexed ones.
+    fn entry(&self, pos: usize) -> Option<(u8, &'a Node<V>)> {
+        match self.children {
+            Children::N4(n) => Some((n.keys[pos], n.nodes[pos].as_ref()?)),
+            Children::N16(n) => Some((n.keys[pos], n.nodes[pos].as_ref()?)),
+            Children::N48(n) => Some((pos as u8, n.nodes[n.find(pos as u8)?].as_ref()?)),
+            Children::N256(n) => Some((pos as u8, n.nodes[pos].as_ref()?)),
+        }
+    }
+}
+
+impl<'a, V> Iterator for ChildIter<'a, V> {
+    type Item = (u8, &'a Node<V>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.front < self.back {
+            self.front += 1;
+            if let Some(entry) = self.entry(self.front - 1) {
+                return Some(entry);
+            }
+        }
+        None
+    }
+}
+
+impl<V> DoubleEndedIterator for ChildIter<'_, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        while self.front < self.back {
+            self.back -= 1;
+            if let Some(entry) = self.entry(self.back) {
+                return Some(entry);
+            }
+        }
+        None
+    }
+}
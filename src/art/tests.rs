@@ -0,0 +1,155 @@
+use crate::art::ArtMap;
+use crate::test_util::words;
+use crate::TrieExt;
+use std::collections::BTreeMap;
+
+fn check(art: &ArtMap<usize>, expected: &BTreeMap<Vec<u8>, usize>) {
+    assert_eq!(art.len(), expected.len());
+    assert_eq!(art.iter().len(), expected.len());
+
+    let entries: Vec<_> = art.iter().map(|(k, v)| (k, *v)).collect();
+    let expected_entries: Vec<_> = expected.iter().map(|(k, v)| (k.clone(), *v)).collect();
+    assert_eq!(entries, expected_entries);
+
+    for (key, value) in expected {
+        assert_eq!(art.get(key), Some(value));
+    }
+}
+
+#[test]
+fn art_insert_get_remove() {
+    for (seed, last) in [(1, 2), (2, 19), (3, 255)] {
+        let keys = words(seed, 2000, 0..=7, 0..=last);
+        let mut art = ArtMap::new();
+        let mut expected = BTreeMap::new();
+
+        for (i, key) in keys.iter().enumerate() {
+            assert_eq!(art.insert(key, i).unwrap(), expected.insert(key.clone(), i));
+        }
+        check(&art, &expected);
+
+        for key in keys.iter().step_by(2) {
+            let removed = art
+                .remove(key, false)
+                .map(|m| m.iter().map(|(k, v)| (k, *v)).collect());
+            let want = expected.remove(key).map(|v| vec![(key.clone(), v)]);
+            assert_eq!(removed, want);
+            assert_eq!(art.get(key), None);
+        }
+        check(&art, &expected);
+
+        for key in &keys {
+            art.remove(key, false);
+        }
+        assert!(art.is_empty());
+        assert_eq!(art.iter().count(), 0);
+    }
+}
+
+#[test]
+fn art_node_growth() {
+    let mut art = ArtMap::new();
+
+    // one inner node walks through every layout and back again
+    for byte in 0..=u8::MAX {
+        art.insert(&[7, byte], byte as usize).unwrap();
+        assert_eq!(art.get(&[7, byte]), Some(&(byte as usize)));
+    }
+    art.insert(&[7], 1000).unwrap();
+
+    let expected: Vec<_> = (0..=u8::MAX).map(|b| vec![7, b]).collect();
+    let keys: Vec<_> = art.iter().skip(1).map(|(k, _)| k).collect();
+    assert_eq!(keys, expected);
+
+    for byte in (0..=u8::MAX).rev() {
+        assert!(art.remove(&[7, byte], false).is_some());
+        assert_eq!(art.get(&[7, byte]), None);
+        assert_eq!(art.len(), byte as usize + 1);
+
+        let remaining = (0..byte).all(|b| art.get(&[7, b]) == Some(&(b as usize)));
+        assert!(remaining);
+    }
+
+    assert_eq!(art.get(&[7]), Some(&1000));
+    assert_eq!(art.get(&[7, 0]), None);
+}
+
+#[test]
+fn art_prefix_keys() {
+    let mut art = ArtMap::new();
+
+    art.insert(b"", 0).unwrap();
+    art.insert(b"romane", 1).unwrap();
+    art.insert(b"romanus", 2).unwrap();
+    art.insert(b"romulus", 3).unwrap();
+    art.insert(b"rom", 4).unwrap();
+    art.insert(b"r", 5).unwrap();
+
+    assert_eq!(art.get(b""), Some(&0));
+    assert_eq!(art.get(b"rom"), Some(&4));
+    assert_eq!(art.get(b"roman"), None);
+    assert_eq!(art.get(b"romanes"), None);
+    assert_eq!(art[&b"romulus"[..]], 3);
+
+    *art.get_mut(b"romanus").unwrap() += 10;
+    assert_eq!(art.get(b"romanus"), Some(&12));
+    assert_eq!(art.get_mut(b"roma"), None);
+
+    assert!(art.contains_key(b"r"));
+    assert_eq!(art.remove(b"ro", false).map(|m| m.len()), None);
+
+    let pruned = art.remove(b"roma", true).unwrap();
+    let keys: Vec<_> = pruned.iter().map(|(k, v)| (k, *v)).collect();
+    assert_eq!(
+        keys,
+        vec![(b"romane".to_vec(), 1), (b"romanus".to_vec(), 12)]
+    );
+
+    let keys: Vec<_> = art.iter().map(|(k, _)| k).collect();
+    assert_eq!(
+        keys,
+        vec![
+            b"".to_vec(),
+            b"r".to_vec(),
+            b"rom".to_vec(),
+            b"romulus".to_vec()
+        ]
+    );
+    assert_eq!(art.len(), 4);
+
+    let pruned = art.remove(b"", true).unwrap();
+    assert_eq!(pruned.len(), 4);
+    assert!(art.is_empty());
+    assert_eq!(art.remove(b"", true).map(|m| m.len()), None);
+}
+
+#[test]
+fn art_prune_random() {
+    let keys = words(9, 3000, 0..=7, 0..=5);
+    let mut art: ArtMap<usize> = keys.iter().enumerate().map(|(i, k)| (k, i)).collect();
+    let mut expected: BTreeMap<_, _> = keys
+        .iter()
+        .enumerate()
+        .map(|(i, k)| (k.clone(), i))
+        .collect();
+
+    for prefix in [&[1, 2][..], &[0], &[5, 5, 5], &[3, 0, 1, 2]] {
+        let removed: BTreeMap<_, _> = expected
+            .iter()
+            .filter(|(k, _)| k.starts_with(prefix))
+            .map(|(k, v)| (k.clone(), *v))
+            .collect();
+        expected.retain(|k, _| !k.starts_with(prefix));
+
+        match art.remove(&prefix, true) {
+            Some(pruned) => check(&pruned, &removed),
+            None => assert!(removed.is_empty()),
+        }
+        check(&art, &expected);
+    }
+
+    let mut other = art.clone();
+    other.clear();
+    assert!(other.is_empty());
+    assert_eq!(art.len(), expected.len());
+}
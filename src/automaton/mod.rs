@@ -0,0 +1,282 @@
+#[cfg(test)]
+mod tests;
+
+mod regex;
+
+pub use regex::Regex;
+
+/// A matcher run along the keys of a trie one element at a time, as done by
+/// [`RadixNode::search`] and [`TrieNode::search`].
+///
+/// States are cloned wherever the trie branches, so keys sharing a prefix
+/// share the work of matching it, and a subtree is skipped as soon as
+/// [`Automaton::can_match`] rules out every key in it.
+///
+/// [`RadixNode::search`]: crate::radix::RadixNode::search
+/// [`TrieNode::search`]: crate::trie::TrieNode::search
+pub trait Automaton<K> {
+    type State: Clone;
+
+    /// State before reading any element.
+    fn start(&self) -> Self::State;
+
+    /// State reached from `state` by reading `elem`.
+    fn accept(&self, state: &Self::State, elem: &K) -> Self::State;
+
+    /// Returns `true` if the key read so far matches.
+    fn is_match(&self, state: &Self::State) -> bool;
+
+    /// Returns `false` if no key extending the one read so far can match.
+    fn can_match(&self, _state: &Self::State) -> bool {
+        true
+    }
+
+    /// Returns `true` if every key extending the one read so far matches.
+    fn will_always_match(&self, _state: &Self::State) -> bool {
+        false
+    }
+
+    /// Matches keys that either automaton matches.
+    fn union<B>(self, other: B) -> Union<Self, B>
+    where
+        Self: Sized,
+        B: Automaton<K>,
+    {
+        Union(self, other)
+    }
+
+    /// Matches keys that both automata match.
+    fn intersection<B>(self, other: B) -> Intersection<Self, B>
+    where
+        Self: Sized,
+        B: Automaton<K>,
+    {
+        Intersection(self, other)
+    }
+
+    /// Matches keys this automaton does not match.
+    fn complement(self) -> Complement<Self>
+    where
+        Self: Sized,
+    {
+        Complement(self)
+    }
+}
+
+impl<K, A> Automaton<K> for &A
+where
+    A: Automaton<K> + ?Sized,
+{
+    type State = A::State;
+
+    fn start(&self) -> Self::State {
+        (**self).start()
+    }
+
+    fn accept(&self, state: &Self::State, elem: &K) -> Self::State {
+        (**self).accept(state, elem)
+    }
+
+    fn is_match(&self, state: &Self::State) -> bool {
+        (**self).is_match(state)
+    }
+
+    fn can_match(&self, state: &Self::State) -> bool {
+        (**self).can_match(state)
+    }
+
+    fn will_always_match(&self, state: &Self::State) -> bool {
+        (**self).will_always_match(state)
+    }
+}
+
+/// Matches every key that starts with a given prefix.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Prefix<K> {
+    prefix: Vec<K>,
+}
+
+impl<K> Prefix<K>
+where
+    K: Clone,
+{
+    pub fn new(prefix: &[K]) -> Self {
+        Self {
+            prefix: prefix.to_vec(),
+        }
+    }
+}
+
+impl<K> Automaton<K> for Prefix<K>
+where
+    K: PartialEq,
+{
+    /// Elements of the prefix read so far, or `None` after a mismatch.
+    type State = Option<usize>;
+
+    fn start(&self) -> Self::State {
+        Some(0)
+    }
+
+    fn accept(&self, state: &Self::State, elem: &K) -> Self::State {
+        match *state {
+            Some(n) if n == self.prefix.len() => Some(n),
+            Some(n) if self.prefix[n] == *elem => Some(n + 1),
+            _ => None,
+        }
+    }
+
+    fn is_match(&self, state: &Self::State) -> bool {
+        *state == Some(self.prefix.len())
+    }
+
+    fn can_match(&self, state: &Self::State) -> bool {
+        state.is_some()
+    }
+
+    fn will_always_match(&self, state: &Self::State) -> bool {
+        self.is_match(state)
+    }
+}
+
+/// Matches every key that contains the given elements in order, not
+/// necessarily next to each other, such as `"tst"` in `"trie-test"`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Subsequence<K> {
+    elems: Vec<K>,
+}
+
+impl<K> Subsequence<K>
+where
+    K: Clone,
+{
+    pub fn new(elems: &[K]) -> Self {
+        Self {
+            elems: elems.to_vec(),
+        }
+    }
+}
+
+impl<K> Automaton<K> for Subsequence<K>
+where
+    K: PartialEq,
+{
+    /// Elements found so far.
+    type State = usize;
+
+    fn start(&self) -> Self::State {
+        0
+    }
+
+    fn accept(&self, &state: &Self::State, elem: &K) -> Self::State {
+        match self.elems.get(state) {
+            Some(next) if next == elem => state + 1,
+            _ => state,
+        }
+    }
+
+    fn is_match(&self, &state: &Self::State) -> bool {
+        state == self.elems.len()
+    }
+
+    fn will_always_match(&self, state: &Self::State) -> bool {
+        self.is_match(state)
+    }
+}
+
+/// Matches keys that either of two automata matches; see
+/// [`Automaton::union`].
+#[derive(Debug, Clone)]
+pub struct Union<A, B>(A, B);
+
+impl<K, A, B> Automaton<K> for Union<A, B>
+where
+    A: Automaton<K>,
+    B: Automaton<K>,
+{
+    type State = (A::State, B::State);
+
+    fn start(&self) -> Self::State {
+        (self.0.start(), self.1.start())
+    }
+
+    fn accept(&self, (a, b): &Self::State, elem: &K) -> Self::State {
+        (self.0.accept(a, elem), self.1.accept(b, elem))
+    }
+
+    fn is_match(&self, (a, b): &Self::State) -> bool {
+        self.0.is_match(a) || self.1.is_match(b)
+    }
+
+    fn can_match(&self, (a, b): &Self::State) -> bool {
+        self.0.can_match(a) || self.1.can_match(b)
+    }
+
+    fn will_always_match(&self, (a, b): &Self::State) -> bool {
+        self.0.will_always_match(a) || self.1.will_always_match(b)
+    }
+}
+
+/// Matches keys that both of two automata match; see
+/// [`Automaton::intersection`].
+#[derive(Debug, Clone)]
+pub struct Intersection<A, B>(A, B);
+
+impl<K, A, B> Automaton<K> for Intersection<A, B>
+where
+    A: Automaton<K>,
+    B: Automaton<K>,
+{
+    type State = (A::State, B::State);
+
+    fn start(&self) -> Self::State {
+        (self.0.start(), self.1.start())
+    }
+
+    fn accept(&self, (a, b): &Self::State, elem: &K) -> Self::State {
+        (self.0.accept(a, elem), self.1.accept(b, elem))
+    }
+
+    fn is_match(&self, (a, b): &Self::State) -> bool {
+        self.0.is_match(a) && self.1.is_match(b)
+    }
+
+    fn can_match(&self, (a, b): &Self::State) -> bool {
+        self.0.can_match(a) && self.1.can_match(b)
+    }
+
+    fn will_always_match(&self, (a, b): &Self::State) -> bool {
+        self.0.will_always_match(a) && self.1.will_always_match(b)
+    }
+}
+
+/// Matches keys an automaton does not match; see [`Automaton::complement`].
+#[derive(Debug, Clone)]
+pub struct Complement<A>(A);
+
+impl<K, A> Automaton<K> for Complement<A>
+where
+    A: Automaton<K>,
+{
+    type State = A::State;
+
+    fn start(&self) -> Self::State {
+        self.0.start()
+    }
+
+    fn accept(&self, state: &Self::State, elem: &K) -> Self::State {
+        self.0.accept(state, elem)
+    }
+
+    fn is_match(&self, state: &Self::State) -> bool {
+        !self.0.is_match(state)
+    }
+
+    fn can_match(&self, state: &Self::State) -> bool {
+        !self.0.will_always_match(state)
+    }
+
+    fn will_always_match(&self, state: &Self::State) -> bool {
+        !self.0.can_match(state)
+    }
+}
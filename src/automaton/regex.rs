@@ -0,0 +1,244 @@
+use super::Automaton;
+use crate::glob::ByteClass;
+use crate::TrieError;
+
+/// A regular expression over byte keys, matched against whole keys.
+///
+/// Supports literals, `.` for any byte, classes such as `[a-z_]` or `[^/]`,
+/// grouping with `(...)`, alternation with `|`, and the repetitions `*`, `+`
+/// and `?`. A `\` makes the next byte literal. There are no anchors, as a
+/// match always spans the whole key, and no counted repetitions.
+///
+/// The pattern is compiled to a Thompson NFA, and a state is the set of NFA
+/// instructions in play, so matching takes time linear in the key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Regex {
+    prog: Vec<Inst>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Inst {
+    Byte(u8),
+    Any,
+    Class(ByteClass),
+    /// Continue at both targets.
+    Split(usize, usize),
+    Jump(usize),
+    Match,
+}
+
+#[derive(Debug)]
+enum Ast {
+    Inst(Inst),
+    Concat(Vec<Ast>),
+    Alt(Box<Ast>, Box<Ast>),
+    Star(Box<Ast>),
+    Plus(Box<Ast>),
+    Opt(Box<Ast>),
+}
+
+impl Regex {
+    /// Compiles `pattern`, failing with [`TrieError::InvalidKey`] if it is
+    /// malformed, such as an unbalanced parenthesis, a repetition with
+    /// nothing to repeat or an unterminated class.
+    pub fn new(pattern: &str) -> Result<Self, TrieError> {
+        let mut parser = Parser {
+            pattern: pattern.as_bytes(),
+            pos: 0,
+        };
+
+        let ast = parser.alt()?;
+        if parser.pos < parser.pattern.len() {
+            return Err(TrieError::InvalidKey);
+        }
+
+        let mut prog = Vec::new();
+        compile(ast, &mut prog);
+        prog.push(Inst::Match);
+        Ok(Self { prog })
+    }
+
+    /// Returns `true` if the whole of `key` matches the pattern.
+    pub fn is_match(&self, key: &[u8]) -> bool {
+        let states = key.iter().try_fold(self.start(), |states, byte| {
+            let next = self.accept(&states, byte);
+            (!next.is_empty()).then_some(next)
+        });
+        states.is_some_and(|states| self.accepts(&states))
+    }
+
+    fn accepts(&self, states: &[usize]) -> bool {
+        states.last() == Some(&(self.prog.len() - 1))
+    }
+
+    /// Adds `pc` to the sorted set `states`, following splits and jumps.
+    fn enter(&self, states: &mut Vec<usize>, pc: usize) {
+        let idx = match states.binary_search(&pc) {
+            Ok(_) => return,
+            Err(idx) => idx,
+        };
+        states.insert(idx, pc);
+
+        match self.prog[pc] {
+            Inst::Split(a, b) => {
+                self.enter(states, a);
+                self.enter(states, b);
+            }
+            Inst::Jump(a) => self.enter(states, a),
+            _ => (),
+        }
+    }
+}
+
+impl Automaton<u8> for Regex {
+    /// Instructions in play, in increasing order.
+    type State = Vec<usize>;
+
+    fn start(&self) -> Self::State {
+        let mut states = Vec::new();
+        self.enter(&mut states, 0);
+        states
+    }
+
+    fn accept(&self, states: &Self::State, &byte: &u8) -> Self::State {
+        let mut next = Vec::new();
+
+        for &pc in states {
+            let matched = match &self.prog[pc] {
+                Inst::Byte(b) => *b == byte,
+                Inst::Any => true,
+                Inst::Class(class) => class.matches(byte),
+                _ => false,
+            };
+            if matched {
+                self.enter(&mut next, pc + 1);
+            }
+        }
+
+        next
+    }
+
+    fn is_match(&self, states: &Self::State) -> bool {
+        self.accepts(states)
+    }
+
+    fn can_match(&self, states: &Self::State) -> bool {
+        !states.is_empty()
+    }
+}
+
+/// Recursive descent parser for [`Regex`] patterns.
+struct Parser<'p> {
+    pattern: &'p [u8],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.pattern.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Result<u8, TrieError> {
+        let byte = self.peek().ok_or(TrieError::InvalidKey)?;
+        self.pos += 1;
+        Ok(byte)
+    }
+
+    fn alt(&mut self) -> Result<Ast, TrieError> {
+        let mut ast = self.concat()?;
+        while self.peek() == Some(b'|') {
+            self.pos += 1;
+            ast = Ast::Alt(Box::new(ast), Box::new(self.concat()?));
+        }
+        Ok(ast)
+    }
+
+    fn concat(&mut self) -> Result<Ast, TrieError> {
+        let mut items = Vec::new();
+        while let Some(byte) = self.peek() {
+            if byte == b'|' || byte == b')' {
+                break;
+            }
+            items.push(self.repeat()?);
+        }
+        Ok(Ast::Concat(items))
+    }
+
+    fn repeat(&mut self) -> Result<Ast, TrieError> {
+        let mut ast = self.atom()?;
+        loop {
+            ast = match self.peek() {
+                Some(b'*') => Ast::Star(Box::new(ast)),
+                Some(b'+') => Ast::Plus(Box::new(ast)),
+                Some(b'?') => Ast::Opt(Box::new(ast)),
+                _ => return Ok(ast),
+            };
+            self.pos += 1;
+        }
+    }
+
+    fn atom(&mut self) -> Result<Ast, TrieError> {
+        let inst = match self.next()? {
+            b'(' => {
+                let ast = self.alt()?;
+                if self.next()? != b')' {
+                    return Err(TrieError::InvalidKey);
+                }
+                return Ok(ast);
+            }
+            b')' | b'*' | b'+' | b'?' => return Err(TrieError::InvalidKey),
+            b'.' => Inst::Any,
+            b'[' => {
+                let (class, len) = ByteClass::parse(&self.pattern[self.pos..], b'^')?;
+                self.pos += len;
+                Inst::Class(class)
+            }
+            b'\\' => Inst::Byte(self.next()?),
+            byte => Inst::Byte(byte),
+        };
+        Ok(Ast::Inst(inst))
+    }
+}
+
+/// Appends the instructions for `ast` to `prog`; they fall through to
+/// whatever is appended next.
+fn compile(ast: Ast, prog: &mut Vec<Inst>) {
+    match ast {
+        Ast::Inst(inst) => prog.push(inst),
+        Ast::Concat(items) => {
+            for item in items {
+                compile(item, prog);
+            }
+        }
+        Ast::Alt(a, b) => {
+            let split = placeholder(prog);
+            compile(*a, prog);
+            let jump = placeholder(prog);
+            let second = prog.len();
+            compile(*b, prog);
+            prog[split] = Inst::Split(split + 1, second);
+            prog[jump] = Inst::Jump(prog.len());
+        }
+        Ast::Star(a) => {
+            let split = placeholder(prog);
+            compile(*a, prog);
+            prog.push(Inst::Jump(split));
+            prog[split] = Inst::Split(split + 1, prog.len());
+        }
+        Ast::Plus(a) => {
+            let start = prog.len();
+            compile(*a, prog);
+            prog.push(Inst::Split(start, prog.len() + 1));
+        }
+        Ast::Opt(a) => {
+            let split = placeholder(prog);
+            compile(*a, prog);
+            prog[split] = Inst::Split(split + 1, prog.len());
+        }
+    }
+}
+
+fn placeholder(prog: &mut Vec<Inst>) -> usize {
+    prog.push(Inst::Match);
+    prog.len() - 1
+}
@@ -0,0 +1,144 @@
+use crate::automaton::{Automaton, Prefix, Regex, Subsequence};
+use crate::glob::Glob;
+use crate::radix::RadixNode;
+use crate::test_util::words;
+use crate::trie::TrieNode;
+use crate::TrieError;
+
+/// Runs `aut` over a whole key without the trie.
+fn accepts<A: Automaton<u8>>(aut: &A, key: &[u8]) -> bool {
+    let state = key.iter().fold(aut.start(), |s, b| aut.accept(&s, b));
+    aut.is_match(&state)
+}
+
+/// Checks that searching both tries yields exactly the keys `keep` allows,
+/// in key order.
+fn check<A, F>(keys: &[Vec<u8>], aut: A, keep: F)
+where
+    A: Automaton<u8>,
+    F: Fn(&[u8]) -> bool,
+{
+    let radix: RadixNode<u8, usize> = keys.iter().zip(0..).collect();
+    let trie: TrieNode<u8, usize> = keys.iter().zip(0..).collect();
+
+    let expected: Vec<_> = radix.iter().filter(|(k, _)| keep(k)).collect();
+    assert_eq!(radix.search(&aut).collect::<Vec<_>>(), expected);
+    assert_eq!(trie.search(&aut).collect::<Vec<_>>(), expected);
+    for (key, _) in radix.iter() {
+        assert_eq!(accepts(&aut, &key), keep(&key), "{key:?}");
+    }
+}
+
+fn is_subsequence(needle: &[u8], key: &[u8]) -> bool {
+    let mut rest = key.iter();
+    needle.iter().all(|b| rest.any(|k| k == b))
+}
+
+#[test]
+fn automaton_builtin() {
+    let keys = words(7, 500, 0..=15, b'a'..=b'c');
+
+    for prefix in [&b""[..], b"a", b"ab", b"cab", b"abcabc"] {
+        check(&keys, Prefix::new(prefix), |k| k.starts_with(prefix));
+    }
+    for needle in [&b""[..], b"c", b"aa", b"bcb", b"cccc"] {
+        check(&keys, Subsequence::new(needle), |k| {
+            is_subsequence(needle, k)
+        });
+    }
+
+    let names: TrieNode<char, ()> = ["trie-test", "tree", "toast", "test"]
+        .iter()
+        .map(|w| (w.chars().collect::<Vec<_>>(), ()))
+        .collect();
+    let found: Vec<String> = names
+        .search(Subsequence::new(&['t', 's', 't']))
+        .map(|(k, _)| k.into_iter().collect())
+        .collect();
+    assert_eq!(found, ["test", "toast", "trie-test"]);
+}
+
+/// Plain predicate a pattern is checked against.
+type Naive = fn(&[u8]) -> bool;
+
+/// Naive matcher for `(a|bc)*c`.
+fn a_or_bc_then_c(key: &[u8]) -> bool {
+    match key {
+        [b'c'] => true,
+        [b'a', rest @ ..] | [b'b', b'c', rest @ ..] => a_or_bc_then_c(rest),
+        _ => false,
+    }
+}
+
+#[test]
+fn automaton_regex() {
+    let keys = words(11, 500, 0..=15, b'a'..=b'c');
+
+    let cases: [(&str, Naive); 8] = [
+        ("", |k| k.is_empty()),
+        ("a*", |k| k.iter().all(|&b| b == b'a')),
+        ("(ab)+", |k| {
+            !k.is_empty() && k.len() % 2 == 0 && k.chunks(2).all(|c| c == b"ab")
+        }),
+        ("a.?c", |k| matches!(k, [b'a', b'c'] | [b'a', _, b'c'])),
+        ("[^a]*", |k| !k.contains(&b'a')),
+        ("(a|bc)*c", a_or_bc_then_c),
+        ("(a*)*b", |k| {
+            k.last() == Some(&b'b') && k[..k.len() - 1].iter().all(|&b| b == b'a')
+        }),
+        ("[a-b]+c?|c", |k| {
+            let body = k.strip_suffix(b"c").unwrap_or(k);
+            k == b"c" || (!body.is_empty() && body.iter().all(|&b| b != b'c'))
+        }),
+    ];
+    for (pattern, keep) in cases {
+        let regex = Regex::new(pattern).unwrap();
+        check(&keys, &regex, keep);
+        for (key, _) in keys.iter().zip(0..) {
+            assert_eq!(regex.is_match(key), keep(key), "{pattern} {key:?}");
+        }
+    }
+
+    // globs have a regex equivalent
+    for (glob, regex) in [
+        ("*", ".*"),
+        ("a?c*", "a.c.*"),
+        ("*ab*[!c]", ".*ab.*[^c]"),
+        ("[bc]*a", "[bc].*a"),
+    ] {
+        let glob = Glob::new(glob).unwrap();
+        let regex = Regex::new(regex).unwrap();
+        check(&keys, &glob, |k| regex.is_match(k));
+    }
+
+    assert!(Regex::new(r"a\*").unwrap().is_match(b"a*"));
+    assert!(!Regex::new(r"a\*").unwrap().is_match(b"aa"));
+    for bad in ["(a", "a)", "*a", "a|+", "[ab", r"a\", "a(*)"] {
+        assert_eq!(Regex::new(bad), Err(TrieError::InvalidKey), "{bad}");
+    }
+}
+
+#[test]
+fn automaton_combinators() {
+    let keys = words(13, 500, 0..=15, b'a'..=b'c');
+    let prefix = || Prefix::new(b"ab");
+    let regex = || Regex::new(".*c").unwrap();
+
+    check(&keys, prefix().union(regex()), |k| {
+        k.starts_with(b"ab") || k.ends_with(b"c")
+    });
+    check(&keys, prefix().intersection(regex()), |k| {
+        k.starts_with(b"ab") && k.ends_with(b"c")
+    });
+    check(&keys, prefix().complement(), |k| !k.starts_with(b"ab"));
+    check(&keys, regex().complement(), |k| !k.ends_with(b"c"));
+    check(
+        &keys,
+        Subsequence::new(b"cc").intersection(prefix().complement()),
+        |k| is_subsequence(b"cc", k) && !k.starts_with(b"ab"),
+    );
+
+    // the complement of a match-all prefix prunes everything
+    let radix: RadixNode<u8, usize> = keys.iter().zip(0..).collect();
+    assert_eq!(radix.search(Prefix::new(b"").complement()).count(), 0);
+}
@@ -0,0 +1,231 @@
+#[cfg(test)]
+mod tests;
+
+use std::fmt;
+use std::io;
+
+/// Converts values to and from the bytes stored in the binary trie formats.
+pub trait ValueCodec<V> {
+    fn encode(&self, value: &V, out: &mut Vec<u8>);
+
+    fn decode(&self, bytes: &[u8]) -> Result<V, FormatError>;
+}
+
+/// Stores nothing, for tries used as sets.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UnitCodec;
+
+/// Stores byte vectors as they are.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BytesCodec;
+
+/// Stores strings as UTF-8, rejecting invalid bytes when decoding.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StrCodec;
+
+/// Stores integers as fixed-width little-endian bytes.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LeCodec;
+
+impl ValueCodec<()> for UnitCodec {
+    fn encode(&self, _: &(), _: &mut Vec<u8>) {}
+
+    fn decode(&self, bytes: &[u8]) -> Result<(), FormatError> {
+        match bytes.is_empty() {
+            true => Ok(()),
+            false => Err(FormatError::InvalidValue),
+        }
+    }
+}
+
+impl ValueCodec<Vec<u8>> for BytesCodec {
+    fn encode(&self, value: &Vec<u8>, out: &mut Vec<u8>) {
+        out.extend_from_slice(value);
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<Vec<u8>, FormatError> {
+        Ok(bytes.to_vec())
+    }
+}
+
+impl ValueCodec<String> for StrCodec {
+    fn encode(&self, value: &String, out: &mut Vec<u8>) {
+        out.extend_from_slice(value.as_bytes());
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<String, FormatError> {
+        String::from_utf8(bytes.to_vec()).map_err(|_| FormatError::InvalidValue)
+    }
+}
+
+macro_rules! le_codec {
+    ($($ty:ty),*) => {
+        $(
+            impl ValueCodec<$ty> for LeCodec {
+                fn encode(&self, value: &$ty, out: &mut Vec<u8>) {
+                    out.extend_from_slice(&value.to_le_bytes());
+                }
+
+                fn decode(&self, bytes: &[u8]) -> Result<$ty, FormatError> {
+                    let bytes = bytes.try_into().map_err(|_| FormatError::InvalidValue)?;
+                    Ok(<$ty>::from_le_bytes(bytes))
+                }
+            }
+        )*
+    };
+}
+
+le_codec!(u8, u16, u32, u64, i8, i16, i32, i64);
+
+#[derive(Debug)]
+pub enum FormatError {
+    Io(io::Error),
+    /// The input does not start with the expected magic bytes.
+    BadMagic,
+    UnsupportedVersion(u16),
+    /// The payload does not match the checksum in the header.
+    ChecksumMismatch {
+        expected: u32,
+        actual: u32,
+    },
+    /// The input ended before the length given in the header.
+    Truncated,
+    /// The payload is well-formed bytes but not a valid trie.
+    Corrupt(&'static str),
+    /// The value codec rejected the stored bytes.
+    InvalidValue,
+    /// The trie does not fit in the offsets used by the format.
+    TooLarge,
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "i/o error: {}", err),
+            Self::BadMagic => f.write_str("not a trie file"),
+            Self::UnsupportedVersion(v) => write!(f, "unsupported format version {}", v),
+            Self::ChecksumMismatch { expected, actual } => write!(
+                f,
+                "checksum mismatch: expected {:08x}, found {:08x}",
+                expected, actual
+            ),
+            Self::Truncated => f.write_str("unexpected end of input"),
+            Self::Corrupt(reason) => write!(f, "corrupt trie data: {}", reason),
+            Self::InvalidValue => f.write_str("invalid value encoding"),
+            Self::TooLarge => f.write_str("trie too large for the format"),
+        }
+    }
+}
+
+impl std::error::Error for FormatError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for FormatError {
+    fn from(err: io::Error) -> Self {
+        match err.kind() {
+            io::ErrorKind::UnexpectedEof => Self::Truncated,
+            _ => Self::Io(err),
+        }
+    }
+}
+
+const CRC_TABLE: [u32; 256] = crc_table();
+
+const fn crc_table() -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = match crc & 1 {
+                1 => (crc >> 1) ^ 0xedb8_8320,
+                _ => crc >> 1,
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+/// CRC-32 (IEEE) of `bytes`.
+pub(crate) fn crc32(bytes: &[u8]) -> u32 {
+    let crc = bytes.iter().fold(!0u32, |crc, &b| {
+        CRC_TABLE[((crc ^ b as u32) & 0xff) as usize] ^ (crc >> 8)
+    });
+    !crc
+}
+
+pub(crate) fn write_varint(out: &mut Vec<u8>, mut n: u64) {
+    while n >= 0x80 {
+        out.push(n as u8 | 0x80);
+        n >>= 7;
+    }
+    out.push(n as u8);
+}
+
+/// Bounds-checked reader over a decoded payload.
+pub(crate) struct Cursor<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Cursor<'a> {
+    pub(crate) fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    pub(crate) fn remaining(&self) -> usize {
+        self.bytes.len()
+    }
+
+    pub(crate) fn byte(&mut self) -> Result<u8, FormatError> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub(crate) fn take(&mut self, n: usize) -> Result<&'a [u8], FormatError> {
+        if n > self.bytes.len() {
+            return Err(FormatError::Corrupt(
+                "length runs past the end of the payload",
+            ));
+        }
+        let (head, tail) = self.bytes.split_at(n);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    pub(crate) fn varint(&mut self) -> Result<u64, FormatError> {
+        let mut n = 0u64;
+        for shift in (0..64).step_by(7) {
+            let b = self.byte()?;
+            n |= ((b & 0x7f) as u64) << shift;
+            if b & 0x80 == 0 {
+                return Ok(n);
+            }
+        }
+        Err(FormatError::Corrupt("varint too long"))
+    }
+
+    /// Reads a varint that counts or sizes something in the payload, which
+    /// can never exceed the bytes left to read.
+    pub(crate) fn length(&mut self) -> Result<usize, FormatError> {
+        let n = self.varint()?;
+        match n <= self.remaining() as u64 {
+            true => Ok(n as usize),
+            false => Err(FormatError::Corrupt(
+                "length runs past the end of the payload",
+            )),
+        }
+    }
+}
@@ -0,0 +1,37 @@
+use crate::codec::{crc32, write_varint, Cursor, LeCodec, StrCodec, ValueCodec};
+
+#[test]
+fn crc32_check_value() {
+    assert_eq!(crc32(b""), 0);
+    assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+}
+
+#[test]
+fn varint_roundtrip() {
+    let mut out = Vec::new();
+    for n in [0, 1, 127, 128, 300, u32::MAX as u64, u64::MAX] {
+        write_varint(&mut out, n);
+    }
+
+    let mut cursor = Cursor::new(&out);
+    for n in [0, 1, 127, 128, 300, u32::MAX as u64, u64::MAX] {
+        assert_eq!(cursor.varint().unwrap(), n);
+    }
+    assert!(cursor.is_empty());
+    assert!(cursor.varint().is_err());
+}
+
+#[test]
+fn value_codecs() {
+    let mut out = Vec::new();
+    LeCodec.encode(&0x0102_0304u32, &mut out);
+    assert_eq!(out, [4, 3, 2, 1]);
+    assert_eq!(
+        ValueCodec::<u32>::decode(&LeCodec, &out).unwrap(),
+        0x0102_0304
+    );
+    assert!(ValueCodec::<u64>::decode(&LeCodec, &out).is_err());
+
+    assert!(StrCodec.decode(&[0xff]).is_err());
+    assert_eq!(StrCodec.decode(b"ok").unwrap(), "ok");
+}
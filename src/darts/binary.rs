@@ -0,0 +1,144 @@
+use super::{DoubleArrayTrie, EMPTY, ROOT};
+use crate::codec::{crc32, write_varint, Cursor, FormatError, ValueCodec};
+use std::io::{Read, Write};
+
+const MAGIC: [u8; 4] = *b"TRDA";
+const VERSION: u16 = 1;
+const HEADER_LEN: usize = 20;
+
+impl<V> DoubleArrayTrie<V> {
+    /// Writes the compiled arrays and the values, encoded with `codec`, so
+    /// [`DoubleArrayTrie::read_from`] can load them without rebuilding.
+    ///
+    /// The output is a 20 byte header, laid out like that of
+    /// [`RadixNode::write_to`](crate::radix::RadixNode::write_to) but with
+    /// magic `TRDA`, followed by the payload: the unit count as a
+    /// little-endian `u32`, the `BASE` and `CHECK` arrays as little-endian
+    /// `u32`s, then the value count as a LEB128 varint and each value as its
+    /// length and encoded bytes, in key order.
+    pub fn write_to<W, C>(&self, mut writer: W, codec: &C) -> Result<(), FormatError>
+    where
+        W: Write,
+        C: ValueCodec<V>,
+    {
+        let mut payload = Vec::with_capacity(4 + self.base.len() * 8);
+        payload.extend_from_slice(&(self.base.len() as u32).to_le_bytes());
+        for unit in self.base.iter().chain(&self.check) {
+            payload.extend_from_slice(&unit.to_le_bytes());
+        }
+
+        write_varint(&mut payload, self.values.len() as u64);
+        let mut value = Vec::new();
+        for v in &self.values {
+            value.clear();
+            codec.encode(v, &mut value);
+            write_varint(&mut payload, value.len() as u64);
+            payload.extend_from_slice(&value);
+        }
+
+        let mut header = Vec::with_capacity(HEADER_LEN);
+        header.extend_from_slice(&MAGIC);
+        header.extend_from_slice(&VERSION.to_le_bytes());
+        header.extend_from_slice(&0u16.to_le_bytes());
+        header.extend_from_slice(&(payload.len() as u64).to_le_bytes());
+        header.extend_from_slice(&crc32(&payload).to_le_bytes());
+
+        writer.write_all(&header)?;
+        writer.write_all(&payload)?;
+        Ok(())
+    }
+
+    /// Reads a trie written by [`DoubleArrayTrie::write_to`], checking the
+    /// header, checksum and arrays before returning it.
+    pub fn read_from<R, C>(mut reader: R, codec: &C) -> Result<Self, FormatError>
+    where
+        R: Read,
+        C: ValueCodec<V>,
+    {
+        let mut header = [0u8; HEADER_LEN];
+        reader.read_exact(&mut header)?;
+
+        if header[0..4] != MAGIC {
+            return Err(FormatError::BadMagic);
+        }
+
+        let version = u16::from_le_bytes([header[4], header[5]]);
+        if version != VERSION {
+            return Err(FormatError::UnsupportedVersion(version));
+        }
+
+        if header[6..8] != [0, 0] {
+            return Err(FormatError::Corrupt("unknown header flags"));
+        }
+
+        let len = u64::from_le_bytes(header[8..16].try_into().unwrap());
+        let expected = u32::from_le_bytes(header[16..20].try_into().unwrap());
+
+        // read through `take` so a corrupt length cannot force a huge allocation
+        let mut payload = Vec::new();
+        reader.take(len).read_to_end(&mut payload)?;
+        if payload.len() as u64 != len {
+            return Err(FormatError::Truncated);
+        }
+
+        let actual = crc32(&payload);
+        if actual != expected {
+            return Err(FormatError::ChecksumMismatch { expected, actual });
+        }
+
+        Self::decode(&payload, codec)
+    }
+
+    fn decode<C>(payload: &[u8], codec: &C) -> Result<Self, FormatError>
+    where
+        C: ValueCodec<V>,
+    {
+        let mut cursor = Cursor::new(payload);
+
+        let units = u32::from_le_bytes(cursor.take(4)?.try_into().unwrap()) as usize;
+        let arrays = units
+            .checked_mul(8)
+            .filter(|&n| n <= cursor.remaining())
+            .ok_or(FormatError::Corrupt(
+                "length runs past the end of the payload",
+            ))?;
+        let words: Vec<u32> = cursor
+            .take(arrays)?
+            .chunks_exact(4)
+            .map(|w| u32::from_le_bytes(w.try_into().unwrap()))
+            .collect();
+        let (base, check) = words.split_at(units);
+
+        let count = cursor.length()?;
+        let mut values = Vec::with_capacity(count);
+        for _ in 0..count {
+            let len = cursor.length()?;
+            values.push(codec.decode(cursor.take(len)?)?);
+        }
+        if !cursor.is_empty() {
+            return Err(FormatError::Corrupt("trailing bytes after the values"));
+        }
+
+        if check.first() != Some(&EMPTY) {
+            return Err(FormatError::Corrupt("missing root state"));
+        }
+        for (unit, &owner) in check.iter().enumerate() {
+            if owner == EMPTY {
+                continue;
+            }
+            if owner as usize >= units || unit == ROOT as usize {
+                return Err(FormatError::Corrupt("check entry out of range"));
+            }
+            // a unit its owner reaches on code 0 ends a key
+            if base[owner as usize] as usize == unit && base[unit] as usize >= count {
+                return Err(FormatError::Corrupt("value index out of range"));
+            }
+        }
+
+        Ok(Self {
+            base: base.to_vec(),
+            check: check.to_vec(),
+            values,
+        })
+    }
+}
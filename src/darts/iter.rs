@@ -0,0 +1,44 @@
+use super::{code, DoubleArrayTrie, ROOT};
+
+/// Iterator over the keys that are prefixes of a text, shortest first,
+/// returned by [`DoubleArrayTrie::common_prefix_search`].
+pub struct PrefixIter<'a, 't, V> {
+    trie: &'a DoubleArrayTrie<V>,
+    text: &'t [u8],
+    /// Bytes of the text read so far.
+    pos: usize,
+    /// State after reading them, or `None` once the walk left the trie.
+    state: Option<u32>,
+}
+
+impl<'a, 't, V> PrefixIter<'a, 't, V> {
+    pub(crate) fn new(trie: &'a DoubleArrayTrie<V>, text: &'t [u8]) -> Self {
+        Self {
+            trie,
+            text,
+            pos: 0,
+            state: Some(ROOT),
+        }
+    }
+}
+
+impl<'a, V> Iterator for PrefixIter<'a, '_, V> {
+    type Item = (usize, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(state) = self.state {
+            let len = self.pos;
+            let value = self.trie.value(state);
+
+            self.state = self.text.get(self.pos).and_then(|&byte| {
+                self.pos += 1;
+                self.trie.step(state, code(byte))
+            });
+
+            if let Some(value) = value {
+                return Some((len, value));
+            }
+        }
+        None
+    }
+}
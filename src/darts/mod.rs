@@ -0,0 +1,227 @@
+#[cfg(test)]
+mod tests;
+
+mod binary;
+mod iter;
+
+pub use iter::PrefixIter;
+
+use crate::trie::TrieNode;
+use crate::TrieError;
+use std::ops::Range;
+
+/// Marks a unit no state owns.
+const EMPTY: u32 = u32::MAX;
+
+/// Largest number of units addressable by a `u32` index other than [`EMPTY`].
+const MAX_UNITS: usize = u32::MAX as usize;
+
+/// The root state, where every walk starts.
+const ROOT: u32 = 0;
+
+/// An immutable trie over bytes compiled into a double array, where each
+/// step along a key is two array reads.
+///
+/// A state `s` moves on byte `b` to `t = BASE[s] + b + 1` whenever
+/// `CHECK[t] == s`; the `CHECK` entry tells the owner of each unit apart, so
+/// the children of every state can be interleaved with those of others in
+/// the same two arrays. Code 0 marks the end of a key: the unit it leads to
+/// holds the index of the key's value in `BASE`.
+///
+/// Compilation fits the children of each state into the first free units
+/// that take them all, so the arrays stay densely packed. The result can be
+/// written out with [`DoubleArrayTrie::write_to`] and loaded back without
+/// being rebuilt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DoubleArrayTrie<V> {
+    base: Vec<u32>,
+    check: Vec<u32>,
+    values: Vec<V>,
+}
+
+fn code(byte: u8) -> usize {
+    byte as usize + 1
+}
+
+impl<V> DoubleArrayTrie<V> {
+    /// Compiles `entries`, whose keys must be strictly increasing.
+    ///
+    /// Fails with [`TrieError::InvalidKey`] on a key out of order or given
+    /// twice, and with [`TrieError::CapacityExceeded`] if the arrays would
+    /// outgrow `u32` indices.
+    pub fn from_sorted<I, Q>(entries: I) -> Result<Self, TrieError>
+    where
+        I: IntoIterator<Item = (Q, V)>,
+        Q: AsRef<[u8]>,
+    {
+        let mut keys: Vec<Vec<u8>> = Vec::new();
+        let mut values = Vec::new();
+
+        for (key, value) in entries {
+            let key = key.as_ref();
+            if keys.last().is_some_and(|last| key <= &last[..]) {
+                return Err(TrieError::InvalidKey);
+            }
+            keys.push(key.to_vec());
+            values.push(value);
+        }
+        if values.len() > MAX_UNITS {
+            return Err(TrieError::CapacityExceeded);
+        }
+
+        let mut trie = Self {
+            base: vec![0],
+            check: vec![EMPTY],
+            values,
+        };
+        trie.build(&keys)?;
+        Ok(trie)
+    }
+
+    /// Returns the number of keys.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Returns the length of the `BASE` and `CHECK` arrays.
+    pub fn units(&self) -> usize {
+        self.base.len()
+    }
+
+    pub fn get(&self, key: &[u8]) -> Option<&V> {
+        let state = key
+            .iter()
+            .try_fold(ROOT, |state, &byte| self.step(state, code(byte)))?;
+        self.value(state)
+    }
+
+    pub fn contains_key(&self, key: &[u8]) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Iterates over every key that is a prefix of `text`, shortest first,
+    /// yielding its length and value: all the dictionary words starting at
+    /// the beginning of `text`.
+    pub fn common_prefix_search<'a, 't>(&'a self, text: &'t [u8]) -> PrefixIter<'a, 't, V> {
+        PrefixIter::new(self, text)
+    }
+
+    /// Moves from `state` on `code`, if that transition exists.
+    fn step(&self, state: u32, code: usize) -> Option<u32> {
+        let next = self.base[state as usize] as usize + code;
+        match self.check.get(next) == Some(&state) {
+            true => Some(next as u32),
+            false => None,
+        }
+    }
+
+    /// Returns the value of the key ending in `state`.
+    fn value(&self, state: u32) -> Option<&V> {
+        let end = self.step(state, 0)?;
+        self.values.get(self.base[end as usize] as usize)
+    }
+
+    /// Lays out the states of the trie of `keys` depth first, each state's
+    /// children placed as soon as it is.
+    fn build(&mut self, keys: &[Vec<u8>]) -> Result<(), TrieError> {
+        // lowest unit that may still be free
+        let mut free = 1;
+        // state, the keys below it and their depth
+        let mut stack = vec![(ROOT, 0..keys.len(), 0)];
+
+        while let Some((state, range, depth)) = stack.pop() {
+            let children = children(keys, range, depth);
+            if children.is_empty() {
+                continue;
+            }
+
+            let base = self.find_base(&children, free)?;
+            self.base[state as usize] = base as u32;
+
+            for (code, range) in children {
+                let unit = base + code;
+                self.check[unit] = state;
+                match code {
+                    // keys are numbered in order, like their values
+                    0 => self.base[unit] = range.start as u32,
+                    _ => stack.push((unit as u32, range, depth + 1)),
+                }
+            }
+
+            while self.check.get(free).is_some_and(|&c| c != EMPTY) {
+                free += 1;
+            }
+        }
+
+        let used = self.check.iter().rposition(|&c| c != EMPTY).unwrap_or(0) + 1;
+        self.base.truncate(used);
+        self.check.truncate(used);
+        self.base.shrink_to_fit();
+        self.check.shrink_to_fit();
+        Ok(())
+    }
+
+    /// Finds the lowest base at or after `free` where every code in
+    /// `children` lands on a free unit, growing the arrays to fit it.
+    fn find_base(
+        &mut self,
+        children: &[(usize, Range<usize>)],
+        free: usize,
+    ) -> Result<usize, TrieError> {
+        let first = children[0].0;
+        let last = children[children.len() - 1].0;
+        let is_free =
+            |check: &[u32], unit: usize| check.get(unit).copied().unwrap_or(EMPTY) == EMPTY;
+
+        let mut unit = free.max(first + 1);
+        let base = loop {
+            if is_free(&self.check, unit) {
+                let base = unit - first;
+                if children
+                    .iter()
+                    .all(|&(code, _)| is_free(&self.check, base + code))
+                {
+                    break base;
+                }
+            }
+            unit += 1;
+        };
+
+        let len = base + last + 1;
+        if len > MAX_UNITS {
+            return Err(TrieError::CapacityExceeded);
+        }
+        if len > self.check.len() {
+            self.base.resize(len, 0);
+            self.check.resize(len, EMPTY);
+        }
+        Ok(base)
+    }
+}
+
+/// Groups `keys[range]` by their code at `depth`, where code 0 is a key
+/// ending there, which sorts first.
+fn children(keys: &[Vec<u8>], range: Range<usize>, depth: usize) -> Vec<(usize, Range<usize>)> {
+    let code_at = |i: usize| keys[i].get(depth).map_or(0, |&b| code(b));
+    let mut children: Vec<(usize, Range<usize>)> = Vec::new();
+
+    for i in range {
+        match children.last_mut() {
+            Some((code, range)) if *code == code_at(i) => range.end = i + 1,
+            _ => children.push((code_at(i), i..i + 1)),
+        }
+    }
+    children
+}
+
+impl<V> TryFrom<TrieNode<u8, V>> for DoubleArrayTrie<V> {
+    type Error = TrieError;
+
+    fn try_from(trie: TrieNode<u8, V>) -> Result<Self, Self::Error> {
+        Self::from_sorted(trie)
+    }
+}
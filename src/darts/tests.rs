@@ -0,0 +1,129 @@
+use crate::codec::{FormatError, LeCodec};
+use crate::darts::DoubleArrayTrie;
+use crate::test_util::{words, Lcg};
+use crate::trie::TrieNode;
+use crate::TrieError;
+use std::collections::BTreeMap;
+
+fn build(seed: u64, n: usize) -> (DoubleArrayTrie<u32>, BTreeMap<Vec<u8>, u32>) {
+    let map: BTreeMap<Vec<u8>, u32> = words(seed, n, 0..=15, b'a'..=b'c')
+        .into_iter()
+        .zip(0..)
+        .collect();
+    let trie = DoubleArrayTrie::from_sorted(map.clone()).unwrap();
+    (trie, map)
+}
+
+#[test]
+fn double_array() {
+    let dict = ["", "a", "ab", "abc", "b", "bcd", "\u{ff}"];
+    let trie = DoubleArrayTrie::from_sorted(dict.iter().map(|k| (k, k.len()))).unwrap();
+
+    assert_eq!(trie.len(), 7);
+    assert_eq!(trie.get(b"abc"), Some(&3));
+    assert_eq!(trie.get(b""), Some(&0));
+    assert_eq!(trie.get("\u{ff}".as_bytes()), Some(&2));
+    assert_eq!(trie.get(b"bc"), None);
+    assert_eq!(trie.get(b"abcd"), None);
+    assert!(!trie.contains_key(b"c"));
+
+    let found: Vec<_> = trie.common_prefix_search(b"abcde").collect();
+    assert_eq!(found, [(0, &0), (1, &1), (2, &2), (3, &3)]);
+    let found: Vec<_> = trie.common_prefix_search(b"bc").collect();
+    assert_eq!(found, [(0, &0), (1, &1)]);
+
+    assert_eq!(
+        DoubleArrayTrie::from_sorted([("b", 1), ("a", 2)]),
+        Err(TrieError::InvalidKey)
+    );
+    assert_eq!(
+        DoubleArrayTrie::from_sorted([("a", 1), ("a", 2)]),
+        Err(TrieError::InvalidKey)
+    );
+
+    let empty = DoubleArrayTrie::<()>::from_sorted(Vec::<(&[u8], ())>::new()).unwrap();
+    assert!(empty.is_empty());
+    assert_eq!(empty.get(b""), None);
+    assert_eq!(empty.common_prefix_search(b"abc").count(), 0);
+}
+
+#[test]
+fn double_array_random() {
+    for seed in 0..10 {
+        let (trie, map) = build(seed, 500);
+        assert_eq!(trie.len(), map.len());
+
+        let node: TrieNode<u8, u32> = map.iter().map(|(k, &v)| (k, v)).collect();
+        let node_count = node.stats().nodes;
+        assert_eq!(DoubleArrayTrie::try_from(node), Ok(trie.clone()));
+
+        for (key, value) in &map {
+            assert_eq!(trie.get(key), Some(value));
+        }
+        for probe in words(seed + 100, 200, 0..=15, b'a'..=b'c') {
+            assert_eq!(trie.get(&probe), map.get(&probe));
+
+            let naive: Vec<_> = (0..=probe.len())
+                .filter_map(|n| map.get(&probe[..n]).map(|v| (n, v)))
+                .collect();
+            assert_eq!(trie.common_prefix_search(&probe).collect::<Vec<_>>(), naive);
+        }
+
+        // every node and key end takes a unit, and children of different
+        // states interleave to leave few gaps
+        let used = node_count + trie.len();
+        assert!(trie.units() >= used);
+        assert!(
+            trie.units() < used + used / 10 + 300,
+            "{} {}",
+            trie.units(),
+            used
+        );
+    }
+}
+
+#[test]
+fn double_array_binary_format() {
+    let (trie, map) = build(4, 400);
+
+    let mut bytes = Vec::new();
+    trie.write_to(&mut bytes, &LeCodec).unwrap();
+    assert_eq!(&bytes[0..4], b"TRDA");
+
+    let read = |bytes: &[u8]| DoubleArrayTrie::<u32>::read_from(bytes, &LeCodec);
+    assert_eq!(read(&bytes).unwrap(), trie);
+
+    assert!(matches!(read(&bytes[..10]), Err(FormatError::Truncated)));
+    assert!(matches!(
+        read(&bytes[..bytes.len() - 1]),
+        Err(FormatError::Truncated)
+    ));
+    assert!(matches!(
+        read(b"not a trie at all..."),
+        Err(FormatError::BadMagic)
+    ));
+
+    let mut bad = bytes.clone();
+    let last = bad.len() - 1;
+    bad[last] ^= 0xff;
+    assert!(matches!(
+        read(&bad),
+        Err(FormatError::ChecksumMismatch { .. })
+    ));
+
+    // corrupt payloads with a valid checksum must fail cleanly, never panic
+    let mut rng = Lcg::new(7);
+    for _ in 0..2000 {
+        let mut bad = bytes.clone();
+        let pos = rng.range(20..=bad.len() as u64 - 1) as usize;
+        bad[pos] = rng.next() as u8;
+        let crc = crate::codec::crc32(&bad[20..]);
+        bad[16..20].copy_from_slice(&crc.to_le_bytes());
+        if let Ok(trie) = read(&bad) {
+            for key in map.keys() {
+                let _ = trie.get(key);
+                let _ = trie.common_prefix_search(key).count();
+            }
+        }
+    }
+}
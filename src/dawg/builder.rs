@@ -0,0 +1,171 @@
+use super::{Dawg, State};
+use crate::TrieError;
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::mem;
+
+/// Largest number of states addressable by a `u32` index.
+const MAX_STATES: usize = u32::MAX as usize;
+
+/// Builds a [`Dawg`] from keys given in sorted order, minimizing as it goes
+/// (Daciuk et al., 2000).
+///
+/// Only the states along the last key inserted are still open. Once the next
+/// key leaves that path, the states below the fork can no longer gain
+/// transitions, so each is replaced by an equivalent one already in the
+/// graph, found by its signature in a register, or added to it. Memory use
+/// thus stays proportional to the minimal graph rather than to the trie.
+#[derive(Debug, Clone)]
+pub struct DawgBuilder<K> {
+    states: Vec<State>,
+    edges: Vec<(K, u32)>,
+    /// Finished states by signature: finality and outgoing transitions.
+    register: HashMap<(bool, Vec<(K, u32)>), u32>,
+    root: Open<K>,
+    /// States along the last key below the root. The last transition of the
+    /// root and of each open state leads to the next one, and its target is
+    /// set once that one is done.
+    open: Vec<Open<K>>,
+    last: Vec<K>,
+    len: usize,
+}
+
+#[derive(Debug, Clone)]
+struct Open<K> {
+    is_final: bool,
+    edges: Vec<(K, u32)>,
+}
+
+impl<K> Open<K> {
+    fn new() -> Self {
+        Self {
+            is_final: false,
+            edges: Vec::new(),
+        }
+    }
+}
+
+impl<K> Default for DawgBuilder<K>
+where
+    K: Clone + Ord + Hash,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K> DawgBuilder<K>
+where
+    K: Clone + Ord + Hash,
+{
+    pub fn new() -> Self {
+        Self {
+            states: Vec::new(),
+            edges: Vec::new(),
+            register: HashMap::new(),
+            root: Open::new(),
+            open: Vec::new(),
+            last: Vec::new(),
+            len: 0,
+        }
+    }
+
+    /// Adds `key`, which must sort strictly after every key added before.
+    ///
+    /// Fails with [`TrieError::InvalidKey`] on a key out of order or given
+    /// twice, and with [`TrieError::CapacityExceeded`] once the graph could
+    /// outgrow `u32` state indices or counts.
+    pub fn insert<Q>(&mut self, key: &Q) -> Result<(), TrieError>
+    where
+        Q: Borrow<[K]> + ?Sized,
+    {
+        let key = key.borrow();
+        if self.len > 0 && key <= &self.last[..] {
+            return Err(TrieError::InvalidKey);
+        }
+        let pending: usize = self.open.iter().map(|o| o.edges.len()).sum();
+        let edges = self.edges.len() + self.root.edges.len() + pending + key.len();
+        let states = self.states.len() + self.open.len() + key.len() + 1;
+        if self.len == MAX_STATES || edges.max(states) > MAX_STATES {
+            return Err(TrieError::CapacityExceeded);
+        }
+
+        let common = key
+            .iter()
+            .zip(&self.last)
+            .take_while(|(a, b)| a == b)
+            .count();
+        self.close(common);
+
+        for elem in &key[common..] {
+            self.tip().edges.push((elem.clone(), u32::MAX));
+            self.open.push(Open::new());
+        }
+        self.tip().is_final = true;
+
+        self.last.clear();
+        self.last.extend_from_slice(key);
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Minimizes the remaining open states and returns the finished graph.
+    pub fn finish(mut self) -> Dawg<K> {
+        self.close(0);
+        let root = mem::replace(&mut self.root, Open::new());
+        let root = self.add(root.is_final, root.edges);
+
+        self.states.shrink_to_fit();
+        self.edges.shrink_to_fit();
+        Dawg {
+            states: self.states,
+            edges: self.edges,
+            root,
+        }
+    }
+
+    /// The deepest open state, where the last key ends.
+    fn tip(&mut self) -> &mut Open<K> {
+        self.open.last_mut().unwrap_or(&mut self.root)
+    }
+
+    /// Replaces every open state deeper than `depth` by its equivalent in the
+    /// register, deepest first, pointing its parent at it.
+    fn close(&mut self, depth: usize) {
+        while self.open.len() > depth {
+            let Open { is_final, edges } = self.open.pop().unwrap();
+            let signature = (is_final, edges);
+
+            let state = match self.register.get(&signature) {
+                Some(&state) => state,
+                None => {
+                    let state = self.add(signature.0, signature.1.clone());
+                    self.register.insert(signature, state);
+                    state
+                }
+            };
+
+            // the parent's last transition is the one leading here
+            self.tip().edges.last_mut().unwrap().1 = state;
+        }
+    }
+
+    /// Appends a finished state, whose successors are all finished already.
+    fn add(&mut self, is_final: bool, edges: Vec<(K, u32)>) -> u32 {
+        let below: u32 = edges
+            .iter()
+            .map(|&(_, next)| self.states[next as usize].count)
+            .sum();
+        let start = self.edges.len() as u32;
+        self.edges.extend(edges);
+
+        self.states.push(State {
+            start,
+            end: self.edges.len() as u32,
+            count: below + u32::from(is_final),
+            is_final,
+        });
+        (self.states.len() - 1) as u32
+    }
+}
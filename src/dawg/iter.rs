@@ -0,0 +1,71 @@
+use super::Dawg;
+use std::slice;
+
+/// Iterator over the keys of a [`Dawg`] in sorted order, returned by
+/// [`Dawg::iter`] and [`Dawg::iter_prefix`].
+///
+/// Keys are spelled out along the paths of the graph, so each one is yielded
+/// as an owned `Vec`.
+pub struct Iter<'a, K> {
+    dawg: &'a Dawg<K>,
+    key: Vec<K>,
+    /// Whether the key the walk starts from is still to be yielded.
+    start: bool,
+    stack: Vec<(usize, slice::Iter<'a, (K, u32)>)>,
+}
+
+impl<'a, K> Iter<'a, K> {
+    /// Walks every key below `state`, which `key` leads to; `None` yields
+    /// nothing.
+    pub(crate) fn new(dawg: &'a Dawg<K>, key: Vec<K>, state: Option<u32>) -> Self {
+        let state = state.map(|state| &dawg.states[state as usize]);
+        let stack = match state {
+            Some(state) => vec![(key.len(), dawg.edges[state.edges()].iter())],
+            None => Vec::new(),
+        };
+
+        Self {
+            dawg,
+            key,
+            start: state.is_some_and(|state| state.is_final),
+            stack,
+        }
+    }
+}
+
+impl<K> Iterator for Iter<'_, K>
+where
+    K: Clone,
+{
+    type Item = Vec<K>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.start {
+            self.start = false;
+            return Some(self.key.clone());
+        }
+
+        loop {
+            let (len, edges) = self.stack.last_mut()?;
+            let len = *len;
+
+            let (label, next) = match edges.next() {
+                Some(edge) => edge,
+                None => {
+                    self.stack.pop();
+                    continue;
+                }
+            };
+
+            let state = &self.dawg.states[*next as usize];
+            self.key.truncate(len);
+            self.key.push(label.clone());
+            self.stack
+                .push((self.key.len(), self.dawg.edges[state.edges()].iter()));
+
+            if state.is_final {
+                return Some(self.key.clone());
+            }
+        }
+    }
+}
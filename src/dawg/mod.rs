@@ -0,0 +1,216 @@
+#[cfg(test)]
+mod tests;
+
+mod builder;
+mod iter;
+
+pub use builder::DawgBuilder;
+pub use iter::Iter;
+
+use crate::memory::MemoryUsage;
+use crate::trie::TrieNode;
+use crate::TrieError;
+use std::borrow::Borrow;
+use std::hash::Hash;
+use std::mem;
+use std::ops::Range;
+
+/// A directed acyclic word graph: the minimal automaton accepting a static
+/// set of keys.
+///
+/// Where a trie shares only prefixes, the graph also merges every pair of
+/// states accepting the same set of suffixes, so endings shared by many keys,
+/// such as `-ing` or `-tion` in a word list, are stored once. It is built
+/// from keys in sorted order by a [`DawgBuilder`] and cannot be changed
+/// afterwards.
+///
+/// Each state also records how many keys it accepts, which numbers the keys
+/// by their rank in sorted order: [`Dawg::ordinal`] and [`Dawg::key_at`] map
+/// between keys and ordinals, making the graph a minimal perfect hash.
+#[derive(Debug, Clone)]
+pub struct Dawg<K> {
+    states: Vec<State>,
+    /// Transitions of every state back to back, each state's sorted by label.
+    edges: Vec<(K, u32)>,
+    root: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct State {
+    /// Range of the state's transitions in `edges`.
+    start: u32,
+    end: u32,
+    /// Keys accepted from this state on.
+    count: u32,
+    is_final: bool,
+}
+
+impl State {
+    fn edges(&self) -> Range<usize> {
+        self.start as usize..self.end as usize
+    }
+}
+
+impl<K> Dawg<K>
+where
+    K: Clone + Ord + Hash,
+{
+    /// Builds the graph of `keys`, which must be strictly increasing; see
+    /// [`DawgBuilder::insert`].
+    pub fn from_sorted<I, Q>(keys: I) -> Result<Self, TrieError>
+    where
+        I: IntoIterator<Item = Q>,
+        Q: AsRef<[K]>,
+    {
+        let mut builder = DawgBuilder::new();
+        for key in keys {
+            builder.insert(key.as_ref())?;
+        }
+        Ok(builder.finish())
+    }
+}
+
+impl<K> Dawg<K>
+where
+    K: Clone + Ord,
+{
+    /// Returns the number of keys.
+    pub fn len(&self) -> usize {
+        self.states[self.root as usize].count as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        Q: Borrow<[K]> + ?Sized,
+    {
+        self.walk(key.borrow())
+            .is_some_and(|state| self.states[state as usize].is_final)
+    }
+
+    /// Iterates over every key in sorted order.
+    pub fn iter(&self) -> Iter<'_, K> {
+        Iter::new(self, Vec::new(), Some(self.root))
+    }
+
+    /// Iterates over every key starting with `prefix`, in sorted order.
+    pub fn iter_prefix<Q>(&self, prefix: &Q) -> Iter<'_, K>
+    where
+        Q: Borrow<[K]> + ?Sized,
+    {
+        let prefix = prefix.borrow();
+        Iter::new(self, prefix.to_vec(), self.walk(prefix))
+    }
+
+    /// Returns the rank of `key` among the keys in sorted order, or `None`
+    /// if it is not in the set.
+    ///
+    /// The rank is the number of smaller keys: those ending on the way to
+    /// `key` and those below every transition passed over at each step.
+    pub fn ordinal<Q>(&self, key: &Q) -> Option<usize>
+    where
+        Q: Borrow<[K]> + ?Sized,
+    {
+        let mut rank = 0;
+        let mut state = &self.states[self.root as usize];
+
+        for elem in key.borrow() {
+            rank += usize::from(state.is_final);
+            let edges = &self.edges[state.edges()];
+            let idx = edges.binary_search_by(|(label, _)| label.cmp(elem)).ok()?;
+            rank += edges[..idx]
+                .iter()
+                .map(|&(_, next)| self.states[next as usize].count as usize)
+                .sum::<usize>();
+            state = &self.states[edges[idx].1 as usize];
+        }
+
+        state.is_final.then_some(rank)
+    }
+
+    /// Returns the key of rank `ordinal` in sorted order; the inverse of
+    /// [`Dawg::ordinal`].
+    pub fn key_at(&self, ordinal: usize) -> Option<Vec<K>> {
+        if ordinal >= self.len() {
+            return None;
+        }
+
+        let mut rest = ordinal;
+        let mut key = Vec::new();
+        let mut state = &self.states[self.root as usize];
+
+        loop {
+            if state.is_final {
+                if rest == 0 {
+                    return Some(key);
+                }
+                rest -= 1;
+            }
+
+            for (label, next) in &self.edges[state.edges()] {
+                let next = &self.states[*next as usize];
+                if rest < next.count as usize {
+                    key.push(label.clone());
+                    state = next;
+                    break;
+                }
+                rest -= next.count as usize;
+            }
+        }
+    }
+
+    /// Estimates the heap footprint of the graph next to that of a
+    /// [`TrieNode`] holding the same keys, which has one node per distinct
+    /// prefix.
+    pub fn memory_usage(&self) -> MemoryUsage {
+        MemoryUsage {
+            nodes: self.states.len(),
+            capacity: self.states.capacity(),
+            arena_bytes: self.states.capacity() * mem::size_of::<State>()
+                + self.edges.capacity() * mem::size_of::<(K, u32)>(),
+            // every node but the root sits in its parent's `children` vector
+            boxed_bytes: (self.prefixes() - 1) * mem::size_of::<TrieNode<K, ()>>(),
+        }
+    }
+
+    /// Counts the paths from the root to every state, which are the distinct
+    /// prefixes of the keys. States are numbered after all their successors,
+    /// so visiting them in decreasing order reaches each one after every path
+    /// into it.
+    fn prefixes(&self) -> usize {
+        let mut paths = vec![0usize; self.states.len()];
+        paths[self.root as usize] = 1;
+
+        for idx in (0..self.states.len()).rev() {
+            for &(_, next) in &self.edges[self.states[idx].edges()] {
+                paths[next as usize] = paths[next as usize].saturating_add(paths[idx]);
+            }
+        }
+
+        paths.iter().fold(0, |sum, &n| sum.saturating_add(n))
+    }
+
+    /// Follows `key` from the root, returning the state it ends in.
+    fn walk(&self, key: &[K]) -> Option<u32> {
+        key.iter().try_fold(self.root, |state, elem| {
+            let edges = &self.edges[self.states[state as usize].edges()];
+            let idx = edges.binary_search_by(|(label, _)| label.cmp(elem)).ok()?;
+            Some(edges[idx].1)
+        })
+    }
+}
+
+impl<'a, K> IntoIterator for &'a Dawg<K>
+where
+    K: Clone + Ord,
+{
+    type Item = Vec<K>;
+    type IntoIter = Iter<'a, K>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
@@ -0,0 +1,119 @@
+use crate::dawg::{Dawg, DawgBuilder};
+use crate::test_util::{words, Lcg};
+use crate::trie::TrieNode;
+use crate::TrieError;
+use std::collections::{BTreeSet, HashSet};
+
+/// Sorted shared-generator words, each given one of a few common endings so
+/// suffixes are widely shared.
+fn suffixed(seed: u64, n: usize) -> Vec<Vec<u8>> {
+    const ENDINGS: [&str; 6] = ["", "s", "ing", "ed", "tion", "tions"];
+    let mut rng = Lcg::new(!seed);
+    let sorted: BTreeSet<Vec<u8>> = words(seed, n, 0..=7, b'a'..=b'd')
+        .into_iter()
+        .map(|mut word| {
+            word.extend_from_slice(ENDINGS[rng.range(0..=5) as usize].as_bytes());
+            word
+        })
+        .collect();
+    sorted.into_iter().collect()
+}
+
+/// Number of states of the minimal automaton: the distinct sets of
+/// suffixes completing a prefix of some key into a key.
+fn minimal_states(keys: &[Vec<u8>]) -> usize {
+    let mut languages = HashSet::new();
+    for key in keys {
+        for i in 0..=key.len() {
+            let prefix = &key[..i];
+            let language: BTreeSet<&[u8]> =
+                keys.iter().filter_map(|k| k.strip_prefix(prefix)).collect();
+            languages.insert(language);
+        }
+    }
+    languages.len().max(1)
+}
+
+#[test]
+fn dawg() {
+    let keys = ["cat", "cats", "dog", "dogs", "tap", "taps"];
+    let dawg = Dawg::from_sorted(keys.map(str::as_bytes)).unwrap();
+
+    assert_eq!(dawg.len(), 6);
+    assert!(dawg.contains(b"dogs"));
+    assert!(!dawg.contains(b"do"));
+    assert!(!dawg.contains(b"catss"));
+    // the root, two states down each of `ca`, `do` and `ta`, then the
+    // shared final state that may take an `s` and the one after it
+    assert_eq!(dawg.memory_usage().nodes, 9);
+
+    let found: Vec<Vec<u8>> = dawg.iter_prefix(b"ta").collect();
+    assert_eq!(found, [b"tap".to_vec(), b"taps".to_vec()]);
+    assert_eq!(dawg.iter_prefix(b"x").count(), 0);
+    assert_eq!(dawg.iter_prefix(b"").count(), 6);
+
+    assert_eq!(dawg.ordinal(b"dog"), Some(2));
+    assert_eq!(dawg.ordinal(b"ca"), None);
+    assert_eq!(dawg.key_at(5).unwrap(), b"taps");
+    assert_eq!(dawg.key_at(6), None);
+
+    let mut builder = DawgBuilder::new();
+    builder.insert(b"").unwrap();
+    builder.insert(b"b").unwrap();
+    assert_eq!(builder.insert(b"a"), Err(TrieError::InvalidKey));
+    assert_eq!(builder.insert(b"b"), Err(TrieError::InvalidKey));
+    let dawg = builder.finish();
+    assert_eq!(
+        dawg.iter().collect::<Vec<_>>(),
+        [b"".to_vec(), b"b".to_vec()]
+    );
+    assert_eq!(dawg.ordinal(b""), Some(0));
+
+    let empty = DawgBuilder::<u8>::new().finish();
+    assert!(empty.is_empty());
+    assert!(!empty.contains(b""));
+    assert_eq!(empty.iter().count(), 0);
+    assert_eq!(empty.key_at(0), None);
+}
+
+#[test]
+fn dawg_random() {
+    for seed in 0..10 {
+        let keys = suffixed(seed, 50 + seed as usize * 40);
+        let dawg = Dawg::from_sorted(&keys).unwrap();
+
+        assert_eq!(dawg.len(), keys.len());
+        assert_eq!(dawg.iter().collect::<Vec<_>>(), keys);
+        assert_eq!(dawg.memory_usage().nodes, minimal_states(&keys));
+
+        for (i, key) in keys.iter().enumerate() {
+            assert!(dawg.contains(key));
+            assert_eq!(dawg.ordinal(key), Some(i));
+            assert_eq!(dawg.key_at(i).as_ref(), Some(key));
+        }
+        for probe in suffixed(seed + 100, 50) {
+            assert_eq!(dawg.contains(&probe), keys.contains(&probe));
+        }
+        for prefix in [&b"a"[..], b"ab", b"dc", b"bing", b"ct"] {
+            let naive: Vec<_> = keys
+                .iter()
+                .filter(|k| k.starts_with(prefix))
+                .cloned()
+                .collect();
+            assert_eq!(dawg.iter_prefix(prefix).collect::<Vec<_>>(), naive);
+        }
+    }
+}
+
+#[test]
+fn dawg_memory() {
+    let keys = suffixed(3, 5000);
+    let dawg = Dawg::from_sorted(&keys).unwrap();
+    let trie: TrieNode<u8, ()> = keys.iter().map(|k| (k, ())).collect();
+
+    let usage = dawg.memory_usage();
+    let size = std::mem::size_of::<TrieNode<u8, ()>>();
+    assert_eq!(usage.boxed_bytes, (trie.stats().nodes - 1) * size);
+    assert!(usage.nodes * 2 < trie.stats().nodes, "{usage:?}");
+    assert!(usage.arena_bytes * 2 < usage.boxed_bytes, "{usage:?}");
+}
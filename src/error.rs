@@ -0,0 +1,28 @@
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TrieError {
+    /// The trie has no room to address another node.
+    CapacityExceeded,
+    /// The key cannot be stored, e.g. a malformed route pattern.
+    InvalidKey,
+    /// No value is stored under the key.
+    KeyNotFound,
+    /// The key overlaps a pattern already in the trie, such as two parameters
+    /// with different names in the same position.
+    ConflictingPattern,
+}
+
+impl fmt::Display for TrieError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            Self::CapacityExceeded => "trie capacity exceeded",
+            Self::InvalidKey => "invalid key",
+            Self::KeyNotFound => "key not found",
+            Self::ConflictingPattern => "key conflicts with an existing pattern",
+        };
+        f.write_str(msg)
+    }
+}
+
+impl std::error::Error for TrieError {}
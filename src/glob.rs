@@ -0,0 +1,192 @@
+use crate::automaton::Automaton;
+use crate::TrieError;
+
+/// A compiled glob pattern over byte keys.
+///
+/// `?` matches any one byte, `*` any run of bytes including none, and a
+/// class such as `[abc]`, `[a-z0-9]` or `[!/]` one byte in (or, after `!`,
+/// not in) the listed bytes and ranges. A `]` right after the opening `[` or
+/// `[!` is taken literally, and `\` makes the next byte literal anywhere.
+///
+/// Matching runs the pattern as a set of positions in it, all advanced one
+/// byte at a time, so a key is matched in a single pass however many `*` the
+/// pattern holds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Glob {
+    tokens: Vec<Token>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Byte(u8),
+    Any,
+    Star,
+    Class(ByteClass),
+}
+
+impl Token {
+    fn matches(&self, byte: u8) -> bool {
+        match self {
+            Token::Byte(b) => *b == byte,
+            Token::Any | Token::Star => true,
+            Token::Class(class) => class.matches(byte),
+        }
+    }
+}
+
+/// A set of bytes given as inclusive ranges, or every byte outside them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ByteClass {
+    negated: bool,
+    ranges: Vec<(u8, u8)>,
+}
+
+impl ByteClass {
+    pub(crate) fn matches(&self, byte: u8) -> bool {
+        let found = self.ranges.iter().any(|&(lo, hi)| lo <= byte && byte <= hi);
+        found != self.negated
+    }
+
+    /// Parses a class from just after its opening `[`, where a leading
+    /// `negate` byte inverts it, returning the class with the number of bytes
+    /// up to and including the closing `]`. A `]` first in the class and a
+    /// `-` last in it are literal, and `\` escapes the next byte.
+    pub(crate) fn parse(pattern: &[u8], negate: u8) -> Result<(Self, usize), TrieError> {
+        let negated = pattern.first() == Some(&negate);
+        let mut i = usize::from(negated);
+        let mut ranges = Vec::new();
+        let byte = |i: usize| pattern.get(i).copied().ok_or(TrieError::InvalidKey);
+
+        loop {
+            let mut lo = byte(i)?;
+            if lo == b']' && !ranges.is_empty() {
+                break;
+            }
+            if lo == b'\\' {
+                i += 1;
+                lo = byte(i)?;
+            }
+
+            let mut hi = lo;
+            if byte(i + 1)? == b'-' && byte(i + 2)? != b']' {
+                i += 2;
+                hi = byte(i)?;
+                if hi == b'\\' {
+                    i += 1;
+                    hi = byte(i)?;
+                }
+            }
+
+            if lo > hi {
+                return Err(TrieError::InvalidKey);
+            }
+            ranges.push((lo, hi));
+            i += 1;
+        }
+
+        Ok((Self { negated, ranges }, i + 1))
+    }
+}
+
+impl Glob {
+    /// Compiles `pattern`, failing with [`TrieError::InvalidKey`] on an
+    /// unterminated class, a reversed range or a trailing `\`.
+    pub fn new(pattern: &str) -> Result<Self, TrieError> {
+        let pattern = pattern.as_bytes();
+        let mut tokens = Vec::new();
+        let mut i = 0;
+
+        while i < pattern.len() {
+            let token = match pattern[i] {
+                b'?' => Token::Any,
+                // runs of `*` match the same keys as a single one
+                b'*' if tokens.last() == Some(&Token::Star) => {
+                    i += 1;
+                    continue;
+                }
+                b'*' => Token::Star,
+                b'\\' => {
+                    i += 1;
+                    Token::Byte(*pattern.get(i).ok_or(TrieError::InvalidKey)?)
+                }
+                b'[' => {
+                    let (class, len) = ByteClass::parse(&pattern[i + 1..], b'!')?;
+                    i += len;
+                    Token::Class(class)
+                }
+                byte => Token::Byte(byte),
+            };
+            tokens.push(token);
+            i += 1;
+        }
+
+        Ok(Self { tokens })
+    }
+
+    /// Returns `true` if the whole of `key` matches the pattern.
+    pub fn is_match(&self, key: &[u8]) -> bool {
+        let states = key.iter().try_fold(self.start(), |states, byte| {
+            let next = self.accept(&states, byte);
+            (!next.is_empty()).then_some(next)
+        });
+        states.is_some_and(|states| self.accepts(&states))
+    }
+
+    fn accepts(&self, states: &[usize]) -> bool {
+        states.last() == Some(&self.tokens.len())
+    }
+
+    /// Adds `pos` to the sorted set `states`, along with the position after
+    /// it if it is a `*`, which may match nothing.
+    fn enter(&self, states: &mut Vec<usize>, pos: usize) {
+        let mut pos = pos;
+        loop {
+            if let Err(idx) = states.binary_search(&pos) {
+                states.insert(idx, pos);
+            }
+            match self.tokens.get(pos) {
+                Some(Token::Star) => pos += 1,
+                _ => return,
+            }
+        }
+    }
+}
+
+impl Automaton<u8> for Glob {
+    /// Positions in the pattern still in play, in increasing order.
+    type State = Vec<usize>;
+
+    fn start(&self) -> Self::State {
+        let mut states = Vec::new();
+        self.enter(&mut states, 0);
+        states
+    }
+
+    fn accept(&self, states: &Self::State, &byte: &u8) -> Self::State {
+        let mut next = Vec::new();
+
+        for &pos in states {
+            match self.tokens.get(pos) {
+                Some(Token::Star) => self.enter(&mut next, pos),
+                Some(token) if token.matches(byte) => self.enter(&mut next, pos + 1),
+                _ => (),
+            }
+        }
+
+        next
+    }
+
+    fn is_match(&self, states: &Self::State) -> bool {
+        self.accepts(states)
+    }
+
+    fn can_match(&self, states: &Self::State) -> bool {
+        !states.is_empty()
+    }
+
+    /// True once the pattern has reached a trailing `*`.
+    fn will_always_match(&self, states: &Self::State) -> bool {
+        let len = self.tokens.len();
+        states.contains(&len) && len > 0 && self.tokens[len - 1] == Token::Star
+    }
+}
@@ -1,8 +1,26 @@
+pub mod aho;
+pub mod art;
+pub mod automaton;
+pub mod codec;
+pub mod darts;
+pub mod dawg;
+pub mod error;
+pub mod glob;
+pub mod louds;
+pub mod memory;
 pub mod params;
 pub mod path;
 pub mod radix;
+mod render;
+pub mod stats;
+#[cfg(test)]
+mod test_util;
 pub mod trie;
 
+pub use error::TrieError;
+pub use memory::MemoryUsage;
+pub use stats::TrieStats;
+
 use std::borrow::Borrow;
 
 pub trait TrieExt<K, V>: Sized
@@ -13,11 +31,32 @@ where
     where
         Q: Borrow<[K]>;
 
-    fn insert<Q>(&mut self, key: &Q, value: V) -> Result<Option<V>, ()>
+    fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        Q: Borrow<[K]>;
+
+    fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        Q: Borrow<[K]>,
+    {
+        self.get(key).is_some()
+    }
+
+    fn insert<Q>(&mut self, key: &Q, value: V) -> Result<Option<V>, TrieError>
     where
         Q: Borrow<[K]> + ?Sized;
 
     fn remove<Q>(&mut self, key: &Q, prune: bool) -> Option<Self>
     where
         Q: Borrow<[K]>;
+
+    /// Returns the number of values stored in the trie.
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes every key and value, leaving an empty trie.
+    fn clear(&mut self);
 }
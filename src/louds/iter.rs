@@ -0,0 +1,62 @@
+use super::LoudsTrie;
+use std::ops::Range;
+
+/// Iterator over the entries of a [`LoudsTrie`] in key order, returned by
+/// [`LoudsTrie::iter`] and [`LoudsTrie::iter_prefix`].
+///
+/// Keys are spelled out from the packed labels, so each one is yielded as an
+/// owned `Vec`.
+pub struct Iter<'a, V> {
+    trie: &'a LoudsTrie<V>,
+    key: Vec<u8>,
+    /// Value of the node the walk starts from, still to be yielded.
+    start: Option<&'a V>,
+    stack: Vec<(usize, Range<usize>)>,
+}
+
+impl<'a, V> Iter<'a, V> {
+    /// Walks every entry below `node`, which `key` leads to; `None` yields
+    /// nothing.
+    pub(crate) fn new(trie: &'a LoudsTrie<V>, key: Vec<u8>, node: Option<usize>) -> Self {
+        Self {
+            trie,
+            start: node.and_then(|node| trie.value(node)),
+            stack: node
+                .map(|node| (key.len(), trie.children(node)))
+                .into_iter()
+                .collect(),
+            key,
+        }
+    }
+}
+
+impl<'a, V> Iterator for Iter<'a, V> {
+    type Item = (Vec<u8>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(value) = self.start.take() {
+            return Some((self.key.clone(), value));
+        }
+
+        loop {
+            let (len, nodes) = self.stack.last_mut()?;
+            let len = *len;
+
+            let node = match nodes.next() {
+                Some(node) => node,
+                None => {
+                    self.stack.pop();
+                    continue;
+                }
+            };
+
+            self.key.truncate(len);
+            self.key.push(self.trie.labels[node - 1]);
+            self.stack.push((self.key.len(), self.trie.children(node)));
+
+            if let Some(value) = self.trie.value(node) {
+                return Some((self.key.clone(), value));
+            }
+        }
+    }
+}
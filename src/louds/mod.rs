@@ -0,0 +1,204 @@
+#[cfg(test)]
+mod tests;
+
+mod bits;
+mod iter;
+
+pub use iter::Iter;
+
+use crate::trie::TrieNode;
+use bits::BitVec;
+use std::collections::VecDeque;
+use std::ops::Range;
+
+/// An immutable trie over bytes in a succinct level-order unary degree
+/// sequence (LOUDS) encoding, taking about twelve bits per node plus the
+/// values.
+///
+/// Nodes are numbered breadth first, the root being 0. The shape is one bit
+/// sequence: `10` for a virtual parent of the root, then for each node in
+/// turn one `1` per child followed by a `0`. The `1` at rank `x` stands for
+/// node `x`, and the children of `x` follow the `0` of rank `x`, so the
+/// children of a node are found by a select over zeros and a rank over ones
+/// without any pointers. Children are numbered consecutively in key order,
+/// and their edge labels are packed in one byte array searched by binary
+/// search.
+///
+/// A second bit sequence marks the nodes holding a value; its rank maps a
+/// node to its slot in the packed value array. Both sequences count bits with
+/// `u32`s, which bounds the trie to about two billion nodes.
+#[derive(Debug, Clone)]
+pub struct LoudsTrie<V> {
+    louds: BitVec,
+    /// Label of the edge into each node but the root, in node order.
+    labels: Vec<u8>,
+    terminal: BitVec,
+    values: Vec<V>,
+}
+
+impl<V> LoudsTrie<V> {
+    /// Returns the number of values.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Returns the number of nodes, including the root.
+    pub fn nodes(&self) -> usize {
+        self.terminal.len()
+    }
+
+    pub fn get(&self, key: &[u8]) -> Option<&V> {
+        let node = self.walk(key)?;
+        self.value(node)
+    }
+
+    pub fn contains_key(&self, key: &[u8]) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Finds the longest stored key that is a prefix of `key`, returning its
+    /// length and value.
+    pub fn longest_prefix(&self, key: &[u8]) -> Option<(usize, &V)> {
+        let mut node = 0;
+        let mut best = self.value(node).map(|v| (0, v));
+
+        for (i, &byte) in key.iter().enumerate() {
+            node = match self.child(node, byte) {
+                Some(node) => node,
+                None => break,
+            };
+            if let Some(v) = self.value(node) {
+                best = Some((i + 1, v));
+            }
+        }
+
+        best
+    }
+
+    /// Iterates over every key and value in key order.
+    pub fn iter(&self) -> Iter<'_, V> {
+        Iter::new(self, Vec::new(), Some(0))
+    }
+
+    /// Iterates over every key starting with `prefix` and its value, in key
+    /// order.
+    pub fn iter_prefix(&self, prefix: &[u8]) -> Iter<'_, V> {
+        Iter::new(self, prefix.to_vec(), self.walk(prefix))
+    }
+
+    /// Bytes held for the shape, labels and terminal marks, which is what the
+    /// encoding saves on; the value array comes on top.
+    pub fn heap_bytes(&self) -> usize {
+        self.louds.heap_bytes() + self.labels.capacity() + self.terminal.heap_bytes()
+    }
+
+    /// Nodes `first..first + degree` are the children of `node`.
+    fn children(&self, node: usize) -> Range<usize> {
+        let start = self.louds.select0(node) + 1;
+        let end = self.louds.select0(node + 1);
+        let first = self.louds.rank1(start);
+        first..first + (end - start)
+    }
+
+    fn child(&self, node: usize, byte: u8) -> Option<usize> {
+        let children = self.children(node);
+        let labels = &self.labels[children.start - 1..children.end - 1];
+        let idx = labels.binary_search(&byte).ok()?;
+        Some(children.start + idx)
+    }
+
+    fn walk(&self, key: &[u8]) -> Option<usize> {
+        key.iter().try_fold(0, |node, &byte| self.child(node, byte))
+    }
+
+    fn value(&self, node: usize) -> Option<&V> {
+        match self.terminal.get(node) {
+            true => Some(&self.values[self.terminal.rank1(node)]),
+            false => None,
+        }
+    }
+}
+
+impl<V> From<TrieNode<u8, V>> for LoudsTrie<V> {
+    /// Encodes `trie` breadth first. Values are moved out in key order, which
+    /// is depth first, and then sorted into node order.
+    fn from(trie: TrieNode<u8, V>) -> Self {
+        let mut louds = BitVec::default();
+        let mut labels = Vec::new();
+        let mut terminal = BitVec::default();
+
+        louds.push(true);
+        louds.push(false);
+
+        // node order, with the node order of each one's first child
+        let mut nodes = Vec::new();
+        let mut queue = VecDeque::from([&trie]);
+        let mut next = 1;
+
+        while let Some(node) = queue.pop_front() {
+            for child in node.children() {
+                louds.push(true);
+                labels.push(*child.key());
+                queue.push_back(child);
+            }
+            louds.push(false);
+            terminal.push(node.value().is_some());
+
+            nodes.push((node, next));
+            next += node.children().len();
+        }
+
+        // values in each subtree, children being numbered after parents
+        let mut below = vec![0; nodes.len()];
+        for (i, &(node, first)) in nodes.iter().enumerate().rev() {
+            let children = first..first + node.children().len();
+            below[i] =
+                usize::from(node.value().is_some()) + children.map(|c| below[c]).sum::<usize>();
+        }
+
+        // for each value in key order, its slot in node order, going by the
+        // key order rank of the first value in each subtree
+        let mut slots = vec![0; below[0]];
+        let mut ranks = vec![0; nodes.len()];
+        let mut slot = 0;
+        for (i, &(node, first)) in nodes.iter().enumerate() {
+            let mut rank = ranks[i];
+            if node.value().is_some() {
+                slots[rank] = slot;
+                slot += 1;
+                rank += 1;
+            }
+            for c in first..first + node.children().len() {
+                ranks[c] = rank;
+                rank += below[c];
+            }
+        }
+
+        let mut values: Vec<_> = slots.into_iter().zip(trie).collect();
+        values.sort_unstable_by_key(|&(slot, _)| slot);
+
+        louds.finish();
+        terminal.finish();
+        labels.shrink_to_fit();
+
+        Self {
+            louds,
+            labels,
+            terminal,
+            values: values.into_iter().map(|(_, (_, value))| value).collect(),
+        }
+    }
+}
+
+impl<V, Q> FromIterator<(Q, V)> for LoudsTrie<V>
+where
+    Q: AsRef<[u8]>,
+{
+    fn from_iter<I: IntoIterator<Item = (Q, V)>>(iter: I) -> Self {
+        TrieNode::from_iter(iter).into()
+    }
+}
@@ -0,0 +1,103 @@
+use crate::louds::bits::BitVec;
+use crate::louds::LoudsTrie;
+use crate::test_util::{words, Lcg};
+use crate::trie::TrieNode;
+use crate::TrieExt;
+use std::collections::BTreeMap;
+
+#[test]
+fn louds_bits() {
+    let mut rng = Lcg::new(5);
+    let mut bits = BitVec::default();
+    let mut naive = Vec::new();
+    for _ in 0..3000 {
+        let bit = rng.range(0..=3) == 0;
+        bits.push(bit);
+        naive.push(bit);
+    }
+    bits.finish();
+
+    let mut ones = 0;
+    let mut zeros = 0;
+    for (i, &bit) in naive.iter().enumerate() {
+        assert_eq!(bits.get(i), bit);
+        assert_eq!(bits.rank1(i), ones);
+        if bit {
+            ones += 1;
+        } else {
+            assert_eq!(bits.select0(zeros), i);
+            zeros += 1;
+        }
+    }
+    assert_eq!(bits.rank1(naive.len()), ones);
+}
+
+#[test]
+fn louds_trie() {
+    let trie: TrieNode<u8, usize> = ["", "a", "ab", "abc", "b", "bca"]
+        .iter()
+        .zip(0..)
+        .map(|(k, v)| (k.as_bytes(), v))
+        .collect();
+    let louds = LoudsTrie::from(trie);
+
+    assert_eq!(louds.len(), 6);
+    assert_eq!(louds.nodes(), 7);
+    assert_eq!(louds.get(b""), Some(&0));
+    assert_eq!(louds.get(b"abc"), Some(&3));
+    assert_eq!(louds.get(b"bc"), None);
+    assert_eq!(louds.get(b"abcd"), None);
+    assert_eq!(louds.longest_prefix(b"abcd"), Some((3, &3)));
+    assert_eq!(louds.longest_prefix(b"bcx"), Some((1, &4)));
+    assert_eq!(louds.longest_prefix(b"x"), Some((0, &0)));
+
+    let keys: Vec<_> = louds.iter_prefix(b"ab").map(|(k, _)| k).collect();
+    assert_eq!(keys, [b"ab".to_vec(), b"abc".to_vec()]);
+    assert_eq!(louds.iter_prefix(b"c").count(), 0);
+
+    let empty = LoudsTrie::<()>::from(TrieNode::default());
+    assert!(empty.is_empty());
+    assert_eq!(empty.get(b""), None);
+    assert_eq!(empty.iter().count(), 0);
+
+    for seed in 0..10 {
+        let mut trie = TrieNode::default();
+        let mut map = BTreeMap::new();
+        for (i, key) in words(seed, 300, 0..=15, b'a'..=b'c')
+            .into_iter()
+            .enumerate()
+        {
+            trie.insert(&key, i).unwrap();
+            map.insert(key, i);
+        }
+        let nodes = trie.stats().nodes;
+        let louds = LoudsTrie::from(trie);
+
+        assert_eq!(louds.nodes(), nodes);
+        assert_eq!(louds.len(), map.len());
+        let entries: Vec<_> = louds.iter().map(|(k, &v)| (k, v)).collect();
+        assert_eq!(entries, map.clone().into_iter().collect::<Vec<_>>());
+
+        for probe in words(seed + 100, 100, 0..=15, b'a'..=b'c') {
+            assert_eq!(louds.get(&probe), map.get(&probe));
+
+            let longest = (0..=probe.len())
+                .rev()
+                .find_map(|n| map.get(&probe[..n]).map(|v| (n, v)));
+            assert_eq!(louds.longest_prefix(&probe), longest);
+
+            let naive: Vec<_> = map.iter().filter(|(k, _)| k.starts_with(&probe)).collect();
+            let found: Vec<_> = louds.iter_prefix(&probe).collect();
+            assert_eq!(found.len(), naive.len());
+            assert!(found.iter().zip(naive).all(|(a, b)| (&a.0, a.1) == b));
+        }
+
+        // two shape bits, a label byte and a terminal bit per node, plus the
+        // rank directories and word padding
+        assert!(
+            louds.heap_bytes() * 8 <= nodes * 13 + 256,
+            "{}",
+            louds.heap_bytes()
+        );
+    }
+}
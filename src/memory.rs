@@ -0,0 +1,22 @@
+/// Estimated heap footprint of an arena-backed trie, next to what the boxed
+/// layout holding the same nodes would take.
+///
+/// Byte counts cover the trie's own allocations, not heap memory owned by
+/// the keys or values themselves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MemoryUsage {
+    /// Live nodes, including the root.
+    pub nodes: usize,
+    /// Node slots allocated by the arena, including freed ones awaiting reuse.
+    pub capacity: usize,
+    /// Bytes held by the arena and its side tables.
+    pub arena_bytes: usize,
+    /// Bytes the equivalent `TrieNode` or `RadixNode` tree would hold.
+    pub boxed_bytes: usize,
+}
+
+/// Approximates the size of a slab slot: the node plus the tag that tells
+/// occupied slots from free-list links.
+pub(crate) fn slot_size<T>() -> usize {
+    std::mem::size_of::<Option<T>>().max(std::mem::size_of::<usize>())
+}
@@ -6,6 +6,12 @@ pub enum Params<'a, 'b> {
     Large(Vec<Param<'a, 'b>>),
 }
 
+impl<'a, 'b> Default for Params<'a, 'b> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<'a, 'b> Params<'a, 'b> {
     pub fn new() -> Self {
         Self::Small([Default::default(); 4], 0)
@@ -35,9 +41,9 @@ impl<'a, 'b> Params<'a, 'b> {
     pub fn get(&self, key: &str) -> Option<&'b str> {
         match self {
             Self::Small(params, count) => {
-                for i in 0..*count {
-                    if params[i].key == key {
-                        return Some(params[i].value);
+                for param in &params[..*count] {
+                    if param.key == key {
+                        return Some(param.value);
                     }
                 }
             }
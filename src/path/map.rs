@@ -0,0 +1,92 @@
+use super::PathTrie;
+use crate::{TrieError, TrieExt};
+use std::borrow::Borrow;
+
+/// Adapts a [`PathTrie`] to [`TrieExt`], keyed by the bytes of each route
+/// pattern. Lookups compare patterns exactly rather than matching parameters.
+#[derive(Debug, Default)]
+pub struct PathMap<T> {
+    trie: PathTrie<T>,
+}
+
+impl<T> PathMap<T> {
+    pub fn new() -> Self {
+        Self {
+            trie: PathTrie::new(),
+        }
+    }
+
+    pub fn trie(&self) -> &PathTrie<T> {
+        &self.trie
+    }
+
+    pub fn into_inner(self) -> PathTrie<T> {
+        self.trie
+    }
+}
+
+impl<T> From<PathTrie<T>> for PathMap<T> {
+    fn from(trie: PathTrie<T>) -> Self {
+        Self { trie }
+    }
+}
+
+impl<T> TrieExt<u8, T> for PathMap<T> {
+    fn get<Q>(&self, key: &Q) -> Option<&T>
+    where
+        Q: Borrow<[u8]>,
+    {
+        let pattern = std::str::from_utf8(key.borrow()).ok()?;
+        self.trie.find(pattern)
+    }
+
+    fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut T>
+    where
+        Q: Borrow<[u8]>,
+    {
+        let pattern = std::str::from_utf8(key.borrow()).ok()?;
+        self.trie.find_mut(pattern)
+    }
+
+    fn insert<Q>(&mut self, key: &Q, value: T) -> Result<Option<T>, TrieError>
+    where
+        Q: Borrow<[u8]> + ?Sized,
+    {
+        let pattern = std::str::from_utf8(key.borrow()).map_err(|_| TrieError::InvalidKey)?;
+        self.trie.insert(pattern, value)
+    }
+
+    fn remove<Q>(&mut self, key: &Q, prune: bool) -> Option<Self>
+    where
+        Q: Borrow<[u8]>,
+    {
+        let pattern = std::str::from_utf8(key.borrow()).ok()?;
+
+        let removed = match prune {
+            true => self.trie.remove_tree(pattern),
+            false => {
+                let value = self.trie.remove(pattern).ok()?;
+                vec![(pattern.to_string(), value)]
+            }
+        };
+
+        if removed.is_empty() {
+            return None;
+        }
+
+        let mut map = PathMap::new();
+        for (pattern, value) in removed {
+            // the patterns were already accepted by `self`
+            let _ = map.trie.insert(pattern, value);
+        }
+        Some(map)
+    }
+
+    fn len(&self) -> usize {
+        self.trie.len()
+    }
+
+    fn clear(&mut self) {
+        self.trie.clear();
+    }
+}
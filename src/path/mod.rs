@@ -1,2 +1,11 @@
+#[cfg(test)]
+mod tests;
+
+mod map;
+#[allow(clippy::module_inception)]
 mod path;
-pub use path::PathTrie;
+#[cfg(feature = "serde")]
+mod serde;
+
+pub use map::PathMap;
+pub use path::{IntoIter, Iter, PathTrie};
@@ -1,11 +1,25 @@
 use crate::params::Params;
+use crate::render::{tree_label, write_tree, Dot};
+use crate::stats::{Collector, TrieStats};
+use crate::TrieError;
 use slab::Slab;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::mem;
+use std::ops::Index;
+use std::vec;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct PathTrie<T> {
     nodes: Slab<Node<T>>,
 }
 
+impl<T> Default for PathTrie<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T> PathTrie<T> {
     pub fn new() -> Self {
         let root = Node::from(String::new(), Vec::new());
@@ -16,13 +30,13 @@ impl<T> PathTrie<T> {
         Self { nodes: slab }
     }
 
-    pub fn get<'a, 'b>(&'a self, key: &'b str) -> Option<(&T, Params<'a, 'b>)> {
+    pub fn get<'a, 'b>(&'a self, key: &'b str) -> Option<(&'a T, Params<'a, 'b>)> {
         let mut params = Params::new();
         let data = self.get_params(&mut params, key)?;
         Some((data, params))
     }
 
-    fn get_params<'a, 'b>(&'a self, params: &mut Params<'a, 'b>, key: &'b str) -> Option<&T> {
+    fn get_params<'a, 'b>(&'a self, params: &mut Params<'a, 'b>, key: &'b str) -> Option<&'a T> {
         let mut key = key.as_bytes();
         let mut curr = 0;
 
@@ -35,13 +49,13 @@ impl<T> PathTrie<T> {
 
             let node = &self.nodes[curr];
 
-            if key.len() == 0 {
+            if key.is_empty() {
                 return node.data.as_ref();
             }
 
             let lut: &[u8] = node.index.as_ref();
 
-            if lut.len() == 0 {
+            if lut.is_empty() {
                 return None;
             }
 
@@ -52,43 +66,36 @@ impl<T> PathTrie<T> {
                 None => key.len(),
             };
 
-            match find(lut, key[0]) {
-                Some(start) => {
-                    for idx in start..lut.len() {
-                        let idx = xs[idx];
-                        let el: &[u8] = self.nodes[idx].path.as_ref();
-
-                        if el.len() < n {
-                            continue;
-                        }
-
-                        if key.starts_with(el) {
-                            curr = idx;
-                            key = &key[el.len()..];
-                            continue 'outer;
-                        }
+            if let Some(start) = find(lut, key[0]) {
+                for &idx in &xs[start..] {
+                    let el: &[u8] = self.nodes[idx].path.as_ref();
+
+                    if el.len() < n {
+                        continue;
+                    }
+
+                    if key.starts_with(el) {
+                        curr = idx;
+                        key = &key[el.len()..];
+                        continue 'outer;
                     }
                 }
-                None => {}
             }
 
-            match find(lut, b':') {
-                Some(idx) => {
-                    let idx = xs[idx];
-                    let node = &self.nodes[idx];
+            if let Some(idx) = find(lut, b':') {
+                let idx = xs[idx];
+                let node = &self.nodes[idx];
 
-                    let (_, k) = node.path.split_at(1);
-                    let (v, rem) = key.split_at(n);
+                let (_, k) = node.path.split_at(1);
+                let (v, rem) = key.split_at(n);
 
-                    let k = to_str(k);
-                    let v = to_str(v);
-                    params.insert(k, v);
+                let k = to_str(k);
+                let v = to_str(v);
+                params.insert(k, v);
 
-                    curr = idx;
-                    key = rem;
-                    continue 'outer;
-                }
-                None => {}
+                curr = idx;
+                key = rem;
+                continue 'outer;
             }
 
             match find(lut, b'*') {
@@ -107,21 +114,27 @@ impl<T> PathTrie<T> {
         }
     }
 
-    pub fn insert<S>(&mut self, key: S, value: T)
+    /// Inserts a route pattern, returning the value previously stored for it.
+    ///
+    /// Parameters (`:name`) and wildcards (`*name`) must be named, a wildcard
+    /// must be the last segment, and a parameter or wildcard cannot share its
+    /// position with one of a different name.
+    pub fn insert<S>(&mut self, key: S, value: T) -> Result<Option<T>, TrieError>
     where
         S: AsRef<str>,
     {
-        let key: Vec<_> = key.as_ref().split('/').filter(|s| s.len() != 0).collect();
+        let key: Vec<_> = key.as_ref().split('/').filter(|s| !s.is_empty()).collect();
+        validate(&key)?;
+
         let mut active = key.as_slice();
         let mut curr = 0;
 
-        'outer: loop {
-            if active.len() == 0 {
-                self.nodes[curr].data = Some(value);
-                break 'outer;
+        let prev = 'outer: loop {
+            if active.is_empty() {
+                break 'outer Ok(self.nodes[curr].data.replace(value));
             }
 
-            if self.nodes[curr].children.len() == 0 {
+            if self.nodes[curr].children.is_empty() {
                 let (start, rem) = longest(active);
                 let node = Node::new(start, Vec::new());
 
@@ -149,7 +162,7 @@ impl<T> PathTrie<T> {
                     }
 
                     let subpath = n_p.after(num).to_string();
-                    let children = std::mem::replace(&mut self.nodes[idx].children, Vec::new());
+                    let children = std::mem::take(&mut self.nodes[idx].children);
 
                     let mut right = Node::from(subpath, children);
                     right.data = self.nodes[idx].data.take();
@@ -173,43 +186,18 @@ impl<T> PathTrie<T> {
                 }
 
                 if equal {
-                    self.nodes[idx].data = Some(value);
-                    break 'outer;
+                    break 'outer Ok(self.nodes[idx].data.replace(value));
                 }
 
-                let p = &unsafe { std::str::from_utf8_unchecked(&self.nodes[idx].path) };
-                let p = p.to_string();
-                let p = &p.at(0)[0..1];
-                match (p, &active[0][0..1]) {
-                    (":", ":") | ("*", ":") => {
-                        if &self.nodes[idx].path == &active[0].as_bytes() {
-                            curr = idx;
-                            active = &active[1..];
-                            continue 'outer;
-                        }
-                        let node = Node::new(&active[0..1], self.nodes[idx].children.clone());
+                match (self.nodes[idx].path[0], active[0].as_bytes()[0]) {
+                    (b':', b':') if self.nodes[idx].path == active[0].as_bytes() => {
                         curr = idx;
                         active = &active[1..];
-
-                        let prev = std::mem::replace(&mut self.nodes[idx], node);
-                        for sub in &prev.children {
-                            self.delete(*sub);
-                        }
-                    }
-                    (":", "*") | ("*", "*") => {
-                        let node = Node::new(&active[0..1], self.nodes[idx].children.clone());
-                        curr = idx;
-                        active = &active[1..];
-
-                        let prev = std::mem::replace(&mut self.nodes[idx], node);
-                        for sub in &prev.children {
-                            self.delete(*sub);
-                        }
+                        continue 'outer;
                     }
+                    (b':' | b'*', b':' | b'*') => break 'outer Err(TrieError::ConflictingPattern),
                     _ => continue,
                 }
-
-                continue 'outer;
             }
 
             let (start, rem) = longest(active);
@@ -220,15 +208,185 @@ impl<T> PathTrie<T> {
 
             curr = pos;
             active = &active[rem..];
-        }
+        };
         self.sort_all();
+        prev
+    }
+
+    /// Returns the value stored for `pattern` itself, without matching
+    /// parameters against it.
+    pub fn find<S>(&self, pattern: S) -> Option<&T>
+    where
+        S: AsRef<str>,
+    {
+        let idx = *self.trace(pattern.as_ref())?.last()?;
+        self.nodes[idx].data.as_ref()
+    }
+
+    pub fn find_mut<S>(&mut self, pattern: S) -> Option<&mut T>
+    where
+        S: AsRef<str>,
+    {
+        let idx = *self.trace(pattern.as_ref())?.last()?;
+        self.nodes[idx].data.as_mut()
+    }
+
+    /// Removes the value stored for `pattern`, dropping any nodes left
+    /// without a value or children. Fails with [`TrieError::KeyNotFound`] if
+    /// no value is stored for the pattern itself.
+    pub fn remove<S>(&mut self, pattern: S) -> Result<T, TrieError>
+    where
+        S: AsRef<str>,
+    {
+        let mut trace = self.trace(pattern.as_ref()).ok_or(TrieError::KeyNotFound)?;
+        let idx = *trace.last().ok_or(TrieError::KeyNotFound)?;
+        let data = self.nodes[idx].data.take().ok_or(TrieError::KeyNotFound)?;
+
+        while trace.len() > 1 {
+            let idx = trace.pop().unwrap();
+            let node = &self.nodes[idx];
+
+            if node.data.is_some() || !node.children.is_empty() {
+                break;
+            }
+
+            self.nodes.remove(idx);
+            let parent = *trace.last().unwrap();
+            self.nodes[parent].children.retain(|&i| i != idx);
+            self.sort(parent);
+            if self.nodes[parent].children.is_empty() {
+                self.nodes[parent].index.clear();
+            }
+        }
+
+        Ok(data)
+    }
+
+    /// Detaches the node for `pattern` along with everything below it,
+    /// returning the removed patterns and their values.
+    pub(crate) fn remove_tree(&mut self, pattern: &str) -> Vec<(String, T)> {
+        let mut removed = Vec::new();
+        let trace = match self.trace(pattern) {
+            Some(trace) => trace,
+            None => return removed,
+        };
+
+        let idx = *trace.last().unwrap();
+        let prefix = normalize(pattern);
+
+        if trace.len() == 1 {
+            if let Some(data) = self.nodes[idx].data.take() {
+                removed.push((prefix, data));
+            }
+            for child in std::mem::take(&mut self.nodes[idx].children) {
+                self.drain(child, "", &mut removed);
+            }
+            self.nodes[idx].index.clear();
+            return removed;
+        }
+
+        let parent = trace[trace.len() - 2];
+        self.nodes[parent].children.retain(|&i| i != idx);
+        self.sort(parent);
+        if self.nodes[parent].children.is_empty() {
+            self.nodes[parent].index.clear();
+        }
+
+        let node = self.nodes.remove(idx);
+        if let Some(data) = node.data {
+            removed.push((prefix.clone(), data));
+        }
+        for child in node.children {
+            self.drain(child, &prefix, &mut removed);
+        }
+        removed
     }
 
-    fn delete(&mut self, idx: usize) {
-        for sub in self.nodes[idx].children.clone() {
-            self.delete(sub);
+    /// Iterates over every stored pattern and value, visiting the nodes in
+    /// the order lookups try them.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            trie: self,
+            pattern: String::new(),
+            stack: vec![(0, 0)],
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.iter().filter(|(_, n)| n.data.is_some()).count()
+    }
+
+    /// Walks the whole tree to gather its [`TrieStats`]. Key bytes are the
+    /// path segments stored on the nodes, and the heap estimate includes the
+    /// slab along with each node's path, `index` table and child list.
+    pub fn stats(&self) -> TrieStats {
+        let mut stats = Collector::default();
+        let mut stack = vec![(0, 0)];
+
+        while let Some((idx, depth)) = stack.pop() {
+            let node = &self.nodes[idx];
+            let heap_bytes = node.path.capacity()
+                + node.index.capacity()
+                + node.children.capacity() * mem::size_of::<usize>();
+
+            stats.visit(
+                depth,
+                node.children.len(),
+                node.data.is_some(),
+                node.path.len(),
+                heap_bytes,
+            );
+            stack.extend(node.children.iter().map(|&child| (child, depth + 1)));
+        }
+
+        stats.finish(self.nodes.capacity() * mem::size_of::<Node<T>>())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Follows `pattern` segment by segment, returning the slab indices of
+    /// every node visited from the root to the node holding the pattern.
+    fn trace(&self, pattern: &str) -> Option<Vec<usize>> {
+        let segments: Vec<_> = pattern.split('/').filter(|s| !s.is_empty()).collect();
+        let mut active = segments.as_slice();
+        let mut trace = vec![0];
+
+        'outer: while !active.is_empty() {
+            let curr = *trace.last().unwrap();
+
+            for &idx in &self.nodes[curr].children {
+                let path = to_str(&self.nodes[idx].path);
+                let n = path.split('/').count();
+
+                if n <= active.len() && path.split('/').eq(active[..n].iter().copied()) {
+                    trace.push(idx);
+                    active = &active[n..];
+                    continue 'outer;
+                }
+            }
+
+            return None;
+        }
+
+        Some(trace)
+    }
+
+    fn drain(&mut self, idx: usize, prefix: &str, out: &mut Vec<(String, T)>) {
+        let node = self.nodes.remove(idx);
+        let pattern = format!("{}/{}", prefix, to_str(&node.path));
+
+        if let Some(data) = node.data {
+            out.push((pattern.clone(), data));
+        }
+        for child in node.children {
+            self.drain(child, &pattern, out);
         }
-        self.nodes.remove(idx);
     }
 
     fn count_children(&self, idx: usize) -> usize {
@@ -250,7 +408,7 @@ impl<T> PathTrie<T> {
     }
 
     fn sort(&mut self, idx: usize) {
-        if self.nodes[idx].children.len() == 0 {
+        if self.nodes[idx].children.is_empty() {
             return;
         }
 
@@ -290,7 +448,194 @@ impl<T> PathTrie<T> {
     }
 }
 
-#[derive(Debug)]
+impl<T> PathTrie<T>
+where
+    T: fmt::Debug,
+{
+    /// Renders the tree as a Graphviz digraph. Each node shows its slab
+    /// index, its `index` table of child first bytes and its value, if any;
+    /// edges are labelled with the path of the node they lead to.
+    pub fn to_dot(&self) -> String {
+        let mut dot = Dot::new("record");
+
+        for (idx, node) in &self.nodes {
+            let index: Vec<_> = node
+                .index
+                .iter()
+                .map(|&b| (b as char).to_string())
+                .collect();
+
+            let mut fields = vec![format!("#{}", idx), format!("index: {}", index.join(" "))];
+            if let Some(data) = &node.data {
+                fields.push(format!("{:?}", data));
+            }
+            dot.record(idx, &fields, node.data.is_some());
+
+            for &child in &node.children {
+                dot.edge(idx, child, to_str(&self.nodes[child].path));
+            }
+        }
+
+        dot.finish()
+    }
+}
+
+/// Draws the tree like `tree(1)`, with each node's path and slab index.
+impl<T> fmt::Display for PathTrie<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = |idx: usize| {
+            let node = &self.nodes[idx];
+            let path = match idx {
+                0 => "/ (#0)".to_string(),
+                _ => format!("{} (#{})", to_str(&node.path), idx),
+            };
+            tree_label(&path, node.data.as_ref())
+        };
+        write_tree(f, 0, &label, &|idx| self.nodes[idx].children.clone())
+    }
+}
+
+impl<T> PartialEq for PathTrie<T>
+where
+    T: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        sorted(self) == sorted(other)
+    }
+}
+
+impl<T> Eq for PathTrie<T> where T: Eq {}
+
+impl<T> Hash for PathTrie<T>
+where
+    T: Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        sorted(self).hash(state);
+    }
+}
+
+/// Builds a trie from `(pattern, value)` pairs.
+///
+/// # Panics
+///
+/// Panics if a pattern is invalid or conflicts with an earlier one.
+impl<S, T> FromIterator<(S, T)> for PathTrie<T>
+where
+    S: AsRef<str>,
+{
+    fn from_iter<I: IntoIterator<Item = (S, T)>>(iter: I) -> Self {
+        let mut trie = PathTrie::new();
+        trie.extend(iter);
+        trie
+    }
+}
+
+/// # Panics
+///
+/// Panics if a pattern is invalid or conflicts with one already stored.
+impl<S, T> Extend<(S, T)> for PathTrie<T>
+where
+    S: AsRef<str>,
+{
+    fn extend<I: IntoIterator<Item = (S, T)>>(&mut self, iter: I) {
+        for (pattern, value) in iter {
+            let pattern = pattern.as_ref();
+            if let Err(err) = self.insert(pattern, value) {
+                panic!("cannot insert {}: {}", pattern, err);
+            }
+        }
+    }
+}
+
+impl<T> IntoIterator for PathTrie<T> {
+    type Item = (String, T);
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        IntoIter {
+            entries: self.remove_tree("/").into_iter(),
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a PathTrie<T> {
+    type Item = (String, &'a T);
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Looks up a pattern exactly, as [`PathTrie::find`] does.
+impl<T> Index<&str> for PathTrie<T> {
+    type Output = T;
+
+    fn index(&self, pattern: &str) -> &T {
+        self.find(pattern).expect("pattern not found in trie")
+    }
+}
+
+/// Borrowing iterator over the patterns and values of a [`PathTrie`].
+pub struct Iter<'a, T> {
+    trie: &'a PathTrie<T>,
+    pattern: String,
+    stack: Vec<(usize, usize)>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = (String, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (idx, len) = self.stack.pop()?;
+            let node = &self.trie.nodes[idx];
+
+            self.pattern.truncate(len);
+            if idx != 0 {
+                self.pattern.push('/');
+                self.pattern.push_str(to_str(&node.path));
+            }
+
+            let len = self.pattern.len();
+            self.stack
+                .extend(node.children.iter().rev().map(|&i| (i, len)));
+
+            if let Some(data) = &node.data {
+                let pattern = match self.pattern.is_empty() {
+                    true => "/".to_string(),
+                    false => self.pattern.clone(),
+                };
+                return Some((pattern, data));
+            }
+        }
+    }
+}
+
+/// Owning iterator over the patterns and values of a [`PathTrie`].
+pub struct IntoIter<T> {
+    entries: vec::IntoIter<(String, T)>,
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = (String, T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.entries.next()
+    }
+}
+
+fn sorted<T>(trie: &PathTrie<T>) -> Vec<(String, &T)> {
+    let mut entries: Vec<_> = trie.iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+    entries
+}
+
+#[derive(Debug, Clone)]
 struct Node<T> {
     path: Vec<u8>,
     index: Vec<u8>,
@@ -319,12 +664,12 @@ impl<T> Node<T> {
 }
 
 fn longest<'a>(key: &'a [&str]) -> (&'a [&'a str], usize) {
-    if key.len() == 0 {
+    if key.is_empty() {
         return (&key[0..0], 0);
     }
     let mut len = 0;
-    for i in 0..key.len() {
-        if &key[i][0..1] == "*" || &key[i][0..1] == ":" {
+    for (i, segment) in key.iter().enumerate() {
+        if &segment[0..1] == "*" || &segment[0..1] == ":" {
             break;
         }
         len = i;
@@ -335,11 +680,11 @@ fn longest<'a>(key: &'a [&str]) -> (&'a [&'a str], usize) {
 fn lcs(a: &String, b: &[&str]) -> usize {
     let min = std::cmp::min(a.length(), b.len());
     let mut last = 0;
-    for i in 0..min {
-        if &a.at(i)[0..1] == ":" && &b[i][0..1] == ":" {
+    for (i, segment) in b.iter().enumerate().take(min) {
+        if &a.at(i)[0..1] == ":" && &segment[0..1] == ":" {
             return last;
         }
-        if a.at(i) != b[i] {
+        if a.at(i) != *segment {
             return i;
         }
         last = i;
@@ -351,21 +696,46 @@ fn eq(s: &String, xs: &[&str]) -> bool {
     if s.length() < xs.len() {
         return false;
     }
-    for i in 0..xs.len() {
-        if s.at(i) != xs[i] {
+    for (i, segment) in xs.iter().enumerate() {
+        if s.at(i) != *segment {
             return false;
         }
     }
     true
 }
 
+fn validate(segments: &[&str]) -> Result<(), TrieError> {
+    for (i, segment) in segments.iter().enumerate() {
+        match segment.as_bytes()[0] {
+            b':' if segment.len() == 1 => return Err(TrieError::InvalidKey),
+            b'*' if segment.len() == 1 || i + 1 != segments.len() => {
+                return Err(TrieError::InvalidKey)
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn normalize(pattern: &str) -> String {
+    let mut s = String::new();
+    for segment in pattern.split('/').filter(|s| !s.is_empty()) {
+        s.push('/');
+        s.push_str(segment);
+    }
+    if s.is_empty() {
+        s.push('/');
+    }
+    s
+}
+
 #[inline]
 fn find(a: &[u8], b: u8) -> Option<usize> {
     a.iter().position(|&a| a == b)
 }
 
 #[inline]
-fn to_str<'a>(bytes: &'a [u8]) -> &'a str {
+fn to_str(bytes: &[u8]) -> &str {
     unsafe { std::str::from_utf8_unchecked(bytes) }
 }
 
@@ -391,7 +761,7 @@ impl Segmented for String {
         for i in 0..self.len() {
             if &self[i..i + 1] == "/" {
                 s = i + 1;
-                c = c + 1;
+                c += 1;
                 if c == idx {
                     return &self[s..];
                 }
@@ -404,12 +774,12 @@ impl Segmented for String {
         let mut c = 0;
         for i in 0..self.len() {
             if &self[i..i + 1] == "/" {
-                c = c + 1;
+                c += 1;
                 if c == idx {
                     return &self[0..i];
                 }
             }
         }
-        &self
+        self
     }
 }
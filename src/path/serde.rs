@@ -0,0 +1,61 @@
+use super::PathTrie;
+use serde::de::{self, Deserialize, Deserializer, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeSeq, Serializer};
+use std::fmt;
+use std::marker::PhantomData;
+
+/// Serializes as a sequence of `(pattern, value)` pairs.
+impl<T> Serialize for PathTrie<T>
+where
+    T: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut seq = serializer.serialize_seq(Some(self.len()))?;
+        for entry in self.iter() {
+            seq.serialize_element(&entry)?;
+        }
+        seq.end()
+    }
+}
+
+/// Rebuilds the trie by inserting each pattern in turn, failing on patterns
+/// that [`PathTrie::insert`] rejects.
+impl<'de, T> Deserialize<'de> for PathTrie<T>
+where
+    T: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(PathVisitor(PhantomData))
+    }
+}
+
+struct PathVisitor<T>(PhantomData<T>);
+
+impl<'de, T> Visitor<'de> for PathVisitor<T>
+where
+    T: Deserialize<'de>,
+{
+    type Value = PathTrie<T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a sequence of (pattern, value) pairs")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut trie = PathTrie::new();
+        while let Some((pattern, value)) = seq.next_element::<(String, T)>()? {
+            trie.insert(&pattern, value)
+                .map_err(|err| de::Error::custom(format_args!("{}: {}", pattern, err)))?;
+        }
+        Ok(trie)
+    }
+}
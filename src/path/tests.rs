@@ -0,0 +1,167 @@
+use crate::path::{PathMap, PathTrie};
+use crate::{TrieError, TrieExt};
+
+#[test]
+fn path_find_remove() {
+    let mut trie = PathTrie::new();
+    trie.insert("/users", 1).unwrap();
+    trie.insert("/users/:id", 2).unwrap();
+    trie.insert("/users/:id/posts", 3).unwrap();
+
+    assert_eq!(trie.len(), 3);
+    assert_eq!(trie.find("/users/:id"), Some(&2));
+    assert_eq!(trie.find("/users/5"), None);
+
+    *trie.find_mut("/users").unwrap() = 4;
+    assert_eq!(trie.get("/users").map(|(v, _)| *v), Some(4));
+
+    assert_eq!(trie.remove("/users/:id/posts"), Ok(3));
+    assert_eq!(trie.remove("/users/:id/posts"), Err(TrieError::KeyNotFound));
+    assert_eq!(trie.remove("/users/:name"), Err(TrieError::KeyNotFound));
+    assert!(trie.get("/users/5/posts").is_none());
+    assert_eq!(trie.get("/users/5").map(|(v, _)| *v), Some(2));
+    assert_eq!(trie.len(), 2);
+}
+
+#[test]
+fn path_map() {
+    let mut map = PathMap::new();
+    map.insert(b"/a/b", 1).unwrap();
+    map.insert(b"/a/:id", 2).unwrap();
+    map.insert(b"/a/:id/c", 3).unwrap();
+
+    assert_eq!(map.insert(b"/a/b", 4), Ok(Some(1)));
+    assert_eq!(map.len(), 3);
+    assert!(map.contains_key(b"/a/:id"));
+
+    *map.get_mut(b"/a/:id/c").unwrap() += 1;
+    assert_eq!(map.get(b"/a/:id/c"), Some(&4));
+
+    let removed = map.remove(b"/a/:id", true).unwrap();
+    assert_eq!(removed.len(), 2);
+    assert_eq!(removed.get(b"/a/:id/c"), Some(&4));
+    assert_eq!(map.len(), 1);
+
+    map.clear();
+    assert!(map.is_empty());
+}
+
+#[test]
+fn path_insert_errors() {
+    let mut trie = PathTrie::new();
+    trie.insert("/files/:id", 1).unwrap();
+    trie.insert("/static/*path", 2).unwrap();
+
+    assert_eq!(
+        trie.insert("/files/:name", 3),
+        Err(TrieError::ConflictingPattern)
+    );
+    assert_eq!(
+        trie.insert("/files/*rest", 3),
+        Err(TrieError::ConflictingPattern)
+    );
+    assert_eq!(
+        trie.insert("/static/:id", 3),
+        Err(TrieError::ConflictingPattern)
+    );
+    assert_eq!(trie.insert("/files/:", 3), Err(TrieError::InvalidKey));
+    assert_eq!(
+        trie.insert("/static/*path/more", 3),
+        Err(TrieError::InvalidKey)
+    );
+
+    assert_eq!(trie.insert("/files/:id", 4), Ok(Some(1)));
+    assert_eq!(trie.get("/files/7").map(|(v, _)| *v), Some(4));
+    assert_eq!(trie.get("/static/a/b").map(|(v, _)| *v), Some(2));
+    assert_eq!(trie.len(), 2);
+
+    let mut map = PathMap::new();
+    assert_eq!(map.insert(&[0xff], 1), Err(TrieError::InvalidKey));
+}
+
+#[test]
+fn path_collection_traits() {
+    let routes = [
+        ("/", 0),
+        ("/users/:id", 1),
+        ("/users", 2),
+        ("/files/*path", 3),
+    ];
+    let trie: PathTrie<i32> = routes.iter().cloned().collect();
+    let other: PathTrie<i32> = routes.iter().rev().cloned().collect();
+
+    assert_eq!(trie, other);
+    assert_eq!(trie.clone(), trie);
+    assert_eq!(trie["/users/:id"], 1);
+    assert_eq!(trie["/"], 0);
+
+    let mut patterns: Vec<_> = trie.iter().map(|(p, v)| (p, *v)).collect();
+    patterns.sort();
+    let mut owned: Vec<_> = trie.into_iter().collect();
+    owned.sort();
+    assert_eq!(patterns, owned);
+    assert_eq!(
+        owned,
+        vec![
+            ("/".to_string(), 0),
+            ("/files/*path".to_string(), 3),
+            ("/users".to_string(), 2),
+            ("/users/:id".to_string(), 1),
+        ]
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn path_serde() {
+    let trie: PathTrie<u32> = [("/a/:id", 1), ("/a/b", 2), ("/", 3)].into_iter().collect();
+
+    let json = serde_json::to_string(&trie).unwrap();
+    let decoded: PathTrie<u32> = serde_json::from_str(&json).unwrap();
+    assert_eq!(decoded, trie);
+    assert_eq!(decoded.get("/a/7").map(|(v, _)| *v), Some(1));
+    assert_eq!(decoded.get("/a/b").map(|(v, _)| *v), Some(2));
+
+    let err = serde_json::from_str::<PathTrie<u32>>(r#"[["/a/:x", 1], ["/a/:y", 2]]"#);
+    assert!(err.unwrap_err().to_string().contains("/a/:y"));
+}
+
+#[test]
+fn path_stats() {
+    let mut trie = PathTrie::new();
+    trie.insert("/users", 1).unwrap();
+    trie.insert("/users/:id", 2).unwrap();
+    trie.insert("/users/:id/posts", 3).unwrap();
+    trie.insert("/about", 4).unwrap();
+
+    let stats = trie.stats();
+    assert_eq!(stats.nodes, 5);
+    assert_eq!(stats.values, 4);
+    assert_eq!(stats.max_depth, 3);
+    assert_eq!(stats.avg_depth, 1.75);
+    assert_eq!(stats.fanout, vec![2, 2, 1]);
+    assert_eq!(stats.key_bytes, "users:idpostsabout".len());
+    assert!(stats.heap_bytes > stats.key_bytes);
+}
+
+#[test]
+fn path_render() {
+    let mut trie = PathTrie::new();
+    trie.insert("/users", 1).unwrap();
+    trie.insert("/users/:id", 2).unwrap();
+    trie.insert("/about", 3).unwrap();
+
+    let tree = "\
+/ (#0)
+├── users (#1) = 1
+│   └── :id (#2) = 2
+└── about (#3) = 3
+";
+    assert_eq!(trie.to_string(), tree);
+
+    let dot = trie.to_dot();
+    assert!(dot.contains("node [shape=record"));
+    assert!(dot.contains("    n0 [label=\"{#0 | index: u a}\"];\n"));
+    assert!(dot.contains("    n1 [label=\"{#1 | index: : | 1}\", peripheries=2];\n"));
+    assert!(dot.contains("    n1 -> n2 [label=\":id\"];\n"));
+}
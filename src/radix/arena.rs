@@ -0,0 +1,582 @@
+use super::{longest_match, RadixNode};
+use crate::memory::{slot_size, MemoryUsage};
+use crate::{TrieError, TrieExt};
+use slab::Slab;
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::mem;
+use std::ops::Index;
+
+/// Marks the end of a child or sibling list.
+const NIL: u32 = u32::MAX;
+
+/// Largest number of nodes, or of edge elements, addressable by a `u32`.
+const MAX_NODES: usize = u32::MAX as usize;
+
+/// A radix trie like [`RadixNode`] whose nodes live in one slab and refer to
+/// each other by `u32` index.
+///
+/// Edge keys are ranges into a single shared buffer instead of a `Vec` per
+/// node, so splitting an edge only splits its range. Removals that merge two
+/// edges may append the joined edge to the buffer and leave the old ranges
+/// behind; [`ArenaRadix::shrink_to_fit`] reclaims them.
+#[derive(Debug, Clone)]
+pub struct ArenaRadix<K, V> {
+    nodes: Slab<Node<V>>,
+    labels: Vec<K>,
+    len: usize,
+    max_nodes: usize,
+}
+
+#[derive(Debug, Clone)]
+struct Node<V> {
+    start: u32,
+    end: u32,
+    value: Option<V>,
+    child: u32,
+    next: u32,
+}
+
+impl<V> Node<V> {
+    fn new(start: usize, end: usize, value: Option<V>) -> Self {
+        Self {
+            start: start as u32,
+            end: end as u32,
+            value,
+            child: NIL,
+            next: NIL,
+        }
+    }
+}
+
+impl<K, V> Default for ArenaRadix<K, V>
+where
+    K: Clone + Ord,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> ArenaRadix<K, V>
+where
+    K: Clone + Ord,
+{
+    pub fn new() -> Self {
+        Self::with_max_nodes(MAX_NODES)
+    }
+
+    /// Creates a trie that refuses to grow past `max_nodes` nodes, the root
+    /// included; `insert` returns [`TrieError::CapacityExceeded`] instead.
+    pub fn with_max_nodes(max_nodes: usize) -> Self {
+        Self::with_root(&[], None, max_nodes)
+    }
+
+    fn with_root(key: &[K], value: Option<V>, max_nodes: usize) -> Self {
+        let mut nodes = Slab::new();
+        nodes.insert(Node::new(0, key.len(), value));
+
+        Self {
+            nodes,
+            labels: key.to_vec(),
+            len: 0,
+            max_nodes: max_nodes.min(MAX_NODES),
+        }
+    }
+
+    pub fn key(&self) -> &[K] {
+        self.edge(0)
+    }
+
+    /// Iterates over every stored key and value in key order, starting with
+    /// the empty key if it holds a value.
+    pub fn iter(&self) -> ArenaIter<'_, K, V> {
+        ArenaIter {
+            radix: self,
+            key: Vec::new(),
+            root: self.nodes[0].value.as_ref(),
+            stack: vec![(self.nodes[0].child, 0)],
+        }
+    }
+
+    /// Compacts the arena so that nodes occupy consecutive slots and edges
+    /// are laid out back to back, then releases the unused capacity.
+    pub fn shrink_to_fit(&mut self) {
+        let mut nodes = Slab::with_capacity(self.nodes.len());
+        let mut labels = Vec::new();
+
+        move_tree(&mut self.nodes, &self.labels, 0, &mut nodes, &mut labels);
+
+        labels.shrink_to_fit();
+        self.nodes = nodes;
+        self.labels = labels;
+    }
+
+    pub fn memory_usage(&self) -> MemoryUsage {
+        let nodes = self.nodes.len();
+        let edges: usize = self
+            .nodes
+            .iter()
+            .map(|(_, n)| (n.end - n.start) as usize)
+            .sum();
+
+        MemoryUsage {
+            nodes,
+            capacity: self.nodes.capacity(),
+            arena_bytes: self.nodes.capacity() * slot_size::<Node<V>>()
+                + self.labels.capacity() * mem::size_of::<K>(),
+            // every node but the root sits in its parent's `children` vector
+            // and owns a separate allocation for its edge
+            boxed_bytes: (nodes - 1) * mem::size_of::<RadixNode<K, V>>()
+                + edges * mem::size_of::<K>(),
+        }
+    }
+
+    fn edge(&self, idx: u32) -> &[K] {
+        let node = &self.nodes[idx as usize];
+        &self.labels[node.start as usize..node.end as usize]
+    }
+
+    /// Looks for the child of `parent` whose edge starts with `first`,
+    /// returning it with the sibling before it. On a miss, returns the
+    /// sibling after which such a child belongs. [`NIL`] stands for "no
+    /// previous sibling".
+    fn find(&self, parent: u32, first: &K) -> Result<(u32, u32), u32> {
+        let mut prev = NIL;
+        let mut cur = self.nodes[parent as usize].child;
+
+        while cur != NIL {
+            match self.edge(cur)[0].cmp(first) {
+                Ordering::Less => {
+                    prev = cur;
+                    cur = self.nodes[cur as usize].next;
+                }
+                Ordering::Equal => return Ok((prev, cur)),
+                Ordering::Greater => break,
+            }
+        }
+
+        Err(prev)
+    }
+
+    /// Returns the index of the node whose path spells exactly `key`.
+    fn locate(&self, key: &[K]) -> Option<u32> {
+        let mut rem = key;
+        let mut node = 0;
+
+        while !rem.is_empty() {
+            let (_, idx) = self.find(node, &rem[0]).ok()?;
+            rem = rem.strip_prefix(self.edge(idx))?;
+            node = idx;
+        }
+
+        Some(node)
+    }
+
+    fn link(&mut self, parent: u32, prev: u32, idx: u32) {
+        let next = match prev {
+            NIL => mem::replace(&mut self.nodes[parent as usize].child, idx),
+            prev => mem::replace(&mut self.nodes[prev as usize].next, idx),
+        };
+        self.nodes[idx as usize].next = next;
+    }
+
+    fn unlink(&mut self, parent: u32, prev: u32, idx: u32) {
+        let next = self.nodes[idx as usize].next;
+        match prev {
+            NIL => self.nodes[parent as usize].child = next,
+            prev => self.nodes[prev as usize].next = next,
+        }
+    }
+
+    fn reserve(&self, nodes: usize, labels: usize) -> Result<(), TrieError> {
+        match self.nodes.len() + nodes <= self.max_nodes && self.labels.len() + labels <= MAX_NODES
+        {
+            true => Ok(()),
+            false => Err(TrieError::CapacityExceeded),
+        }
+    }
+
+    /// Stores `edge` in the label buffer and returns a leaf over it.
+    fn leaf(&mut self, edge: &[K], value: V) -> u32 {
+        let start = self.labels.len();
+        self.labels.extend_from_slice(edge);
+        let node = Node::new(start, self.labels.len(), Some(value));
+        self.nodes.insert(node) as u32
+    }
+
+    /// Restores the radix invariants for a node below the root that lost its
+    /// value or a child: without a value it is dropped if it has no children
+    /// and merged with its child if it has exactly one.
+    fn compact(&mut self, parent: u32, prev: u32, idx: u32) {
+        let node = &self.nodes[idx as usize];
+        if node.value.is_some() {
+            return;
+        }
+
+        let child = node.child;
+        if child == NIL {
+            self.unlink(parent, prev, idx);
+            self.nodes.remove(idx as usize);
+            return;
+        }
+
+        if self.nodes[child as usize].next != NIL {
+            return;
+        }
+
+        let (start, end) = match node.end == self.nodes[child as usize].start {
+            // the two edges are still adjacent from an earlier split
+            true => (node.start as usize, self.nodes[child as usize].end as usize),
+            false => {
+                let len = self.edge(idx).len() + self.edge(child).len();
+                if self.reserve(0, len).is_err() {
+                    // leave the edges unmerged rather than fail a removal
+                    return;
+                }
+
+                let start = self.labels.len();
+                let (a, b) = (self.nodes[idx as usize].start, self.nodes[idx as usize].end);
+                self.labels.extend_from_within(a as usize..b as usize);
+                let (a, b) = (
+                    self.nodes[child as usize].start,
+                    self.nodes[child as usize].end,
+                );
+                self.labels.extend_from_within(a as usize..b as usize);
+                (start, self.labels.len())
+            }
+        };
+
+        let child = self.nodes.remove(child as usize);
+        let node = &mut self.nodes[idx as usize];
+        node.start = start as u32;
+        node.end = end as u32;
+        node.value = child.value;
+        node.child = child.child;
+    }
+
+    /// Moves the subtree rooted at `idx`, which must already be unlinked,
+    /// into a trie of its own.
+    fn split_tree(&mut self, idx: u32) -> Self {
+        let mut nodes = Slab::new();
+        let mut labels = Vec::new();
+
+        let len = move_tree(&mut self.nodes, &self.labels, idx, &mut nodes, &mut labels);
+        self.len -= len;
+
+        Self {
+            nodes,
+            labels,
+            len,
+            max_nodes: self.max_nodes,
+        }
+    }
+}
+
+/// Moves the subtree rooted at `root` from one arena into another, copying
+/// its edges to the end of `into_labels`. Returns the number of values moved.
+fn move_tree<K, V>(
+    from: &mut Slab<Node<V>>,
+    from_labels: &[K],
+    root: u32,
+    into: &mut Slab<Node<V>>,
+    into_labels: &mut Vec<K>,
+) -> usize
+where
+    K: Clone,
+{
+    let mut moved = |node: &mut Node<V>| {
+        let start = into_labels.len();
+        into_labels.extend_from_slice(&from_labels[node.start as usize..node.end as usize]);
+        node.start = start as u32;
+        node.end = into_labels.len() as u32;
+        (
+            mem::replace(&mut node.child, NIL),
+            mem::replace(&mut node.next, NIL),
+        )
+    };
+
+    let mut node = from.remove(root as usize);
+    let mut values = usize::from(node.value.is_some());
+
+    let (first, _) = moved(&mut node);
+    let new_root = into.insert(node) as u32;
+
+    // each entry is a sibling list still in `from` and its new parent
+    let mut stack = vec![(first, new_root)];
+
+    while let Some((mut cur, parent)) = stack.pop() {
+        let mut prev = NIL;
+
+        while cur != NIL {
+            let mut node = from.remove(cur as usize);
+            values += usize::from(node.value.is_some());
+
+            let (child, next) = moved(&mut node);
+            cur = next;
+
+            let idx = into.insert(node) as u32;
+            match prev {
+                NIL => into[parent as usize].child = idx,
+                prev => into[prev as usize].next = idx,
+            }
+
+            prev = idx;
+            stack.push((child, idx));
+        }
+    }
+
+    values
+}
+
+impl<K, V> TrieExt<K, V> for ArenaRadix<K, V>
+where
+    K: Clone + Ord,
+{
+    fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        Q: Borrow<[K]>,
+    {
+        let idx = self.locate(key.borrow())?;
+        self.nodes[idx as usize].value.as_ref()
+    }
+
+    fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        Q: Borrow<[K]>,
+    {
+        let idx = self.locate(key.borrow())?;
+        self.nodes[idx as usize].value.as_mut()
+    }
+
+    fn insert<Q>(&mut self, key: &Q, value: V) -> Result<Option<V>, TrieError>
+    where
+        Q: Borrow<[K]> + ?Sized,
+    {
+        let mut rem = key.borrow();
+        let mut node = 0;
+
+        let idx = loop {
+            if rem.is_empty() {
+                let old = self.nodes[node as usize].value.replace(value);
+                if old.is_none() {
+                    self.len += 1;
+                }
+                return Ok(old);
+            }
+
+            let idx = match self.find(node, &rem[0]) {
+                Ok((_, idx)) => idx,
+                Err(prev) => {
+                    self.reserve(1, rem.len())?;
+                    let leaf = self.leaf(rem, value);
+                    self.link(node, prev, leaf);
+                    self.len += 1;
+                    return Ok(None);
+                }
+            };
+
+            let lcs = longest_match(self.edge(idx), rem);
+            if lcs < self.edge(idx).len() {
+                break idx;
+            }
+
+            rem = &rem[lcs..];
+            node = idx;
+        };
+
+        // split the edge: `idx` keeps the shared part and a new node takes the
+        // rest of its range along with its value and children
+        let lcs = longest_match(self.edge(idx), rem);
+        let rest = &rem[lcs..];
+        self.reserve(1 + usize::from(!rest.is_empty()), rest.len())?;
+
+        let split = &mut self.nodes[idx as usize];
+        let mid = split.start + lcs as u32;
+        let mut tail = Node::new(mid as usize, split.end as usize, split.value.take());
+        tail.child = split.child;
+        split.end = mid;
+
+        let tail = self.nodes.insert(tail) as u32;
+        self.nodes[idx as usize].child = tail;
+
+        if rest.is_empty() {
+            self.nodes[idx as usize].value = Some(value);
+        } else {
+            let leaf = self.leaf(rest, value);
+            let prev = match self.edge(leaf)[0] < self.edge(tail)[0] {
+                true => NIL,
+                false => tail,
+            };
+            self.link(idx, prev, leaf);
+        }
+
+        self.len += 1;
+        Ok(None)
+    }
+
+    fn remove<Q>(&mut self, key: &Q, prune: bool) -> Option<Self>
+    where
+        Q: Borrow<[K]>,
+    {
+        let key = key.borrow();
+
+        // the empty key lives on the root, which is never detached
+        if key.is_empty() {
+            let root = self.key().to_vec();
+            if prune {
+                let empty = Self::with_root(&root, None, self.max_nodes);
+                return match self.is_empty() {
+                    true => None,
+                    false => Some(mem::replace(self, empty)),
+                };
+            }
+
+            let value = self.nodes[0].value.take()?;
+            self.len -= 1;
+
+            let mut removed = Self::with_root(&root, Some(value), self.max_nodes);
+            removed.len = 1;
+            return Some(removed);
+        }
+
+        // (parent, previous sibling, node) for each edge along the key
+        let mut path = Vec::new();
+        let mut rem = key;
+        let mut node = 0;
+
+        while !rem.is_empty() {
+            let (prev, idx) = self.find(node, &rem[0]).ok()?;
+            rem = rem.strip_prefix(self.edge(idx))?;
+            path.push((node, prev, idx));
+            node = idx;
+        }
+
+        let (parent, prev, idx) = path.pop()?;
+
+        let removed = match prune {
+            true => {
+                self.unlink(parent, prev, idx);
+                self.split_tree(idx)
+            }
+            false => {
+                let value = self.nodes[idx as usize].value.take()?;
+                self.len -= 1;
+
+                let mut removed = Self::with_root(self.edge(idx), Some(value), self.max_nodes);
+                removed.len = 1;
+                self.compact(parent, prev, idx);
+                removed
+            }
+        };
+
+        // the parent may now be a value-less node with a single child
+        if let Some((grandparent, prev, parent)) = path.pop() {
+            self.compact(grandparent, prev, parent);
+        }
+
+        Some(removed)
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn clear(&mut self) {
+        let root = self.key().to_vec();
+        *self = Self::with_root(&root, None, self.max_nodes);
+    }
+}
+
+impl<K, V, Q> FromIterator<(Q, V)> for ArenaRadix<K, V>
+where
+    K: Clone + Ord,
+    Q: AsRef<[K]>,
+{
+    fn from_iter<I: IntoIterator<Item = (Q, V)>>(iter: I) -> Self {
+        let mut radix = ArenaRadix::new();
+        radix.extend(iter);
+        radix
+    }
+}
+
+impl<K, V, Q> Extend<(Q, V)> for ArenaRadix<K, V>
+where
+    K: Clone + Ord,
+    Q: AsRef<[K]>,
+{
+    /// # Panics
+    ///
+    /// Panics if the trie runs out of node capacity.
+    fn extend<I: IntoIterator<Item = (Q, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key.as_ref(), value)
+                .expect("arena radix capacity exceeded");
+        }
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a ArenaRadix<K, V>
+where
+    K: Clone + Ord,
+{
+    type Item = (Vec<K>, &'a V);
+    type IntoIter = ArenaIter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K, V> Index<&[K]> for ArenaRadix<K, V>
+where
+    K: Clone + Ord,
+{
+    type Output = V;
+
+    fn index(&self, key: &[K]) -> &V {
+        self.get(&key).expect("key not found in trie")
+    }
+}
+
+/// Borrowing iterator over the entries of an [`ArenaRadix`], in key order.
+pub struct ArenaIter<'a, K, V> {
+    radix: &'a ArenaRadix<K, V>,
+    key: Vec<K>,
+    root: Option<&'a V>,
+    /// Sibling lists still to visit, with the key length at their level.
+    stack: Vec<(u32, usize)>,
+}
+
+impl<'a, K, V> Iterator for ArenaIter<'a, K, V>
+where
+    K: Clone + Ord,
+{
+    type Item = (Vec<K>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(value) = self.root.take() {
+            return Some((Vec::new(), value));
+        }
+
+        loop {
+            let (idx, len) = self.stack.pop()?;
+            if idx == NIL {
+                continue;
+            }
+
+            let node = &self.radix.nodes[idx as usize];
+
+            self.key.truncate(len);
+            self.key.extend_from_slice(self.radix.edge(idx));
+
+            // descendants come before the next sibling
+            self.stack.push((node.next, len));
+            self.stack.push((node.child, self.key.len()));
+
+            if let Some(value) = &node.value {
+                return Some((self.key.clone(), value));
+            }
+        }
+    }
+}
@@ -0,0 +1,182 @@
+use super::RadixNode;
+use crate::codec::{crc32, write_varint, Cursor, FormatError, ValueCodec};
+use std::io::{Read, Write};
+
+const MAGIC: [u8; 4] = *b"TRIX";
+const VERSION: u16 = 1;
+const HEADER_LEN: usize = 20;
+
+const HAS_VALUE: u8 = 1;
+
+impl<V> RadixNode<u8, V> {
+    /// Writes the trie in a compact, versioned binary format, encoding each
+    /// value with `codec`.
+    ///
+    /// The output is a 20 byte header followed by the payload. All integers in
+    /// the header are little-endian:
+    ///
+    /// | bytes  | field                                |
+    /// |--------|--------------------------------------|
+    /// | 0..4   | magic `TRIX`                         |
+    /// | 4..6   | format version, currently 1          |
+    /// | 6..8   | flags, currently 0                   |
+    /// | 8..16  | payload length in bytes              |
+    /// | 16..20 | CRC-32 (IEEE) of the payload         |
+    ///
+    /// The payload lists every node in pre-order, starting with the root.
+    /// Each node is its edge length as a LEB128 varint, the edge bytes, a flag
+    /// byte (bit 0 set if the node holds a value), the value length and
+    /// encoded value when present, and finally its child count. Children
+    /// follow their parent in key order, so a shared prefix is stored once
+    /// on the edge leading to it.
+    pub fn write_to<W, C>(&self, mut writer: W, codec: &C) -> Result<(), FormatError>
+    where
+        W: Write,
+        C: ValueCodec<V>,
+    {
+        let mut payload = Vec::new();
+        let mut value = Vec::new();
+        self.encode(&mut payload, &mut value, codec);
+
+        let mut header = Vec::with_capacity(HEADER_LEN);
+        header.extend_from_slice(&MAGIC);
+        header.extend_from_slice(&VERSION.to_le_bytes());
+        header.extend_from_slice(&0u16.to_le_bytes());
+        header.extend_from_slice(&(payload.len() as u64).to_le_bytes());
+        header.extend_from_slice(&crc32(&payload).to_le_bytes());
+
+        writer.write_all(&header)?;
+        writer.write_all(&payload)?;
+        Ok(())
+    }
+
+    /// Reads a trie written by [`RadixNode::write_to`], checking the header,
+    /// checksum and structure before returning it.
+    pub fn read_from<R, C>(mut reader: R, codec: &C) -> Result<Self, FormatError>
+    where
+        R: Read,
+        C: ValueCodec<V>,
+    {
+        let mut header = [0u8; HEADER_LEN];
+        reader.read_exact(&mut header)?;
+
+        if header[0..4] != MAGIC {
+            return Err(FormatError::BadMagic);
+        }
+
+        let version = u16::from_le_bytes([header[4], header[5]]);
+        if version != VERSION {
+            return Err(FormatError::UnsupportedVersion(version));
+        }
+
+        if header[6..8] != [0, 0] {
+            return Err(FormatError::Corrupt("unknown header flags"));
+        }
+
+        let len = u64::from_le_bytes(header[8..16].try_into().unwrap());
+        let expected = u32::from_le_bytes(header[16..20].try_into().unwrap());
+
+        // read through `take` so a corrupt length cannot force a huge allocation
+        let mut payload = Vec::new();
+        reader.take(len).read_to_end(&mut payload)?;
+        if payload.len() as u64 != len {
+            return Err(FormatError::Truncated);
+        }
+
+        let actual = crc32(&payload);
+        if actual != expected {
+            return Err(FormatError::ChecksumMismatch { expected, actual });
+        }
+
+        Self::decode(&payload, codec)
+    }
+
+    fn encode<C>(&self, out: &mut Vec<u8>, value: &mut Vec<u8>, codec: &C)
+    where
+        C: ValueCodec<V>,
+    {
+        write_varint(out, self.key.len() as u64);
+        out.extend_from_slice(&self.key);
+
+        match &self.value {
+            Some(v) => {
+                out.push(HAS_VALUE);
+                value.clear();
+                codec.encode(v, value);
+                write_varint(out, value.len() as u64);
+                out.extend_from_slice(value);
+            }
+            None => out.push(0),
+        }
+
+        write_varint(out, self.children.len() as u64);
+        for child in &self.children {
+            child.encode(out, value, codec);
+        }
+    }
+
+    fn decode<C>(payload: &[u8], codec: &C) -> Result<Self, FormatError>
+    where
+        C: ValueCodec<V>,
+    {
+        let mut cursor = Cursor::new(payload);
+        // nodes whose children are still being read, with the count left
+        let mut stack: Vec<(Self, usize)> = Vec::new();
+
+        let root = 'outer: loop {
+            let key = cursor.length()?;
+            let key = cursor.take(key)?.to_vec();
+
+            if !stack.is_empty() && key.is_empty() {
+                return Err(FormatError::Corrupt("empty edge below the root"));
+            }
+
+            let value = match cursor.byte()? {
+                0 => None,
+                HAS_VALUE => {
+                    let len = cursor.length()?;
+                    Some(codec.decode(cursor.take(len)?)?)
+                }
+                _ => return Err(FormatError::Corrupt("unknown node flags")),
+            };
+
+            let count = cursor.length()?;
+            if !stack.is_empty() && value.is_none() && count < 2 {
+                return Err(FormatError::Corrupt(
+                    "value-less node with fewer than two children",
+                ));
+            }
+
+            let node = RadixNode {
+                key,
+                value,
+                children: Vec::new(),
+            };
+            stack.push((node, count));
+
+            while let Some((_, 0)) = stack.last() {
+                let (node, _) = stack.pop().unwrap();
+
+                let (parent, remaining) = match stack.last_mut() {
+                    Some(parent) => parent,
+                    None => break 'outer node,
+                };
+
+                if let Some(prev) = parent.children.last() {
+                    if prev.key[0] >= node.key[0] {
+                        return Err(FormatError::Corrupt("children out of order"));
+                    }
+                }
+
+                parent.children.push(node);
+                *remaining -= 1;
+            }
+        };
+
+        if !cursor.is_empty() {
+            return Err(FormatError::Corrupt("trailing bytes after the root"));
+        }
+
+        Ok(root)
+    }
+}
@@ -0,0 +1,370 @@
+use super::longest_match;
+use std::borrow::Borrow;
+use std::mem;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::vec;
+
+/// A radix trie that can be read and written from many threads at once.
+///
+/// Every node has its own lock, and operations walk down the tree by lock
+/// coupling: the lock on a child is taken before the one on its parent is
+/// released. Readers share locks, so lookups on disjoint keys run in
+/// parallel, and a writer holds at most two nodes at a time, so writers only
+/// wait for each other where their paths overlap. Every operation takes
+/// effect atomically at some point between its call and its return.
+///
+/// Locks are always taken from parent to child, which rules out deadlock.
+#[derive(Debug)]
+pub struct ConcurrentRadix<K, V> {
+    root: Node<K, V>,
+    len: AtomicUsize,
+}
+
+type Node<K, V> = RwLock<Inner<K, V>>;
+
+#[derive(Debug)]
+struct Inner<K, V> {
+    key: Vec<K>,
+    value: Option<V>,
+    /// Children keyed by the first element of their edge, which never
+    /// changes while the child is linked, so siblings can be searched
+    /// without locking them.
+    children: Vec<(K, Arc<Node<K, V>>)>,
+}
+
+impl<K, V> Inner<K, V> {
+    fn new(key: Vec<K>, value: Option<V>) -> Self {
+        Self {
+            key,
+            value,
+            children: Vec::new(),
+        }
+    }
+}
+
+impl<K, V> Default for Inner<K, V> {
+    fn default() -> Self {
+        Self::new(Vec::new(), None)
+    }
+}
+
+impl<K, V> Default for ConcurrentRadix<K, V>
+where
+    K: Clone + Ord,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> ConcurrentRadix<K, V>
+where
+    K: Clone + Ord,
+{
+    pub fn new() -> Self {
+        Self {
+            root: RwLock::new(Inner::default()),
+            len: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the number of values stored in the trie. The count is updated
+    /// after each write, so it may lag behind writers still running.
+    pub fn len(&self) -> usize {
+        self.len.load(Ordering::Acquire)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn insert<Q>(&self, key: &Q, value: V) -> Option<V>
+    where
+        Q: Borrow<[K]> + ?Sized,
+    {
+        let old = insert_at(write(&self.root), key.borrow(), value);
+        if old.is_none() {
+            self.len.fetch_add(1, Ordering::AcqRel);
+        }
+        old
+    }
+
+    pub fn remove<Q>(&self, key: &Q) -> Option<V>
+    where
+        Q: Borrow<[K]> + ?Sized,
+    {
+        let key = key.borrow();
+        let mut root = write(&self.root);
+
+        let old = match key.is_empty() {
+            true => root.value.take(),
+            false => remove_at(root, key, true),
+        };
+        if old.is_some() {
+            self.len.fetch_sub(1, Ordering::AcqRel);
+        }
+        old
+    }
+}
+
+impl<K, V> ConcurrentRadix<K, V>
+where
+    K: Clone + Ord,
+    V: Clone,
+{
+    /// Returns a copy of the value stored under `key`.
+    pub fn get<Q>(&self, key: &Q) -> Option<V>
+    where
+        Q: Borrow<[K]> + ?Sized,
+    {
+        get_at(read(&self.root), key.borrow())
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        Q: Borrow<[K]> + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+
+    /// Iterates over a snapshot of every entry whose key starts with
+    /// `prefix`, in key order.
+    ///
+    /// The snapshot is taken atomically: writers already below the prefix
+    /// finish first, and new ones wait until it has been copied.
+    pub fn iter_prefix<Q>(&self, prefix: &Q) -> vec::IntoIter<(Vec<K>, V)>
+    where
+        Q: Borrow<[K]> + ?Sized,
+    {
+        let mut entries = Vec::new();
+        prefix_at(read(&self.root), prefix.borrow(), Vec::new(), &mut entries);
+        entries.into_iter()
+    }
+
+    /// Iterates over a snapshot of every entry in key order; see
+    /// [`ConcurrentRadix::iter_prefix`].
+    pub fn iter(&self) -> vec::IntoIter<(Vec<K>, V)> {
+        self.iter_prefix(&[][..])
+    }
+}
+
+// a panic while a lock is held cannot leave a node half-updated, since every
+// change is made after all the allocations and comparisons it depends on
+fn read<K, V>(node: &Node<K, V>) -> RwLockReadGuard<'_, Inner<K, V>> {
+    node.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn write<K, V>(node: &Node<K, V>) -> RwLockWriteGuard<'_, Inner<K, V>> {
+    node.write().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn find_child<K, V>(children: &[(K, Arc<Node<K, V>>)], first: &K) -> Result<usize, usize>
+where
+    K: Ord,
+{
+    children.binary_search_by(|(k, _)| k.cmp(first))
+}
+
+fn leaf<K, V>(key: &[K], value: V) -> (K, Arc<Node<K, V>>)
+where
+    K: Clone,
+{
+    let node = Inner::new(key.to_vec(), Some(value));
+    (key[0].clone(), Arc::new(RwLock::new(node)))
+}
+
+fn get_at<K, V>(node: RwLockReadGuard<'_, Inner<K, V>>, rem: &[K]) -> Option<V>
+where
+    K: Clone + Ord,
+    V: Clone,
+{
+    if rem.is_empty() {
+        return node.value.clone();
+    }
+
+    let idx = find_child(&node.children, &rem[0]).ok()?;
+    let child = Arc::clone(&node.children[idx].1);
+    let guard = read(&child);
+    drop(node);
+
+    let rem = rem.strip_prefix(&guard.key[..])?;
+    get_at(guard, rem)
+}
+
+fn insert_at<K, V>(mut node: RwLockWriteGuard<'_, Inner<K, V>>, k: &[K], value: V) -> Option<V>
+where
+    K: Clone + Ord,
+{
+    if k.is_empty() {
+        return node.value.replace(value);
+    }
+
+    let idx = match find_child(&node.children, &k[0]) {
+        Ok(idx) => idx,
+        Err(pos) => {
+            node.children.insert(pos, leaf(k, value));
+            return None;
+        }
+    };
+
+    let child = Arc::clone(&node.children[idx].1);
+    let mut guard = write(&child);
+    drop(node);
+
+    let lcs = longest_match(&guard.key, k);
+    if lcs == guard.key.len() {
+        return insert_at(guard, &k[lcs..], value);
+    }
+
+    // split the edge in place: the child keeps the shared part, and thus its
+    // first element, so the parent needs no change
+    let tail = Inner {
+        key: guard.key.split_off(lcs),
+        value: guard.value.take(),
+        children: mem::take(&mut guard.children),
+    };
+    let first = tail.key[0].clone();
+    guard.children.push((first, Arc::new(RwLock::new(tail))));
+
+    if lcs == k.len() {
+        guard.value = Some(value);
+    } else {
+        let new = leaf(&k[lcs..], value);
+        let pos = usize::from(guard.children[0].0 < new.0);
+        guard.children.insert(pos, new);
+    }
+
+    None
+}
+
+/// Removes the value at `rem` below `node`, holding the locks on both the
+/// parent and the child so that a child left empty can be unlinked.
+fn remove_at<K, V>(mut node: RwLockWriteGuard<'_, Inner<K, V>>, rem: &[K], root: bool) -> Option<V>
+where
+    K: Clone + Ord,
+{
+    let idx = find_child(&node.children, &rem[0]).ok()?;
+    let child = Arc::clone(&node.children[idx].1);
+    let mut guard = write(&child);
+
+    let rem = rem.strip_prefix(&guard.key[..])?;
+    if !rem.is_empty() {
+        drop(node);
+        return remove_at(guard, rem, false);
+    }
+
+    let value = guard.value.take()?;
+
+    match guard.children.len() {
+        0 => {
+            drop(guard);
+            node.children.remove(idx);
+            if !root {
+                absorb(&mut node);
+            }
+        }
+        1 => absorb(&mut guard),
+        _ => (),
+    }
+
+    Some(value)
+}
+
+/// Folds a value-less node with a single child into that child, in place.
+/// Taking the child's lock waits out any thread still inside it; no thread
+/// can reach it afterwards, as that would mean locking `node` first.
+fn absorb<K, V>(node: &mut Inner<K, V>) {
+    if node.value.is_some() || node.children.len() != 1 {
+        return;
+    }
+
+    let (_, child) = node.children.pop().unwrap();
+    let mut child = mem::take(&mut *write(&child));
+
+    node.key.append(&mut child.key);
+    node.value = child.value;
+    node.children = child.children;
+}
+
+/// Walks down to the node covering `rem` and copies its subtree into `out`,
+/// with `key` holding the path walked so far.
+fn prefix_at<K, V>(
+    node: RwLockReadGuard<'_, Inner<K, V>>,
+    rem: &[K],
+    mut key: Vec<K>,
+    out: &mut Vec<(Vec<K>, V)>,
+) where
+    K: Clone + Ord,
+    V: Clone,
+{
+    if rem.is_empty() {
+        collect(&node, &mut key, out);
+        return;
+    }
+
+    let idx = match find_child(&node.children, &rem[0]) {
+        Ok(idx) => idx,
+        Err(_) => return,
+    };
+    let child = Arc::clone(&node.children[idx].1);
+    let guard = read(&child);
+    drop(node);
+
+    let lcs = longest_match(&guard.key, rem);
+    key.extend_from_slice(&guard.key);
+
+    if lcs == rem.len() {
+        collect(&guard, &mut key, out);
+    } else if lcs == guard.key.len() {
+        prefix_at(guard, &rem[lcs..], key, out);
+    }
+}
+
+/// Copies every entry below `node`. The locks on the path from `node` stay
+/// held until the whole subtree has been read, so no writer can enter it
+/// meanwhile, and writers already inside it are never overtaken.
+fn collect<K, V>(node: &Inner<K, V>, key: &mut Vec<K>, out: &mut Vec<(Vec<K>, V)>)
+where
+    K: Clone,
+    V: Clone,
+{
+    if let Some(value) = &node.value {
+        out.push((key.clone(), value.clone()));
+    }
+
+    for (_, child) in &node.children {
+        let child = read(child);
+        let len = key.len();
+
+        key.extend_from_slice(&child.key);
+        collect(&child, key, out);
+        key.truncate(len);
+    }
+}
+
+impl<K, V, Q> FromIterator<(Q, V)> for ConcurrentRadix<K, V>
+where
+    K: Clone + Ord,
+    Q: AsRef<[K]>,
+{
+    fn from_iter<I: IntoIterator<Item = (Q, V)>>(iter: I) -> Self {
+        let radix = ConcurrentRadix::new();
+        for (key, value) in iter {
+            radix.insert(key.as_ref(), value);
+        }
+        radix
+    }
+}
+
+impl<K, V, Q> Extend<(Q, V)> for ConcurrentRadix<K, V>
+where
+    K: Clone + Ord,
+    Q: AsRef<[K]>,
+{
+    fn extend<I: IntoIterator<Item = (Q, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key.as_ref(), value);
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use super::RadixNode;
+use std::borrow::Borrow;
+
+impl<K, V> RadixNode<K, V>
+where
+    K: Clone + Ord,
+{
+    /// Returns every key within Levenshtein distance `max` of `query`, that is
+    /// reachable from it by at most `max` single-element insertions,
+    /// deletions or substitutions, along with its value and distance, in key
+    /// order.
+    ///
+    /// The trie is walked depth first with one row of the edit distance table
+    /// per key element, so keys sharing a prefix share its rows, and a subtree
+    /// is skipped as soon as every entry of the row exceeds `max`.
+    pub fn fuzzy<Q>(&self, query: &Q, max: usize) -> Vec<(Vec<K>, &V, usize)>
+    where
+        Q: Borrow<[K]> + ?Sized,
+    {
+        Fuzzy::new(query.borrow(), max, false).run(self)
+    }
+
+    /// Like [`RadixNode::fuzzy`], but also counts swapping two adjacent
+    /// elements as a single edit (the optimal string alignment distance).
+    pub fn fuzzy_transposed<Q>(&self, query: &Q, max: usize) -> Vec<(Vec<K>, &V, usize)>
+    where
+        Q: Borrow<[K]> + ?Sized,
+    {
+        Fuzzy::new(query.borrow(), max, true).run(self)
+    }
+}
+
+/// State of one fuzzy search: the key walked so far and one row of the
+/// distance table per element of it, plus the row for the empty key, stored
+/// back to back.
+struct Fuzzy<'q, K> {
+    query: &'q [K],
+    max: usize,
+    transpose: bool,
+    key: Vec<K>,
+    rows: Vec<usize>,
+}
+
+impl<'q, K> Fuzzy<'q, K>
+where
+    K: Clone + Ord,
+{
+    fn new(query: &'q [K], max: usize, transpose: bool) -> Self {
+        Self {
+            query,
+            max,
+            transpose,
+            key: Vec::new(),
+            rows: (0..=query.len()).collect(),
+        }
+    }
+
+    fn run<V>(mut self, root: &RadixNode<K, V>) -> Vec<(Vec<K>, &V, usize)> {
+        let mut out = Vec::new();
+        self.visit(root, &mut out);
+        out
+    }
+
+    /// Reports `node` if it is close enough, then descends into each child
+    /// whose edge keeps some entry of the row within `max`.
+    fn visit<'a, V>(&mut self, node: &'a RadixNode<K, V>, out: &mut Vec<(Vec<K>, &'a V, usize)>) {
+        let width = self.query.len() + 1;
+
+        if let Some(value) = &node.value {
+            let distance = self.rows[self.rows.len() - 1];
+            if distance <= self.max {
+                out.push((self.key.clone(), value, distance));
+            }
+        }
+
+        for child in &node.children {
+            let depth = self.key.len();
+
+            if child.key.iter().all(|elem| self.push(elem)) {
+                self.visit(child, out);
+            }
+
+            self.key.truncate(depth);
+            self.rows.truncate((depth + 1) * width);
+        }
+    }
+
+    /// Extends the key by `elem` and adds its row, returning `false` once no
+    /// extension of the key can come within `max`. Row entries never drop
+    /// below the smallest entry of the row above, and a transposition costs
+    /// no less than the substitution it stands in for, so pruning on the
+    /// minimum is safe for both distances.
+    fn push(&mut self, elem: &K) -> bool {
+        let query = self.query;
+        let width = query.len() + 1;
+        let prev = self.rows.len() - width;
+
+        // the previous key element, if a swap with it is worth checking
+        let swap = match self.transpose {
+            true => self.key.last(),
+            false => None,
+        };
+
+        let first = self.rows[prev] + 1;
+        let mut min = first;
+        self.rows.push(first);
+
+        for j in 1..=query.len() {
+            let cost = usize::from(query[j - 1] != *elem);
+            let left = self.rows[self.rows.len() - 1];
+            let mut best = (self.rows[prev + j] + 1)
+                .min(left + 1)
+                .min(self.rows[prev + j - 1] + cost);
+
+            if let Some(last) = swap {
+                if j > 1 && query[j - 2] == *elem && query[j - 1] == *last {
+                    best = best.min(self.rows[prev - width + j - 2] + 1);
+                }
+            }
+
+            min = min.min(best);
+            self.rows.push(best);
+        }
+
+        self.key.push(elem.clone());
+        min <= self.max
+    }
+}
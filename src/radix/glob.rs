@@ -0,0 +1,15 @@
+use super::RadixNode;
+use crate::glob::Glob;
+use crate::TrieError;
+
+impl<V> RadixNode<u8, V> {
+    /// Returns every entry whose key matches the glob `pattern`, in key
+    /// order; see [`Glob`] for the syntax.
+    ///
+    /// This is [`RadixNode::search`] with the compiled pattern, so a subtree
+    /// is skipped as soon as no key in it can match.
+    pub fn matching(&self, pattern: &str) -> Result<Vec<(Vec<u8>, &V)>, TrieError> {
+        let glob = Glob::new(pattern)?;
+        Ok(self.search(&glob).collect())
+    }
+}
@@ -0,0 +1,107 @@
+use super::RadixNode;
+use std::{slice, vec};
+
+/// Borrowing iterator over the entries of a [`RadixNode`], in key order.
+///
+/// Keys are rebuilt from the compressed edges, so each one is yielded as an
+/// owned `Vec`.
+pub struct Iter<'a, K, V> {
+    key: Vec<K>,
+    root: Option<&'a V>,
+    stack: Vec<(usize, slice::Iter<'a, RadixNode<K, V>>)>,
+}
+
+impl<'a, K, V> Iter<'a, K, V> {
+    pub(crate) fn new(root: &'a RadixNode<K, V>) -> Self {
+        Self {
+            key: Vec::new(),
+            root: root.value.as_ref(),
+            stack: vec![(0, root.children.iter())],
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V>
+where
+    K: Clone,
+{
+    type Item = (Vec<K>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(value) = self.root.take() {
+            return Some((Vec::new(), value));
+        }
+
+        loop {
+            let (len, nodes) = self.stack.last_mut()?;
+            let len = *len;
+
+            let node = match nodes.next() {
+                Some(node) => node,
+                None => {
+                    self.stack.pop();
+                    continue;
+                }
+            };
+
+            self.key.truncate(len);
+            self.key.extend_from_slice(&node.key);
+            self.stack.push((self.key.len(), node.children.iter()));
+
+            if let Some(value) = &node.value {
+                return Some((self.key.clone(), value));
+            }
+        }
+    }
+}
+
+/// Owning iterator over the entries of a [`RadixNode`], in key order.
+pub struct IntoIter<K, V> {
+    key: Vec<K>,
+    root: Option<V>,
+    stack: Vec<(usize, vec::IntoIter<RadixNode<K, V>>)>,
+}
+
+impl<K, V> IntoIter<K, V> {
+    pub(crate) fn new(root: RadixNode<K, V>) -> Self {
+        Self {
+            key: Vec::new(),
+            root: root.value,
+            stack: vec![(0, root.children.into_iter())],
+        }
+    }
+}
+
+impl<K, V> Iterator for IntoIter<K, V>
+where
+    K: Clone,
+{
+    type Item = (Vec<K>, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(value) = self.root.take() {
+            return Some((Vec::new(), value));
+        }
+
+        loop {
+            let (len, nodes) = self.stack.last_mut()?;
+            let len = *len;
+
+            let node = match nodes.next() {
+                Some(node) => node,
+                None => {
+                    self.stack.pop();
+                    continue;
+                }
+            };
+
+            self.key.truncate(len);
+            self.key.extend_from_slice(&node.key);
+            self.stack.push((self.key.len(), node.children.into_iter()));
+
+            if let Some(value) = node.value {
+                return Some((self.key.clone(), value));
+            }
+        }
+    }
+}
@@ -1,17 +1,56 @@
 #[cfg(test)]
 mod tests;
 
-use crate::TrieExt;
+mod arena;
+mod binary;
+mod concurrent;
+mod fuzzy;
+mod glob;
+mod iter;
+mod persistent;
+mod prune;
+mod scored;
+mod search;
+#[cfg(feature = "serde")]
+mod serde;
+mod set;
+mod suffix;
+mod view;
+
+pub use arena::{ArenaIter, ArenaRadix};
+pub use concurrent::ConcurrentRadix;
+pub use iter::{IntoIter, Iter};
+pub use persistent::{PersistentIter, PersistentRadix};
+pub use scored::{ScoredIter, ScoredRadix};
+pub use search::Search;
+pub use suffix::SuffixTree;
+pub use view::{RadixView, ViewIter};
+
+use crate::render::{tree_label, write_tree, Dot};
+use crate::stats::{Collector, TrieStats};
+use crate::{TrieError, TrieExt};
 use std::borrow::Borrow;
-use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::mem;
+use std::ops::Index;
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Clone)]
 pub struct RadixNode<K, V> {
     key: Vec<K>,
     value: Option<V>,
     children: Vec<RadixNode<K, V>>,
 }
 
+impl<K, V> Default for RadixNode<K, V>
+where
+    K: Clone + Ord,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<K, V> RadixNode<K, V>
 where
     K: Clone + Ord,
@@ -58,6 +97,55 @@ where
         &self.children
     }
 
+    /// Iterates over every stored key and value in key order, starting with
+    /// the empty key if it holds a value.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter::new(self)
+    }
+
+    /// Walks the whole tree to gather its [`TrieStats`]. Key bytes count the
+    /// edge elements below the root, at `size_of::<K>()` bytes each.
+    pub fn stats(&self) -> TrieStats {
+        let mut stats = Collector::default();
+        let mut stack = vec![(self, 0)];
+
+        while let Some((node, depth)) = stack.pop() {
+            let key_bytes = match depth {
+                0 => 0,
+                _ => node.key.len() * mem::size_of::<K>(),
+            };
+            let heap_bytes = node.key.capacity() * mem::size_of::<K>()
+                + node.children.capacity() * mem::size_of::<Self>();
+
+            stats.visit(
+                depth,
+                node.children.len(),
+                node.value.is_some(),
+                key_bytes,
+                heap_bytes,
+            );
+            stack.extend(node.children.iter().map(|n| (n, depth + 1)));
+        }
+
+        stats.finish(0)
+    }
+
+    /// Restores the radix invariants for a node whose children are already
+    /// compact: a node left without a value is dropped if it has no children
+    /// and folded into its child if it has exactly one.
+    fn compact(mut self) -> Option<Self> {
+        match (&self.value, self.children.len()) {
+            (None, 0) => None,
+            (None, 1) => {
+                let mut child = self.children.pop().unwrap();
+                self.key.append(&mut child.key);
+                child.key = self.key;
+                Some(child)
+            }
+            _ => Some(self),
+        }
+    }
+
     pub fn to_parts(self) -> (Vec<K>, Option<V>, Vec<RadixNode<K, V>>) {
         (self.key, self.value, self.children)
     }
@@ -71,134 +159,98 @@ where
     where
         Q: Borrow<[K]>,
     {
-        let k = key.borrow();
-        assert!(k.len() > 0);
-
-        let mut rem = k;
-        let mut nodes = &self.children;
-
-        let mut i = 0;
+        let mut rem = key.borrow();
+        let mut node = self;
 
         loop {
-            if nodes.len() == 0 {
-                return None;
+            if rem.is_empty() {
+                return node.value.as_ref();
             }
 
-            let node_key = &nodes[i].key;
-            let lcs = longest_match(node_key, &rem);
+            let idx = find_child(&node.children, &rem[0]).ok()?;
+            let child = &node.children[idx];
 
-            if lcs == 0 {
-                i += 1;
-                if i >= nodes.len() {
-                    break;
-                }
-                continue;
+            if !rem.starts_with(&child.key) {
+                return None;
             }
 
-            if node_key == rem {
-                return nodes[i].value.as_ref();
-            }
+            rem = &rem[child.key.len()..];
+            node = child;
+        }
+    }
 
-            if node_key.len() == lcs && rem.len() > lcs {
-                nodes = &nodes[i].children;
-                rem = &rem[lcs..];
-                i = 0;
-                continue;
+    fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        Q: Borrow<[K]>,
+    {
+        let mut rem = key.borrow();
+        let mut node = self;
+
+        loop {
+            if rem.is_empty() {
+                return node.value.as_mut();
             }
 
-            i += 1;
+            let idx = find_child(&node.children, &rem[0]).ok()?;
+            let child = &mut node.children[idx];
 
-            if i == nodes.len() {
-                break;
+            if !rem.starts_with(&child.key) {
+                return None;
             }
-        }
 
-        None
+            rem = &rem[child.key.len()..];
+            node = child;
+        }
     }
 
-    fn insert<Q>(&mut self, key: &Q, value: V) -> Result<Option<V>, ()>
+    fn insert<Q>(&mut self, key: &Q, value: V) -> Result<Option<V>, TrieError>
     where
         Q: Borrow<[K]> + ?Sized,
     {
-        let mut nodes = &mut self.children;
         let mut k = key.borrow();
+        let mut node = self;
 
-        let mut i = 0;
         loop {
-            if nodes.len() == 0 {
-                nodes.push(RadixNode::from(k, value));
-                return Ok(None);
+            if k.is_empty() {
+                return Ok(node.value.replace(value));
             }
 
-            let node_key = &nodes[i].key;
-            let lcs = longest_match(node_key, k);
-
-            if lcs == 0 {
-                i += 1;
-                if i >= nodes.len() {
-                    break;
+            let idx = match find_child(&node.children, &k[0]) {
+                Ok(idx) => idx,
+                Err(pos) => {
+                    node.children.insert(pos, RadixNode::from(k, value));
+                    return Ok(None);
                 }
+            };
+
+            let child = &mut node.children[idx];
+            let lcs = longest_match(&child.key, k);
+
+            if lcs == child.key.len() {
+                k = &k[lcs..];
+                node = child;
                 continue;
             }
 
-            let res = node_key.len().cmp(&lcs);
-
-            match res {
-                Ordering::Equal => match k[lcs..].len() {
-                    0 => {
-                        let val = std::mem::replace(&mut nodes[i].value, Some(value));
-                        return Ok(val);
-                    }
-                    _ => {
-                        nodes = &mut nodes[i].children;
-                        i = 0;
-                        k = &k[lcs..];
-                        continue;
-                    }
-                },
-                Ordering::Greater => {
-                    let mut new_root = RadixNode::from_key(&k[0..lcs]);
-                    let mut old = nodes.remove(i);
-
-                    match old.key[lcs..] == new_root.key {
-                        true => {
-                            new_root.value = old.value.take();
-                            std::mem::swap(&mut new_root.children, &mut old.children);
-                            drop(old);
-                        }
-                        false => {
-                            old.key = old.key[lcs..].to_vec();
-                            new_root.children.push(old);
-                        }
-                    }
-
-                    match &k[lcs..].len() {
-                        0 => {
-                            new_root.value = Some(value);
-                            nodes.push(new_root);
-                            nodes.sort_by(|a, b| a.key.cmp(&b.key));
-                            return Ok(None);
-                        }
-                        _ => {
-                            let mut child = RadixNode::from_key(&k[lcs..]);
-                            child.value = Some(value);
-                            new_root.children.push(child);
-
-                            nodes.push(new_root);
-                            nodes.sort_by(|a, b| a.key.cmp(&b.key));
-
-                            return Ok(None);
-                        }
-                    }
-                }
-                Ordering::Less => unreachable!(),
+            // split the edge: `child` keeps the shared part and the rest of
+            // its key moves down into a new node that inherits its subtree
+            let old = RadixNode {
+                key: child.key.split_off(lcs),
+                value: child.value.take(),
+                children: std::mem::take(&mut child.children),
+            };
+            child.children.push(old);
+
+            if lcs == k.len() {
+                child.value = Some(value);
+            } else {
+                let new = RadixNode::from(&k[lcs..], value);
+                let pos = usize::from(child.children[0].key[0] < new.key[0]);
+                child.children.insert(pos, new);
             }
-        }
 
-        nodes.push(RadixNode::from(k, value));
-        nodes.sort_by(|a, b| a.key.cmp(&b.key));
-
-        Ok(None)
+            return Ok(None);
+        }
     }
 
     fn remove<Q>(&mut self, key: &Q, prune: bool) -> Option<Self>
@@ -206,75 +258,209 @@ where
         Q: Borrow<[K]>,
     {
         let key = key.borrow();
-        assert!(key.len() > 0);
 
-        let mut rem = key;
-        let mut nodes = &mut self.children;
+        // the empty key lives on the root, which is never detached
+        if key.is_empty() {
+            if prune {
+                let root = RadixNode::from_key(&self.key);
+                return match self.is_empty() {
+                    true => None,
+                    false => Some(std::mem::replace(self, root)),
+                };
+            }
+            let value = self.value.take()?;
+            return Some(RadixNode::from(&self.key, value));
+        }
 
-        let mut idx = 0;
+        remove_node(&mut self.children, key, prune)
+    }
 
-        loop {
-            let node_key = &nodes[idx].key;
-            let lcs = longest_match(node_key, &rem);
+    fn len(&self) -> usize {
+        let count = usize::from(self.value.is_some());
+        self.children.iter().fold(count, |acc, n| acc + n.len())
+    }
 
-            if lcs == 0 {
-                idx += 1;
-                if idx == nodes.len() {
-                    break;
-                }
-                continue;
-            }
+    fn clear(&mut self) {
+        self.children.clear();
+        self.value = None;
+    }
+}
 
-            if node_key == rem {
-                if prune {
-                    let removed = nodes.remove(idx);
-                    return Some(removed);
-                }
+impl<K, V> PartialEq for RadixNode<K, V>
+where
+    K: Clone + Ord,
+    V: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
 
-                match nodes[idx].children.len() {
-                    0 => {
-                        let removed = nodes.remove(idx);
-                        return Some(removed);
-                    }
-                    1 => {
-                        // a node with one child is only possible if the node has a value
-                        let node = &mut nodes[idx];
-
-                        let mut child = node.children.pop().unwrap();
-                        let mut prefix = node.key.clone();
-                        let prefix = prefix.drain(0..node.key.len());
-                        child.key.splice(0..0, prefix);
-
-                        let removed = std::mem::replace(node, child);
-                        return Some(removed);
-                    }
-                    _ => {
-                        let node = &mut nodes[idx];
-                        let key = node.key.clone();
-                        let res = match node.value.take() {
-                            Some(v) => RadixNode::from(key, v),
-                            None => RadixNode::from_key(key),
-                        };
-                        return Some(res);
-                    }
-                }
-            }
+impl<K, V> Eq for RadixNode<K, V>
+where
+    K: Clone + Ord,
+    V: Eq,
+{
+}
 
-            if node_key.len() == lcs && rem.len() > lcs {
-                nodes = &mut nodes[idx].children;
-                rem = &rem[lcs..];
-                idx = 0;
-                continue;
-            }
+impl<K, V> Hash for RadixNode<K, V>
+where
+    K: Clone + Ord + Hash,
+    V: Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for entry in self.iter() {
+            entry.hash(state);
+        }
+    }
+}
+
+impl<K, V, Q> FromIterator<(Q, V)> for RadixNode<K, V>
+where
+    K: Clone + Ord,
+    Q: AsRef<[K]>,
+{
+    fn from_iter<I: IntoIterator<Item = (Q, V)>>(iter: I) -> Self {
+        let mut radix = RadixNode::new();
+        radix.extend(iter);
+        radix
+    }
+}
+
+impl<K, V, Q> Extend<(Q, V)> for RadixNode<K, V>
+where
+    K: Clone + Ord,
+    Q: AsRef<[K]>,
+{
+    fn extend<I: IntoIterator<Item = (Q, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            // inserting into a radix trie cannot fail
+            let _ = self.insert(key.as_ref(), value);
+        }
+    }
+}
+
+impl<K, V> IntoIterator for RadixNode<K, V>
+where
+    K: Clone + Ord,
+{
+    type Item = (Vec<K>, V);
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter::new(self)
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a RadixNode<K, V>
+where
+    K: Clone + Ord,
+{
+    type Item = (Vec<K>, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K, V> Index<&[K]> for RadixNode<K, V>
+where
+    K: Clone + Ord,
+{
+    type Output = V;
 
-            idx += 1;
+    fn index(&self, key: &[K]) -> &V {
+        self.get(&key).expect("key not found in trie")
+    }
+}
+
+/// Locates the child whose key starts with `first`. Siblings are sorted and
+/// never share a first element, so a binary search on it is exact; on a miss
+/// the error holds the position that keeps the children sorted.
+fn find_child<K, V>(nodes: &[RadixNode<K, V>], first: &K) -> Result<usize, usize>
+where
+    K: Ord,
+{
+    nodes.binary_search_by(|n| n.key[0].cmp(first))
+}
 
-            if idx == nodes.len() {
-                break;
+/// Detaches the node holding exactly `rem` from `nodes`, or only its value
+/// unless `prune`, compacting every node left behind on the way back up. A
+/// node without a value is not a key, so it is only removed when pruning.
+fn remove_node<K, V>(
+    nodes: &mut Vec<RadixNode<K, V>>,
+    rem: &[K],
+    prune: bool,
+) -> Option<RadixNode<K, V>>
+where
+    K: Clone + Ord,
+{
+    let idx = find_child(nodes, &rem[0]).ok()?;
+    let node = &mut nodes[idx];
+
+    let removed = if node.key[..] == *rem {
+        if prune {
+            return Some(nodes.remove(idx));
+        }
+        let value = node.value.take()?;
+        RadixNode::from(&node.key, value)
+    } else if rem.starts_with(&node.key) {
+        let len = node.key.len();
+        remove_node(&mut node.children, &rem[len..], prune)?
+    } else {
+        return None;
+    };
+
+    if let Some(node) = nodes.remove(idx).compact() {
+        nodes.insert(idx, node);
+    }
+
+    Some(removed)
+}
+
+impl<K, V> RadixNode<K, V>
+where
+    K: Clone + Ord + fmt::Debug,
+    V: fmt::Debug,
+{
+    /// Renders the tree as a Graphviz digraph. Edges are labelled with their
+    /// keys, and nodes holding a value show it inside a double outline.
+    pub fn to_dot(&self) -> String {
+        let mut dot = Dot::new("circle");
+        let mut stack = vec![(self, 0)];
+        let mut next = 1;
+
+        while let Some((node, id)) = stack.pop() {
+            let label = match &node.value {
+                Some(value) => format!("{:?}", value),
+                None => String::new(),
+            };
+            dot.node(id, &label, node.value.is_some());
+
+            for child in &node.children {
+                dot.edge(id, next, &format!("{:?}", child.key));
+                stack.push((child, next));
+                next += 1;
             }
         }
 
-        None
+        dot.finish()
+    }
+}
+
+/// Draws the tree like `tree(1)`, one edge key per line.
+impl<K, V> fmt::Display for RadixNode<K, V>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = |node: &Self| match std::ptr::eq(node, self) {
+            true => tree_label(".", node.value.as_ref()),
+            false => tree_label(&format!("{:?}", node.key), node.value.as_ref()),
+        };
+        write_tree(f, self, &label, &|node| node.children.iter().collect())
     }
 }
 
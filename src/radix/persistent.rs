@@ -0,0 +1,472 @@
+use super::{longest_match, RadixNode};
+use crate::{TrieError, TrieExt};
+use std::borrow::Borrow;
+use std::mem;
+use std::ops::Index;
+use std::slice;
+use std::sync::Arc;
+
+/// A radix trie whose nodes are shared between versions through `Arc`.
+///
+/// Cloning is O(1) and yields an independent version. Writing to a version
+/// copies only the nodes on the path to the key that are still shared with
+/// another version, so older versions stay valid and unchanged. Values are
+/// cloned along with the nodes that hold them.
+#[derive(Debug)]
+pub struct PersistentRadix<K, V> {
+    root: Arc<Node<K, V>>,
+    len: usize,
+}
+
+#[derive(Debug, Clone)]
+struct Node<K, V> {
+    key: Vec<K>,
+    value: Option<V>,
+    children: Vec<Arc<Node<K, V>>>,
+}
+
+impl<K, V> Node<K, V> {
+    fn new(key: Vec<K>, value: Option<V>) -> Self {
+        Self {
+            key,
+            value,
+            children: Vec::new(),
+        }
+    }
+
+    fn count(&self) -> usize {
+        let count = usize::from(self.value.is_some());
+        self.children.iter().fold(count, |acc, n| acc + n.count())
+    }
+}
+
+impl<K, V> Clone for PersistentRadix<K, V> {
+    /// Returns a new version sharing every node with `self`.
+    fn clone(&self) -> Self {
+        Self {
+            root: Arc::clone(&self.root),
+            len: self.len,
+        }
+    }
+}
+
+impl<K, V> Default for PersistentRadix<K, V>
+where
+    K: Clone + Ord,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> PersistentRadix<K, V>
+where
+    K: Clone + Ord,
+{
+    pub fn new() -> Self {
+        Self {
+            root: Arc::new(Node::new(Vec::new(), None)),
+            len: 0,
+        }
+    }
+
+    /// Iterates over every stored key and value in key order, starting with
+    /// the empty key if it holds a value.
+    pub fn iter(&self) -> PersistentIter<'_, K, V> {
+        PersistentIter {
+            key: Vec::new(),
+            root: self.root.value.as_ref(),
+            stack: vec![(0, self.root.children.iter())],
+        }
+    }
+
+    /// Returns `true` if both versions share the same root, in which case
+    /// they are guaranteed to hold the same entries.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.root, &other.root)
+    }
+
+    /// Walks down to the node reached by `key`, or to the node whose edge
+    /// `key` ends in when `partial` is set.
+    fn locate(&self, key: &[K], partial: bool) -> Option<&Node<K, V>> {
+        let mut rem = key;
+        let mut node = &*self.root;
+
+        while !rem.is_empty() {
+            let idx = find_child(&node.children, &rem[0]).ok()?;
+            let child = &*node.children[idx];
+            let lcs = longest_match(&child.key, rem);
+
+            match lcs == child.key.len() || (partial && lcs == rem.len()) {
+                true => rem = &rem[lcs..],
+                false => return None,
+            }
+            node = child;
+        }
+
+        Some(node)
+    }
+}
+
+impl<K, V> PersistentRadix<K, V>
+where
+    K: Clone + Ord,
+    V: Clone,
+{
+    /// Returns a new version with `key` mapped to `value`, leaving `self`
+    /// untouched.
+    pub fn update<Q>(&self, key: &Q, value: V) -> Self
+    where
+        Q: Borrow<[K]> + ?Sized,
+    {
+        let mut version = self.clone();
+        // inserting into a radix trie cannot fail
+        let _ = version.insert(key, value);
+        version
+    }
+
+    /// Returns a new version without `key`, leaving `self` untouched. If
+    /// `key` is absent the new version shares its root with `self`.
+    pub fn without<Q>(&self, key: &Q) -> Self
+    where
+        Q: Borrow<[K]>,
+    {
+        let mut version = self.clone();
+        version.remove(key, false);
+        version
+    }
+}
+
+impl<K, V> TrieExt<K, V> for PersistentRadix<K, V>
+where
+    K: Clone + Ord,
+    V: Clone,
+{
+    fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        Q: Borrow<[K]>,
+    {
+        self.locate(key.borrow(), false)?.value.as_ref()
+    }
+
+    /// Copies the shared nodes on the path to `key` only if it holds a value.
+    fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        Q: Borrow<[K]>,
+    {
+        let mut rem = key.borrow();
+        self.get(&rem)?;
+
+        let mut node = Arc::make_mut(&mut self.root);
+
+        while !rem.is_empty() {
+            let idx = find_child(&node.children, &rem[0]).ok()?;
+            node = Arc::make_mut(&mut node.children[idx]);
+            rem = &rem[node.key.len()..];
+        }
+
+        node.value.as_mut()
+    }
+
+    fn insert<Q>(&mut self, key: &Q, value: V) -> Result<Option<V>, TrieError>
+    where
+        Q: Borrow<[K]> + ?Sized,
+    {
+        let mut k = key.borrow();
+        let mut node = Arc::make_mut(&mut self.root);
+
+        loop {
+            if k.is_empty() {
+                let old = node.value.replace(value);
+                if old.is_none() {
+                    self.len += 1;
+                }
+                return Ok(old);
+            }
+
+            let idx = match find_child(&node.children, &k[0]) {
+                Ok(idx) => idx,
+                Err(pos) => {
+                    let leaf = Node::new(k.to_vec(), Some(value));
+                    node.children.insert(pos, Arc::new(leaf));
+                    self.len += 1;
+                    return Ok(None);
+                }
+            };
+
+            let child = Arc::make_mut(&mut node.children[idx]);
+            let lcs = longest_match(&child.key, k);
+
+            if lcs == child.key.len() {
+                k = &k[lcs..];
+                node = child;
+                continue;
+            }
+
+            // split the edge: `child` keeps the shared part and the rest of
+            // its key moves down into a new node that inherits its subtree,
+            // whose nodes stay shared
+            let old = Node {
+                key: child.key.split_off(lcs),
+                value: child.value.take(),
+                children: mem::take(&mut child.children),
+            };
+            child.children.push(Arc::new(old));
+
+            if lcs == k.len() {
+                child.value = Some(value);
+            } else {
+                let new = Node::new(k[lcs..].to_vec(), Some(value));
+                let pos = usize::from(child.children[0].key[0] < new.key[0]);
+                child.children.insert(pos, Arc::new(new));
+            }
+
+            self.len += 1;
+            return Ok(None);
+        }
+    }
+
+    /// Removes `key` and returns it in a trie of its own. With `prune`, every
+    /// key starting with `key` is removed and returned instead. Nothing is
+    /// copied if there is nothing to remove.
+    fn remove<Q>(&mut self, key: &Q, prune: bool) -> Option<Self>
+    where
+        Q: Borrow<[K]>,
+    {
+        let key = key.borrow();
+        let node = self.locate(key, prune)?;
+
+        if !prune && node.value.is_none() {
+            return None;
+        }
+
+        if key.is_empty() {
+            if prune {
+                return match self.is_empty() {
+                    true => None,
+                    false => Some(mem::take(self)),
+                };
+            }
+
+            let value = Arc::make_mut(&mut self.root).value.take();
+            self.len -= 1;
+
+            let mut removed = Self::new();
+            removed.root = Arc::new(Node::new(Vec::new(), value));
+            removed.len = 1;
+            return Some(removed);
+        }
+
+        let removed = remove_at(Arc::make_mut(&mut self.root), key, prune)?;
+        let len = removed.count();
+        self.len -= len;
+
+        let mut root = Node::new(Vec::new(), None);
+        root.children.push(Arc::new(removed));
+        Some(Self {
+            root: Arc::new(root),
+            len,
+        })
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn clear(&mut self) {
+        *self = Self::new();
+    }
+}
+
+/// Detaches what `remove` takes below `node`: the subtree holding every key
+/// starting with `rem` when pruning, otherwise a leaf with the value at
+/// `rem`. The result is keyed relative to `node`. The caller has checked
+/// that there is something to remove, so only the path to it is copied.
+fn remove_at<K, V>(node: &mut Node<K, V>, rem: &[K], prune: bool) -> Option<Node<K, V>>
+where
+    K: Clone + Ord,
+    V: Clone,
+{
+    let idx = find_child(&node.children, &rem[0]).ok()?;
+    let lcs = longest_match(&node.children[idx].key, rem);
+
+    if prune && lcs == rem.len() {
+        let removed = node.children.remove(idx);
+        return Some(Arc::unwrap_or_clone(removed));
+    }
+
+    let child = Arc::make_mut(&mut node.children[idx]);
+    let mut removed = match lcs == rem.len() {
+        true => Node::new(Vec::new(), Some(child.value.take()?)),
+        false => remove_at(child, &rem[lcs..], prune)?,
+    };
+    removed.key.splice(0..0, child.key.iter().cloned());
+
+    // `child` may now be a value-less node with fewer than two children
+    if child.value.is_none() {
+        match child.children.len() {
+            0 => {
+                node.children.remove(idx);
+            }
+            1 => {
+                let mut grandchild = Arc::unwrap_or_clone(child.children.pop()?);
+                child.key.append(&mut grandchild.key);
+                grandchild.key = mem::take(&mut child.key);
+                *child = grandchild;
+            }
+            _ => (),
+        }
+    }
+
+    Some(removed)
+}
+
+/// Locates the child whose key starts with `first`; see the `RadixNode`
+/// version for the search itself.
+fn find_child<K, V>(nodes: &[Arc<Node<K, V>>], first: &K) -> Result<usize, usize>
+where
+    K: Ord,
+{
+    nodes.binary_search_by(|n| n.key[0].cmp(first))
+}
+
+impl<K, V> From<RadixNode<K, V>> for PersistentRadix<K, V>
+where
+    K: Clone + Ord,
+{
+    fn from(radix: RadixNode<K, V>) -> Self {
+        fn convert<K, V>(radix: RadixNode<K, V>) -> Node<K, V>
+        where
+            K: Clone + Ord,
+        {
+            let (key, value, children) = radix.to_parts();
+            let children = children.into_iter().map(|n| Arc::new(convert(n)));
+            Node {
+                key,
+                value,
+                children: children.collect(),
+            }
+        }
+
+        let len = radix.len();
+        let mut root = convert(radix);
+        root.key.clear();
+
+        Self {
+            root: Arc::new(root),
+            len,
+        }
+    }
+}
+
+impl<K, V> PartialEq for PersistentRadix<K, V>
+where
+    K: Clone + Ord,
+    V: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.ptr_eq(other) || (self.len == other.len && self.iter().eq(other.iter()))
+    }
+}
+
+impl<K, V> Eq for PersistentRadix<K, V>
+where
+    K: Clone + Ord,
+    V: Eq,
+{
+}
+
+impl<K, V, Q> FromIterator<(Q, V)> for PersistentRadix<K, V>
+where
+    K: Clone + Ord,
+    V: Clone,
+    Q: AsRef<[K]>,
+{
+    fn from_iter<I: IntoIterator<Item = (Q, V)>>(iter: I) -> Self {
+        let mut radix = PersistentRadix::new();
+        radix.extend(iter);
+        radix
+    }
+}
+
+impl<K, V, Q> Extend<(Q, V)> for PersistentRadix<K, V>
+where
+    K: Clone + Ord,
+    V: Clone,
+    Q: AsRef<[K]>,
+{
+    fn extend<I: IntoIterator<Item = (Q, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            // inserting into a radix trie cannot fail
+            let _ = self.insert(key.as_ref(), value);
+        }
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a PersistentRadix<K, V>
+where
+    K: Clone + Ord,
+{
+    type Item = (Vec<K>, &'a V);
+    type IntoIter = PersistentIter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K, V> Index<&[K]> for PersistentRadix<K, V>
+where
+    K: Clone + Ord,
+    V: Clone,
+{
+    type Output = V;
+
+    fn index(&self, key: &[K]) -> &V {
+        self.get(&key).expect("key not found in trie")
+    }
+}
+
+/// Borrowing iterator over the entries of a [`PersistentRadix`], in key
+/// order.
+pub struct PersistentIter<'a, K, V> {
+    key: Vec<K>,
+    root: Option<&'a V>,
+    stack: Vec<(usize, Siblings<'a, K, V>)>,
+}
+
+type Siblings<'a, K, V> = slice::Iter<'a, Arc<Node<K, V>>>;
+
+impl<'a, K, V> Iterator for PersistentIter<'a, K, V>
+where
+    K: Clone,
+{
+    type Item = (Vec<K>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(value) = self.root.take() {
+            return Some((Vec::new(), value));
+        }
+
+        loop {
+            let (len, nodes) = self.stack.last_mut()?;
+            let len = *len;
+
+            let node = match nodes.next() {
+                Some(node) => node,
+                None => {
+                    self.stack.pop();
+                    continue;
+                }
+            };
+
+            self.key.truncate(len);
+            self.key.extend_from_slice(&node.key);
+            self.stack.push((self.key.len(), node.children.iter()));
+
+            if let Some(value) = &node.value {
+                return Some((self.key.clone(), value));
+            }
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use super::{find_child, longest_match, RadixNode};
+use std::borrow::Borrow;
+
+impl<K, V> RadixNode<K, V>
+where
+    K: Clone + Ord,
+{
+    /// Keeps only the entries for which `f` returns `true`, compressing the
+    /// nodes left behind.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&[K], &mut V) -> bool,
+    {
+        let mut key = Vec::new();
+
+        if let Some(value) = &mut self.value {
+            if !f(&key, value) {
+                self.value = None;
+            }
+        }
+
+        let children = std::mem::take(&mut self.children);
+        self.children = retain_children(children, &mut key, &mut f);
+    }
+
+    /// Removes every entry whose key starts with `prefix`, returning them in
+    /// key order.
+    pub fn drain_prefix<Q>(&mut self, prefix: &Q) -> Vec<(Vec<K>, V)>
+    where
+        Q: Borrow<[K]> + ?Sized,
+    {
+        self.split_off_prefix(prefix).into_iter().collect()
+    }
+
+    /// Moves every entry whose key starts with `prefix` into a new trie.
+    pub fn split_off_prefix<Q>(&mut self, prefix: &Q) -> Self
+    where
+        Q: Borrow<[K]> + ?Sized,
+    {
+        let prefix = prefix.borrow();
+
+        if prefix.is_empty() {
+            let root = RadixNode::from_key(&self.key);
+            return std::mem::replace(self, root);
+        }
+
+        let mut split = RadixNode::new();
+        split
+            .children
+            .extend(take_prefix(&mut self.children, prefix));
+        split
+    }
+
+    /// Moves every entry whose key is greater than or equal to `key` into a
+    /// new trie, leaving the smaller keys in `self`.
+    pub fn split_off<Q>(&mut self, key: &Q) -> Self
+    where
+        Q: Borrow<[K]> + ?Sized,
+    {
+        let key = key.borrow();
+
+        if key.is_empty() {
+            let root = RadixNode::from_key(&self.key);
+            return std::mem::replace(self, root);
+        }
+
+        let mut split = RadixNode::new();
+        split.children = split_children(&mut self.children, key);
+        split
+    }
+}
+
+fn retain_children<K, V, F>(
+    nodes: Vec<RadixNode<K, V>>,
+    key: &mut Vec<K>,
+    f: &mut F,
+) -> Vec<RadixNode<K, V>>
+where
+    K: Clone + Ord,
+    F: FnMut(&[K], &mut V) -> bool,
+{
+    nodes
+        .into_iter()
+        .filter_map(|mut node| {
+            let len = key.len();
+            key.extend_from_slice(&node.key);
+
+            if let Some(value) = &mut node.value {
+                if !f(key, value) {
+                    node.value = None;
+                }
+            }
+
+            let children = std::mem::take(&mut node.children);
+            node.children = retain_children(children, key, f);
+            key.truncate(len);
+
+            node.compact()
+        })
+        .collect()
+}
+
+/// Detaches the subtree holding every key that starts with `rem`, returning
+/// it with its key extended back to the level of `nodes`.
+fn take_prefix<K, V>(nodes: &mut Vec<RadixNode<K, V>>, rem: &[K]) -> Option<RadixNode<K, V>>
+where
+    K: Clone + Ord,
+{
+    let idx = find_child(nodes, &rem[0]).ok()?;
+    let lcs = longest_match(&nodes[idx].key, rem);
+
+    if lcs == rem.len() {
+        return Some(nodes.remove(idx));
+    }
+
+    if lcs < nodes[idx].key.len() {
+        return None;
+    }
+
+    let node = &mut nodes[idx];
+    let mut taken = take_prefix(&mut node.children, &rem[lcs..])?;
+    taken.key.splice(0..0, node.key.iter().cloned());
+
+    if let Some(node) = nodes.remove(idx).compact() {
+        nodes.insert(idx, node);
+    }
+
+    Some(taken)
+}
+
+/// Moves the nodes holding keys greater than or equal to `rem` out of
+/// `nodes`, splitting the one node whose subtree straddles `rem`.
+fn split_children<K, V>(nodes: &mut Vec<RadixNode<K, V>>, rem: &[K]) -> Vec<RadixNode<K, V>>
+where
+    K: Clone + Ord,
+{
+    let pos = nodes.partition_point(|n| n.key[0] < rem[0]);
+    let mut right = nodes.split_off(pos);
+
+    if right.is_empty() || right[0].key[0] != rem[0] {
+        return right;
+    }
+
+    let mut node = right.remove(0);
+    let lcs = longest_match(&node.key, rem);
+
+    if lcs == rem.len() {
+        // every key below `node` starts with `rem`
+        right.insert(0, node);
+    } else if lcs == node.key.len() {
+        let moved = split_children(&mut node.children, &rem[lcs..]);
+
+        let mut split = RadixNode::from_key(&node.key);
+        split.children = moved;
+        if let Some(split) = split.compact() {
+            right.insert(0, split);
+        }
+        if let Some(node) = node.compact() {
+            nodes.push(node);
+        }
+    } else if node.key[lcs] > rem[lcs] {
+        right.insert(0, node);
+    } else {
+        nodes.push(node);
+    }
+
+    right
+}
@@ -0,0 +1,481 @@
+use super::longest_match;
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::mem;
+
+/// A radix trie whose entries carry a score, for ranking completions.
+///
+/// Every node caches the highest score in its subtree, so
+/// [`ScoredRadix::top_k`] can search best first: it expands the most
+/// promising subtree next and stops after `k` entries, leaving the rest of
+/// the trie unvisited. Writes refresh the cache on the path to the key, at a
+/// cost of one pass over the children of each node on that path.
+#[derive(Debug, Clone)]
+pub struct ScoredRadix<K, V, S> {
+    root: Node<K, V, S>,
+    len: usize,
+}
+
+#[derive(Debug, Clone)]
+struct Node<K, V, S> {
+    key: Vec<K>,
+    entry: Option<(V, S)>,
+    /// Highest score in the subtree, `None` only for an empty root.
+    best: Option<S>,
+    children: Vec<Node<K, V, S>>,
+}
+
+impl<K, V, S> Node<K, V, S>
+where
+    S: Ord + Clone,
+{
+    fn new(key: Vec<K>, entry: Option<(V, S)>) -> Self {
+        let best = entry.as_ref().map(|(_, score)| score.clone());
+        Self {
+            key,
+            entry,
+            best,
+            children: Vec::new(),
+        }
+    }
+
+    /// Recomputes the cached best score from the entry and the children.
+    fn refresh(&mut self) {
+        let children = self.children.iter().filter_map(|n| n.best.as_ref());
+        let own = self.entry.as_ref().map(|(_, score)| score);
+        self.best = own.into_iter().chain(children).max().cloned();
+    }
+}
+
+impl<K, V, S> Default for ScoredRadix<K, V, S>
+where
+    K: Clone + Ord,
+    S: Ord + Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V, S> ScoredRadix<K, V, S>
+where
+    K: Clone + Ord,
+    S: Ord + Clone,
+{
+    pub fn new() -> Self {
+        Self {
+            root: Node::new(Vec::new(), None),
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<(&V, &S)>
+    where
+        Q: Borrow<[K]> + ?Sized,
+    {
+        let mut rem = key.borrow();
+        let mut node = &self.root;
+
+        while !rem.is_empty() {
+            let idx = find_child(&node.children, &rem[0]).ok()?;
+            node = &node.children[idx];
+            rem = rem.strip_prefix(&node.key[..])?;
+        }
+
+        node.entry.as_ref().map(|(value, score)| (value, score))
+    }
+
+    /// Returns a mutable reference to the value at `key`. Scores can only be
+    /// changed through [`ScoredRadix::set_score`], which keeps the cached
+    /// maxima up to date.
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        Q: Borrow<[K]> + ?Sized,
+    {
+        let mut rem = key.borrow();
+        let mut node = &mut self.root;
+
+        while !rem.is_empty() {
+            let idx = find_child(&node.children, &rem[0]).ok()?;
+            node = &mut node.children[idx];
+            rem = rem.strip_prefix(&node.key[..])?;
+        }
+
+        node.entry.as_mut().map(|(value, _)| value)
+    }
+
+    /// Stores `value` under `key` with `score`, returning the previous value
+    /// and score if the key was present.
+    pub fn insert<Q>(&mut self, key: &Q, value: V, score: S) -> Option<(V, S)>
+    where
+        Q: Borrow<[K]> + ?Sized,
+    {
+        let old = insert_at(&mut self.root, key.borrow(), (value, score));
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<(V, S)>
+    where
+        Q: Borrow<[K]> + ?Sized,
+    {
+        let key = key.borrow();
+
+        let old = match key.is_empty() {
+            true => self.root.entry.take(),
+            false => remove_at(&mut self.root, key),
+        }?;
+
+        self.root.refresh();
+        self.len -= 1;
+        Some(old)
+    }
+
+    /// Replaces the score of `key`, returning the old one, or `None` if the
+    /// key is absent.
+    pub fn set_score<Q>(&mut self, key: &Q, score: S) -> Option<S>
+    where
+        Q: Borrow<[K]> + ?Sized,
+    {
+        set_score_at(&mut self.root, key.borrow(), score)
+    }
+
+    /// Returns the `k` highest-scored entries whose key starts with `prefix`,
+    /// best first, with ties in key order.
+    pub fn top_k<Q>(&self, prefix: &Q, k: usize) -> Vec<(Vec<K>, &V, &S)>
+    where
+        Q: Borrow<[K]> + ?Sized,
+    {
+        let mut out = Vec::new();
+        let mut heap = BinaryHeap::new();
+
+        heap.extend(self.locate(prefix.borrow()));
+
+        while out.len() < k {
+            let candidate = match heap.pop() {
+                Some(candidate) => candidate,
+                None => break,
+            };
+
+            let node = match candidate.item {
+                Item::Entry(value) => {
+                    out.push((candidate.key, value, candidate.score));
+                    continue;
+                }
+                Item::Node(node) => node,
+            };
+
+            if let Some((value, score)) = &node.entry {
+                heap.push(Candidate {
+                    score,
+                    key: candidate.key.clone(),
+                    item: Item::Entry(value),
+                });
+            }
+
+            for child in &node.children {
+                if let Some(best) = &child.best {
+                    let mut key = candidate.key.clone();
+                    key.extend_from_slice(&child.key);
+                    heap.push(Candidate::node(best, key, child));
+                }
+            }
+        }
+
+        out
+    }
+
+    /// Iterates over every entry in key order, starting with the empty key if
+    /// it holds one.
+    pub fn iter(&self) -> ScoredIter<'_, K, V, S> {
+        ScoredIter {
+            key: Vec::new(),
+            root: self.root.entry.as_ref(),
+            stack: vec![(0, self.root.children.iter())],
+        }
+    }
+
+    /// Finds the node below which every key starts with `prefix`, as the
+    /// first candidate of a search.
+    fn locate(&self, prefix: &[K]) -> Option<Candidate<'_, K, V, S>> {
+        let mut rem = prefix;
+        let mut node = &self.root;
+        let mut key = Vec::new();
+
+        while !rem.is_empty() {
+            let idx = find_child(&node.children, &rem[0]).ok()?;
+            node = &node.children[idx];
+
+            let lcs = longest_match(&node.key, rem);
+            if lcs < rem.len() && lcs < node.key.len() {
+                return None;
+            }
+
+            key.extend_from_slice(&node.key);
+            rem = &rem[lcs..];
+        }
+
+        let best = node.best.as_ref()?;
+        Some(Candidate::node(best, key, node))
+    }
+}
+
+fn find_child<K, V, S>(nodes: &[Node<K, V, S>], first: &K) -> Result<usize, usize>
+where
+    K: Ord,
+{
+    nodes.binary_search_by(|n| n.key[0].cmp(first))
+}
+
+fn insert_at<K, V, S>(node: &mut Node<K, V, S>, k: &[K], entry: (V, S)) -> Option<(V, S)>
+where
+    K: Clone + Ord,
+    S: Ord + Clone,
+{
+    if k.is_empty() {
+        let old = node.entry.replace(entry);
+        node.refresh();
+        return old;
+    }
+
+    let old = match find_child(&node.children, &k[0]) {
+        Err(pos) => {
+            node.children
+                .insert(pos, Node::new(k.to_vec(), Some(entry)));
+            None
+        }
+        Ok(idx) => {
+            let child = &mut node.children[idx];
+            let lcs = longest_match(&child.key, k);
+
+            if lcs == child.key.len() {
+                insert_at(child, &k[lcs..], entry)
+            } else {
+                // split the edge: the rest of the child's key moves down
+                // into a new node that inherits its entry and subtree
+                let tail = Node {
+                    key: child.key.split_off(lcs),
+                    entry: child.entry.take(),
+                    best: child.best.take(),
+                    children: mem::take(&mut child.children),
+                };
+                child.children.push(tail);
+
+                if lcs == k.len() {
+                    child.entry = Some(entry);
+                } else {
+                    let new = Node::new(k[lcs..].to_vec(), Some(entry));
+                    let pos = usize::from(child.children[0].key[0] < new.key[0]);
+                    child.children.insert(pos, new);
+                }
+
+                child.refresh();
+                None
+            }
+        }
+    };
+
+    node.refresh();
+    old
+}
+
+/// Removes the entry at `rem` below `node`, which must be non-empty, and
+/// restores the radix invariants and cached scores of the child it passed
+/// through. `node` itself is refreshed by the caller.
+fn remove_at<K, V, S>(node: &mut Node<K, V, S>, rem: &[K]) -> Option<(V, S)>
+where
+    K: Clone + Ord,
+    S: Ord + Clone,
+{
+    let idx = find_child(&node.children, &rem[0]).ok()?;
+    let child = &mut node.children[idx];
+    let rest = rem.strip_prefix(&child.key[..])?;
+
+    let old = match rest.is_empty() {
+        true => child.entry.take()?,
+        false => remove_at(child, rest)?,
+    };
+
+    match (&child.entry, child.children.len()) {
+        (None, 0) => {
+            node.children.remove(idx);
+        }
+        (None, 1) => {
+            let mut grandchild = child.children.pop().unwrap();
+            child.key.append(&mut grandchild.key);
+            grandchild.key = mem::take(&mut child.key);
+            *child = grandchild;
+        }
+        _ => child.refresh(),
+    }
+
+    Some(old)
+}
+
+fn set_score_at<K, V, S>(node: &mut Node<K, V, S>, rem: &[K], score: S) -> Option<S>
+where
+    K: Clone + Ord,
+    S: Ord + Clone,
+{
+    let old = match rem.first() {
+        None => {
+            let (_, old) = node.entry.as_mut()?;
+            mem::replace(old, score)
+        }
+        Some(first) => {
+            let idx = find_child(&node.children, first).ok()?;
+            let child = &mut node.children[idx];
+            let rest = rem.strip_prefix(&child.key[..])?;
+            set_score_at(child, rest, score)?
+        }
+    };
+
+    node.refresh();
+    Some(old)
+}
+
+/// A subtree or entry waiting in the best-first search, ordered by score and
+/// then by key, smallest first.
+struct Candidate<'a, K, V, S> {
+    score: &'a S,
+    key: Vec<K>,
+    item: Item<'a, K, V, S>,
+}
+
+enum Item<'a, K, V, S> {
+    Node(&'a Node<K, V, S>),
+    Entry(&'a V),
+}
+
+impl<'a, K, V, S> Candidate<'a, K, V, S> {
+    fn node(score: &'a S, key: Vec<K>, node: &'a Node<K, V, S>) -> Self {
+        Self {
+            score,
+            key,
+            item: Item::Node(node),
+        }
+    }
+}
+
+impl<K, V, S> Ord for Candidate<'_, K, V, S>
+where
+    K: Ord,
+    S: Ord,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        // a node and the entry it holds share a key but are never queued
+        // together, so an entry only needs to win over a node to break ties
+        let rank = |c: &Self| matches!(c.item, Item::Entry(_));
+        self.score
+            .cmp(other.score)
+            .then_with(|| other.key.cmp(&self.key))
+            .then_with(|| rank(self).cmp(&rank(other)))
+    }
+}
+
+impl<K, V, S> PartialOrd for Candidate<'_, K, V, S>
+where
+    K: Ord,
+    S: Ord,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K, V, S> PartialEq for Candidate<'_, K, V, S>
+where
+    K: Ord,
+    S: Ord,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<K, V, S> Eq for Candidate<'_, K, V, S>
+where
+    K: Ord,
+    S: Ord,
+{
+}
+
+impl<K, V, S, Q> FromIterator<(Q, V, S)> for ScoredRadix<K, V, S>
+where
+    K: Clone + Ord,
+    S: Ord + Clone,
+    Q: AsRef<[K]>,
+{
+    fn from_iter<I: IntoIterator<Item = (Q, V, S)>>(iter: I) -> Self {
+        let mut radix = ScoredRadix::new();
+        radix.extend(iter);
+        radix
+    }
+}
+
+impl<K, V, S, Q> Extend<(Q, V, S)> for ScoredRadix<K, V, S>
+where
+    K: Clone + Ord,
+    S: Ord + Clone,
+    Q: AsRef<[K]>,
+{
+    fn extend<I: IntoIterator<Item = (Q, V, S)>>(&mut self, iter: I) {
+        for (key, value, score) in iter {
+            self.insert(key.as_ref(), value, score);
+        }
+    }
+}
+
+/// Borrowing iterator over the entries of a [`ScoredRadix`], in key order.
+pub struct ScoredIter<'a, K, V, S> {
+    key: Vec<K>,
+    root: Option<&'a (V, S)>,
+    stack: Vec<(usize, Siblings<'a, K, V, S>)>,
+}
+
+type Siblings<'a, K, V, S> = std::slice::Iter<'a, Node<K, V, S>>;
+
+impl<'a, K, V, S> Iterator for ScoredIter<'a, K, V, S>
+where
+    K: Clone,
+{
+    type Item = (Vec<K>, &'a V, &'a S);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some((value, score)) = self.root.take() {
+            return Some((Vec::new(), value, score));
+        }
+
+        loop {
+            let (len, nodes) = self.stack.last_mut()?;
+            let len = *len;
+
+            let node = match nodes.next() {
+                Some(node) => node,
+                None => {
+                    self.stack.pop();
+                    continue;
+                }
+            };
+
+            self.key.truncate(len);
+            self.key.extend_from_slice(&node.key);
+            self.stack.push((self.key.len(), node.children.iter()));
+
+            if let Some((value, score)) = &node.entry {
+                return Some((self.key.clone(), value, score));
+            }
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use super::RadixNode;
+use crate::automaton::Automaton;
+use std::slice;
+
+impl<K, V> RadixNode<K, V>
+where
+    K: Clone,
+{
+    /// Streams every entry whose key `aut` matches, in key order.
+    ///
+    /// The automaton is run along each edge once, so keys sharing a prefix
+    /// share the work of matching it, and a subtree is skipped as soon as the
+    /// automaton reports that nothing in it can match.
+    pub fn search<A>(&self, aut: A) -> Search<'_, K, V, A>
+    where
+        A: Automaton<K>,
+    {
+        Search::new(self, aut)
+    }
+}
+
+/// Iterator over the entries of a [`RadixNode`] matched by an
+/// [`Automaton`], returned by [`RadixNode::search`].
+pub struct Search<'a, K, V, A>
+where
+    A: Automaton<K>,
+{
+    aut: A,
+    key: Vec<K>,
+    root: Option<&'a V>,
+    stack: Vec<Frame<'a, K, V, A::State>>,
+}
+
+/// Length of the key down to a node, the state after reading it, and the
+/// node's children still to visit.
+type Frame<'a, K, V, S> = (usize, S, slice::Iter<'a, RadixNode<K, V>>);
+
+impl<'a, K, V, A> Search<'a, K, V, A>
+where
+    A: Automaton<K>,
+{
+    fn new(root: &'a RadixNode<K, V>, aut: A) -> Self {
+        let state = aut.start();
+        let root_value = root.value.as_ref().filter(|_| aut.is_match(&state));
+        let stack = match aut.can_match(&state) {
+            true => vec![(0, state, root.children.iter())],
+            false => Vec::new(),
+        };
+
+        Self {
+            aut,
+            key: Vec::new(),
+            root: root_value,
+            stack,
+        }
+    }
+}
+
+/// Reads `edge` from `state`, giving up as soon as nothing can match. Once
+/// everything matches the state is kept as is.
+fn walk<K, A>(aut: &A, state: &A::State, edge: &[K]) -> Option<A::State>
+where
+    A: Automaton<K>,
+{
+    let mut state = state.clone();
+    for elem in edge {
+        if aut.will_always_match(&state) {
+            break;
+        }
+        state = aut.accept(&state, elem);
+        if !aut.can_match(&state) {
+            return None;
+        }
+    }
+    Some(state)
+}
+
+impl<'a, K, V, A> Iterator for Search<'a, K, V, A>
+where
+    K: Clone,
+    A: Automaton<K>,
+{
+    type Item = (Vec<K>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(value) = self.root.take() {
+            return Some((Vec::new(), value));
+        }
+
+        loop {
+            let (len, state, nodes) = self.stack.last_mut()?;
+            let len = *len;
+
+            let node = match nodes.next() {
+                Some(node) => node,
+                None => {
+                    self.stack.pop();
+                    continue;
+                }
+            };
+
+            let state = match walk(&self.aut, state, &node.key) {
+                Some(state) => state,
+                None => continue,
+            };
+            let value = node
+                .value
+                .as_ref()
+                .filter(|_| self.aut.will_always_match(&state) || self.aut.is_match(&state));
+
+            self.key.truncate(len);
+            self.key.extend_from_slice(&node.key);
+            self.stack
+                .push((self.key.len(), state, node.children.iter()));
+
+            if let Some(value) = value {
+                return Some((self.key.clone(), value));
+            }
+        }
+    }
+}
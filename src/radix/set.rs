@@ -0,0 +1,256 @@
+use super::{longest_match, RadixNode};
+use std::cmp::Ordering;
+
+impl<K, V> RadixNode<K, V>
+where
+    K: Clone + Ord,
+{
+    /// Returns every entry found in either trie, keeping the value from `self`
+    /// for keys present in both.
+    pub fn union(self, other: Self) -> Self {
+        self.merge_with(other, |a, _| a)
+    }
+
+    /// Returns the entries of `self` whose keys are also present in `other`.
+    pub fn intersection<W>(mut self, other: &RadixNode<K, W>) -> Self {
+        if other.value.is_none() {
+            self.value = None;
+        }
+        self.children = intersect_children(self.children, &other.children);
+        self
+    }
+
+    /// Returns the entries of `self` whose keys are not present in `other`.
+    pub fn difference<W>(mut self, other: &RadixNode<K, W>) -> Self {
+        if other.value.is_some() {
+            self.value = None;
+        }
+        self.children = subtract_children(self.children, &other.children);
+        self
+    }
+
+    /// Returns the entries whose keys are present in exactly one of the tries.
+    pub fn symmetric_difference(self, other: Self) -> Self {
+        self.combine(other, &mut |_, _| None)
+    }
+
+    /// Merges `other` into `self`, calling `f` with the value from `self` and
+    /// the value from `other` for every key present in both.
+    pub fn merge_with<F>(self, other: Self, mut f: F) -> Self
+    where
+        F: FnMut(V, V) -> V,
+    {
+        self.combine(other, &mut |a, b| Some(f(a, b)))
+    }
+
+    fn combine<F>(mut self, other: Self, f: &mut F) -> Self
+    where
+        F: FnMut(V, V) -> Option<V>,
+    {
+        self.value = merge_values(self.value, other.value, f);
+        self.children = merge_children(self.children, other.children, f);
+        self
+    }
+}
+
+fn merge_values<V, F>(a: Option<V>, b: Option<V>, f: &mut F) -> Option<V>
+where
+    F: FnMut(V, V) -> Option<V>,
+{
+    match (a, b) {
+        (Some(a), Some(b)) => f(a, b),
+        (a, None) => a,
+        (None, b) => b,
+    }
+}
+
+/// Merges two sibling lists sorted by first element, walking into the pairs
+/// that share a first element instead of reinserting their entries.
+fn merge_children<K, V, F>(
+    a: Vec<RadixNode<K, V>>,
+    b: Vec<RadixNode<K, V>>,
+    f: &mut F,
+) -> Vec<RadixNode<K, V>>
+where
+    K: Clone + Ord,
+    F: FnMut(V, V) -> Option<V>,
+{
+    let mut merged = Vec::with_capacity(usize::max(a.len(), b.len()));
+    let mut a = a.into_iter().peekable();
+    let mut b = b.into_iter().peekable();
+
+    loop {
+        let ord = match (a.peek(), b.peek()) {
+            (Some(x), Some(y)) => x.key[0].cmp(&y.key[0]),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => break,
+        };
+
+        match ord {
+            Ordering::Less => merged.push(a.next().unwrap()),
+            Ordering::Greater => merged.push(b.next().unwrap()),
+            Ordering::Equal => {
+                let (x, y) = (a.next().unwrap(), b.next().unwrap());
+                merged.extend(merge_nodes(x, y, f));
+            }
+        }
+    }
+
+    merged
+}
+
+fn merge_nodes<K, V, F>(
+    mut a: RadixNode<K, V>,
+    mut b: RadixNode<K, V>,
+    f: &mut F,
+) -> Option<RadixNode<K, V>>
+where
+    K: Clone + Ord,
+    F: FnMut(V, V) -> Option<V>,
+{
+    let lcs = longest_match(&a.key, &b.key);
+
+    match (lcs == a.key.len(), lcs == b.key.len()) {
+        (true, true) => {
+            a.value = merge_values(a.value, b.value, f);
+            a.children = merge_children(a.children, b.children, f);
+            a.compact()
+        }
+        (true, false) => {
+            b.key.drain(..lcs);
+            a.children = merge_children(a.children, vec![b], f);
+            a.compact()
+        }
+        (false, true) => {
+            a.key.drain(..lcs);
+            b.children = merge_children(vec![a], b.children, f);
+            b.compact()
+        }
+        (false, false) => {
+            let mut parent = RadixNode::from_key(&a.key[..lcs]);
+            a.key.drain(..lcs);
+            b.key.drain(..lcs);
+            parent.children = match a.key < b.key {
+                true => vec![a, b],
+                false => vec![b, a],
+            };
+            Some(parent)
+        }
+    }
+}
+
+fn intersect_children<K, V, W>(
+    a: Vec<RadixNode<K, V>>,
+    b: &[RadixNode<K, W>],
+) -> Vec<RadixNode<K, V>>
+where
+    K: Clone + Ord,
+{
+    a.into_iter()
+        .filter_map(|node| {
+            let idx = b.binary_search_by(|n| n.key[0].cmp(&node.key[0])).ok()?;
+            intersect_node(node, &b[idx].key, &b[idx])
+        })
+        .collect()
+}
+
+/// Intersects `a` with the part of `b` left after `key`, the unmatched tail
+/// of `b`'s edge, which shares its first element with `a`'s key.
+fn intersect_node<K, V, W>(
+    mut a: RadixNode<K, V>,
+    key: &[K],
+    b: &RadixNode<K, W>,
+) -> Option<RadixNode<K, V>>
+where
+    K: Clone + Ord,
+{
+    let lcs = longest_match(&a.key, key);
+
+    match (lcs == a.key.len(), lcs == key.len()) {
+        (true, true) => {
+            if b.value.is_none() {
+                a.value = None;
+            }
+            a.children = intersect_children(a.children, &b.children);
+            a.compact()
+        }
+        (true, false) => {
+            let rest = &key[lcs..];
+            a.value = None;
+            a.children = a
+                .children
+                .into_iter()
+                .filter(|n| n.key[0] == rest[0])
+                .filter_map(|n| intersect_node(n, rest, b))
+                .collect();
+            a.compact()
+        }
+        (false, true) => {
+            let mut parent = RadixNode::from_key(&a.key[..lcs]);
+            a.key.drain(..lcs);
+            parent.children = intersect_children(vec![a], &b.children);
+            parent.compact()
+        }
+        (false, false) => None,
+    }
+}
+
+fn subtract_children<K, V, W>(
+    a: Vec<RadixNode<K, V>>,
+    b: &[RadixNode<K, W>],
+) -> Vec<RadixNode<K, V>>
+where
+    K: Clone + Ord,
+{
+    a.into_iter()
+        .filter_map(
+            |node| match b.binary_search_by(|n| n.key[0].cmp(&node.key[0])) {
+                Ok(idx) => subtract_node(node, &b[idx].key, &b[idx]),
+                Err(_) => Some(node),
+            },
+        )
+        .collect()
+}
+
+/// Removes from `a` everything stored in `b` after `key`, with the same
+/// alignment as [`intersect_node`].
+fn subtract_node<K, V, W>(
+    mut a: RadixNode<K, V>,
+    key: &[K],
+    b: &RadixNode<K, W>,
+) -> Option<RadixNode<K, V>>
+where
+    K: Clone + Ord,
+{
+    let lcs = longest_match(&a.key, key);
+
+    match (lcs == a.key.len(), lcs == key.len()) {
+        (true, true) => {
+            if b.value.is_some() {
+                a.value = None;
+            }
+            a.children = subtract_children(a.children, &b.children);
+            a.compact()
+        }
+        (true, false) => {
+            let rest = &key[lcs..];
+            a.children = a
+                .children
+                .into_iter()
+                .filter_map(|n| match n.key[0] == rest[0] {
+                    true => subtract_node(n, rest, b),
+                    false => Some(n),
+                })
+                .collect();
+            a.compact()
+        }
+        (false, true) => {
+            let mut parent = RadixNode::from_key(&a.key[..lcs]);
+            a.key.drain(..lcs);
+            parent.children = subtract_children(vec![a], &b.children);
+            parent.compact()
+        }
+        (false, false) => Some(a),
+    }
+}
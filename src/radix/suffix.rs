@@ -0,0 +1,364 @@
+use std::borrow::Borrow;
+
+const ROOT: usize = 0;
+/// Edge end of a leaf while the tree is being built, standing for "the end
+/// of the text read so far".
+const OPEN: usize = usize::MAX;
+
+/// A generalized suffix tree over one or more documents, built in linear
+/// time with Ukkonen's algorithm.
+///
+/// The tree has the shape of a [`RadixNode`](super::RadixNode) holding every
+/// suffix of every document: edges are compressed and children are kept
+/// sorted by their first element. Edge labels are ranges of the stored text
+/// rather than owned keys, though, so the whole tree takes space linear in
+/// the text. Each document is followed by a terminator unique to it, so no
+/// suffix is a prefix of another and matches never span two documents.
+///
+/// Positions are given as `(document, offset)` pairs, counting documents in
+/// the order they were passed in.
+#[derive(Debug, Clone)]
+pub struct SuffixTree<K> {
+    /// Every document followed by its terminator, written `None`.
+    text: Vec<Option<K>>,
+    /// Position in `text` of each document's terminator.
+    ends: Vec<usize>,
+    nodes: Vec<Node>,
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    /// Range of `text` labelling the edge into the node.
+    start: usize,
+    end: usize,
+    /// Suffix link, only meaningful for internal nodes.
+    link: usize,
+    /// Start in `text` of the suffix a leaf spells, or `OPEN` for internal
+    /// nodes.
+    suffix: usize,
+    /// Sorted by the first element of their edge.
+    children: Vec<usize>,
+}
+
+/// An element of the text, where each terminator is distinct from every
+/// other element and ordered by its position.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Sym<'a, K> {
+    End(usize),
+    Elem(&'a K),
+}
+
+impl<K> SuffixTree<K>
+where
+    K: Clone + Ord,
+{
+    /// Builds the suffix tree of a single document.
+    pub fn new<Q>(text: &Q) -> Self
+    where
+        Q: Borrow<[K]> + ?Sized,
+    {
+        Self::from_documents([text.borrow()])
+    }
+
+    /// Builds the generalized suffix tree of several documents.
+    pub fn from_documents<I, D>(docs: I) -> Self
+    where
+        I: IntoIterator<Item = D>,
+        D: AsRef<[K]>,
+    {
+        let mut text = Vec::new();
+        let mut ends = Vec::new();
+        for doc in docs {
+            text.extend(doc.as_ref().iter().cloned().map(Some));
+            ends.push(text.len());
+            text.push(None);
+        }
+
+        let mut tree = Self {
+            text,
+            ends,
+            nodes: vec![Node::new(0, 0)],
+        };
+        tree.build();
+        tree
+    }
+
+    /// Returns the number of documents in the tree.
+    pub fn documents(&self) -> usize {
+        self.ends.len()
+    }
+
+    /// Returns `true` if `pattern` occurs in any document.
+    pub fn contains<Q>(&self, pattern: &Q) -> bool
+    where
+        Q: Borrow<[K]> + ?Sized,
+    {
+        self.locate(pattern.borrow()).is_some()
+    }
+
+    /// Returns every position at which `pattern` occurs, sorted. The empty
+    /// pattern occurs at every offset of every document.
+    pub fn occurrences<Q>(&self, pattern: &Q) -> Vec<(usize, usize)>
+    where
+        Q: Borrow<[K]> + ?Sized,
+    {
+        let node = match self.locate(pattern.borrow()) {
+            Some(node) => node,
+            None => return Vec::new(),
+        };
+
+        let mut found = Vec::new();
+        let mut stack = vec![node];
+        while let Some(idx) = stack.pop() {
+            let node = &self.nodes[idx];
+            match node.children.is_empty() {
+                true if self.text[node.suffix].is_some() => {
+                    found.push(self.position(node.suffix));
+                }
+                true => (),
+                false => stack.extend(&node.children),
+            }
+        }
+
+        found.sort_unstable();
+        found
+    }
+
+    /// Returns the longest substring occurring at least twice, within one
+    /// document or across several, or `None` if no element repeats. Ties go
+    /// to the smallest substring.
+    pub fn longest_repeated(&self) -> Option<Vec<K>> {
+        let mut best = (0, ROOT);
+        let mut stack = vec![(ROOT, 0)];
+
+        while let Some((idx, depth)) = stack.pop() {
+            let node = &self.nodes[idx];
+            if node.children.is_empty() {
+                continue;
+            }
+            if depth > best.0 {
+                best = (depth, idx);
+            }
+            // children reversed so the smallest is visited first
+            stack.extend(node.children.iter().rev().map(|&c| {
+                let child = &self.nodes[c];
+                (c, depth + child.end - child.start)
+            }));
+        }
+
+        self.label(best)
+    }
+
+    /// Returns the longest substring occurring in every document, or `None`
+    /// if they share no element. Ties go to the smallest substring.
+    ///
+    /// Each internal node gathers the set of documents below it, bottom up,
+    /// and the deepest node whose set is complete spells the answer.
+    pub fn longest_common_substring(&self) -> Option<Vec<K>> {
+        // a lone document ends in a leaf, which the walk below never takes
+        if let [end] = self.ends[..] {
+            return (end > 0).then(|| self.text[..end].iter().flatten().cloned().collect());
+        }
+
+        let words = self.documents().div_ceil(64);
+        let full = |docs: &[u64]| {
+            let count: u32 = docs.iter().map(|w| w.count_ones()).sum();
+            count as usize == self.documents()
+        };
+
+        let mut best = (0, ROOT);
+        // node, string depth, index of the next child, documents seen below
+        let mut stack = vec![(ROOT, 0, 0, vec![0u64; words])];
+
+        while let Some((idx, depth, next, docs)) = stack.pop() {
+            let node = &self.nodes[idx];
+
+            if let Some(&c) = node.children.get(next) {
+                let child = &self.nodes[c];
+                let mut below = vec![0u64; words];
+                if child.children.is_empty() {
+                    let (doc, _) = self.position(child.suffix);
+                    below[doc / 64] |= 1 << (doc % 64);
+                }
+                let child_depth = depth + child.end - child.start;
+                stack.push((idx, depth, next + 1, docs));
+                stack.push((c, child_depth, 0, below));
+                continue;
+            }
+
+            // every child is done, so `docs` is complete
+            if !node.children.is_empty() && depth > best.0 && full(&docs) {
+                best = (depth, idx);
+            }
+            if let Some((_, _, _, parent)) = stack.last_mut() {
+                for (p, d) in parent.iter_mut().zip(&docs) {
+                    *p |= d;
+                }
+            }
+        }
+
+        self.label(best)
+    }
+
+    /// Runs Ukkonen's algorithm over the whole text, then closes the leaves
+    /// and records the suffix each one spells.
+    fn build(&mut self) {
+        let mut active_node = ROOT;
+        let mut active_edge = 0;
+        let mut active_len = 0;
+        let mut remainder = 0;
+
+        for i in 0..self.text.len() {
+            remainder += 1;
+            let mut last_split: Option<usize> = None;
+
+            while remainder > 0 {
+                if active_len == 0 {
+                    active_edge = i;
+                }
+
+                match self.child(active_node, self.sym(active_edge)) {
+                    Err(pos) => {
+                        let leaf = self.push(Node::new(i, OPEN));
+                        self.nodes[active_node].children.insert(pos, leaf);
+                        if let Some(split) = last_split.take() {
+                            self.nodes[split].link = active_node;
+                        }
+                    }
+                    Ok(pos) => {
+                        let next = self.nodes[active_node].children[pos];
+                        let start = self.nodes[next].start;
+                        let len = self.nodes[next].end.min(i + 1) - start;
+                        if active_len >= len {
+                            // walk down past the whole edge
+                            active_edge += len;
+                            active_len -= len;
+                            active_node = next;
+                            continue;
+                        }
+
+                        if self.sym(start + active_len) == self.sym(i) {
+                            // already present: extend the active point and
+                            // leave the rest of the suffixes implicit
+                            if let Some(split) = last_split.take() {
+                                self.nodes[split].link = active_node;
+                            }
+                            active_len += 1;
+                            break;
+                        }
+
+                        let split = self.push(Node::new(start, start + active_len));
+                        let leaf = self.push(Node::new(i, OPEN));
+                        self.nodes[next].start += active_len;
+                        self.nodes[active_node].children[pos] = split;
+                        let children = match self.sym(start + active_len) < self.sym(i) {
+                            true => vec![next, leaf],
+                            false => vec![leaf, next],
+                        };
+                        self.nodes[split].children = children;
+
+                        if let Some(prev) = last_split.replace(split) {
+                            self.nodes[prev].link = split;
+                        }
+                    }
+                }
+
+                remainder -= 1;
+                if active_node == ROOT && active_len > 0 {
+                    active_len -= 1;
+                    active_edge = i + 1 - remainder;
+                } else if active_node != ROOT {
+                    active_node = self.nodes[active_node].link;
+                }
+            }
+        }
+
+        let len = self.text.len();
+        let mut stack = vec![(ROOT, 0)];
+        while let Some((idx, depth)) = stack.pop() {
+            let node = &mut self.nodes[idx];
+            if node.end == OPEN {
+                node.end = len;
+            }
+            let depth = depth + node.end - node.start;
+            if node.children.is_empty() {
+                node.suffix = len - depth;
+            }
+            stack.extend(node.children.iter().map(|&c| (c, depth)));
+        }
+    }
+
+    fn push(&mut self, node: Node) -> usize {
+        self.nodes.push(node);
+        self.nodes.len() - 1
+    }
+
+    fn sym(&self, pos: usize) -> Sym<'_, K> {
+        match &self.text[pos] {
+            Some(elem) => Sym::Elem(elem),
+            None => Sym::End(pos),
+        }
+    }
+
+    /// Finds the child of `node` whose edge starts with `sym`.
+    fn child(&self, node: usize, sym: Sym<'_, K>) -> Result<usize, usize> {
+        self.nodes[node]
+            .children
+            .binary_search_by(|&c| self.sym(self.nodes[c].start).cmp(&sym))
+    }
+
+    /// Returns the node at or just below the end of the path spelling
+    /// `pattern`, if there is one.
+    fn locate(&self, pattern: &[K]) -> Option<usize> {
+        let mut node = ROOT;
+        let mut rest = pattern;
+
+        while let Some(first) = rest.first() {
+            let pos = self.child(node, Sym::Elem(first)).ok()?;
+            node = self.nodes[node].children[pos];
+
+            let Node { start, end, .. } = self.nodes[node];
+            let n = rest.len().min(end - start);
+            let label = &self.text[start..start + n];
+            if label.iter().zip(rest).any(|(t, k)| t.as_ref() != Some(k)) {
+                return None;
+            }
+            rest = &rest[n..];
+        }
+
+        Some(node)
+    }
+
+    /// Maps a position in `text` to a document and an offset in it.
+    fn position(&self, pos: usize) -> (usize, usize) {
+        let doc = self.ends.partition_point(|&end| end < pos);
+        let start = match doc {
+            0 => 0,
+            _ => self.ends[doc - 1] + 1,
+        };
+        (doc, pos - start)
+    }
+
+    /// Spells the path down to `node`, given its string depth, as it ends
+    /// that many elements before the end of the node's edge.
+    fn label(&self, (depth, node): (usize, usize)) -> Option<Vec<K>> {
+        if depth == 0 {
+            return None;
+        }
+        let end = self.nodes[node].end;
+        let elems = self.text[end - depth..end].iter().flatten();
+        Some(elems.cloned().collect())
+    }
+}
+
+impl Node {
+    fn new(start: usize, end: usize) -> Self {
+        Self {
+            start,
+            end,
+            link: ROOT,
+            suffix: OPEN,
+            children: Vec::new(),
+        }
+    }
+}
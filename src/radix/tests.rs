@@ -1,5 +1,11 @@
-use crate::radix::RadixNode;
-use crate::TrieExt;
+use crate::radix::{
+    ArenaRadix, ConcurrentRadix, PersistentRadix, RadixNode, ScoredRadix, SuffixTree,
+};
+use crate::test_util::{words, Lcg};
+use crate::{TrieError, TrieExt};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
+use std::hash::{Hash, Hasher};
 
 #[test]
 fn radix1() {
@@ -68,3 +74,1179 @@ fn radix1() {
     assert_eq!(v, None);
 }
 
+#[test]
+fn radix_get_mut() {
+    let mut radix = RadixNode::new();
+    assert!(radix.is_empty());
+
+    radix.insert(&[1, 2, 3], 1).unwrap();
+    radix.insert(&[1, 2], 2).unwrap();
+    radix.insert(&[1, 3], 3).unwrap();
+    assert_eq!(radix.len(), 3);
+
+    *radix.get_mut(&[1, 2, 3]).unwrap() += 10;
+    assert_eq!(radix.get(&[1, 2, 3]), Some(&11));
+    assert!(radix.get_mut(&[1]).is_none());
+    assert!(radix.get_mut(&[1, 2, 3, 4]).is_none());
+
+    assert!(radix.contains_key(&[1, 2]));
+    assert!(!radix.contains_key(&[2]));
+
+    radix.clear();
+    assert!(radix.is_empty());
+    assert_eq!(radix.get(&[1, 2]), None);
+
+    radix.insert(b"ab", 1).unwrap();
+    radix.insert(b"ac", 2).unwrap();
+    radix.remove(b"ab", false);
+    radix.remove(b"ac", false);
+    assert!(radix.is_empty());
+    assert_eq!(radix.stats().nodes, 1);
+}
+
+#[test]
+fn radix_empty_key() {
+    let mut radix = RadixNode::new();
+    let empty: [u8; 0] = [];
+
+    assert_eq!(radix.get(&empty), None);
+    assert!(radix.remove(&empty, false).is_none());
+
+    assert_eq!(radix.insert(&empty, 1), Ok(None));
+    radix.insert(b"ab", 2).unwrap();
+    radix.insert(b"a", 3).unwrap();
+    assert_eq!(radix.insert(&empty, 4), Ok(Some(1)));
+
+    assert_eq!(radix.get(&empty), Some(&4));
+    assert_eq!(radix.len(), 3);
+    assert_eq!(radix.children().len(), 1);
+
+    let entries: Vec<_> = radix.iter().collect();
+    assert_eq!(
+        entries,
+        vec![(vec![], &4), (b"a".to_vec(), &3), (b"ab".to_vec(), &2)]
+    );
+
+    *radix.get_mut(&empty).unwrap() = 5;
+    let removed = radix.remove(&empty, false).unwrap();
+    assert_eq!(removed.value(), Some(&5));
+    assert_eq!(radix.get(&empty), None);
+    assert_eq!(radix.get(b"ab"), Some(&2));
+
+    let removed = radix.remove(&empty, true).unwrap();
+    assert_eq!(removed.len(), 2);
+    assert!(radix.is_empty());
+}
+
+#[test]
+fn radix_remove_missing() {
+    let mut radix = RadixNode::new();
+    assert!(radix.remove(b"abc", false).is_none());
+
+    radix.insert(b"abc", 1).unwrap();
+    assert!(radix.remove(b"abd", false).is_none());
+    assert!(radix.remove(b"abcd", true).is_none());
+    assert_eq!(radix.remove(b"abc", false).unwrap().value(), Some(&1));
+    assert!(radix.remove(b"abc", true).is_none());
+
+    // a branching node without a value is not a key
+    radix.insert(b"ab", 2).unwrap();
+    radix.insert(b"ac", 3).unwrap();
+    assert!(radix.remove(b"a", false).is_none());
+    assert_eq!(radix.remove(b"ab", false).unwrap().value(), Some(&2));
+    assert_eq!(radix.children().len(), 1);
+    assert_eq!(radix.children()[0].key(), b"ac");
+}
+
+#[test]
+fn radix_wide_fanout() {
+    let mut radix = RadixNode::new();
+    let mut expected = BTreeMap::new();
+
+    // insert in a scrambled order so new children land all over the vector
+    for i in 0..=u8::MAX {
+        let a = i.wrapping_mul(167);
+        for b in [a.wrapping_mul(31), a, 0] {
+            radix.insert(&[a, b, 1], u32::from(b)).unwrap();
+            expected.insert(vec![a, b, 1], u32::from(b));
+        }
+    }
+
+    assert_eq!(radix.children().len(), 256);
+    assert!(radix.children().windows(2).all(|w| w[0].key() < w[1].key()));
+    check(&radix, &expected);
+
+    for i in (0..=u8::MAX).step_by(3) {
+        assert_eq!(radix.remove(&[i, 0, 1], false).unwrap().value(), Some(&0));
+        assert!(radix.remove(&[i, 0], false).is_none());
+        expected.remove(&vec![i, 0, 1]);
+    }
+
+    check(&radix, &expected);
+    assert_eq!(radix.get(&[7, 7]), None);
+}
+
+fn build(keys: &[Vec<u8>], tag: u32) -> (RadixNode<u8, u32>, BTreeMap<Vec<u8>, u32>) {
+    let mut radix = RadixNode::new();
+    let mut map = BTreeMap::new();
+    for (i, key) in keys.iter().enumerate() {
+        let value = tag + i as u32;
+        radix.insert(key, value).unwrap();
+        map.insert(key.clone(), value);
+    }
+    (radix, map)
+}
+
+fn same_shape(a: &RadixNode<u8, u32>, b: &RadixNode<u8, u32>) -> bool {
+    a.key() == b.key()
+        && a.value() == b.value()
+        && a.children().len() == b.children().len()
+        && a.children()
+            .iter()
+            .zip(b.children())
+            .all(|(x, y)| same_shape(x, y))
+}
+
+fn check(radix: &RadixNode<u8, u32>, expected: &BTreeMap<Vec<u8>, u32>) {
+    let entries: Vec<_> = radix.iter().map(|(k, v)| (k, *v)).collect();
+    let expected_entries: Vec<_> = expected.iter().map(|(k, v)| (k.clone(), *v)).collect();
+    assert_eq!(entries, expected_entries);
+
+    // structurally identical to a trie built key by key
+    let rebuilt: RadixNode<u8, u32> = expected.iter().map(|(k, v)| (k, *v)).collect();
+    assert!(same_shape(radix, &rebuilt));
+}
+
+#[test]
+fn radix_set_operations() {
+    let a_keys = words(1, 40, 0..=15, b'a'..=b'c');
+    let b_keys = words(2, 40, 0..=15, b'a'..=b'c');
+    let a = || build(&a_keys, 0).0;
+    let b = || build(&b_keys, 1000).0;
+    let (_, a_map) = build(&a_keys, 0);
+    let (_, b_map) = build(&b_keys, 1000);
+
+    let mut union = a_map.clone();
+    for (k, v) in &b_map {
+        union.entry(k.clone()).or_insert(*v);
+    }
+    check(&a().union(b()), &union);
+
+    let mut merged = a_map.clone();
+    for (k, v) in &b_map {
+        *merged.entry(k.clone()).or_insert(0) += *v;
+    }
+    check(&a().merge_with(b(), |x, y| x + y), &merged);
+
+    let intersection: BTreeMap<_, _> = a_map
+        .iter()
+        .filter(|(k, _)| b_map.contains_key(*k))
+        .map(|(k, v)| (k.clone(), *v))
+        .collect();
+    assert!(!intersection.is_empty());
+    check(&a().intersection(&b()), &intersection);
+
+    let difference: BTreeMap<_, _> = a_map
+        .iter()
+        .filter(|(k, _)| !b_map.contains_key(*k))
+        .map(|(k, v)| (k.clone(), *v))
+        .collect();
+    check(&a().difference(&b()), &difference);
+
+    let mut symmetric = difference.clone();
+    for (k, v) in &b_map {
+        if !a_map.contains_key(k) {
+            symmetric.insert(k.clone(), *v);
+        }
+    }
+    check(&a().symmetric_difference(b()), &symmetric);
+}
+
+#[test]
+fn radix_retain_and_split() {
+    let keys = words(3, 60, 0..=15, b'a'..=b'c');
+    let (_, map) = build(&keys, 0);
+
+    let (mut radix, _) = build(&keys, 0);
+    radix.retain(|k, v| {
+        *v += 1;
+        k.len() % 2 == 0
+    });
+    let retained: BTreeMap<_, _> = map
+        .iter()
+        .filter(|(k, _)| k.len() % 2 == 0)
+        .map(|(k, v)| (k.clone(), v + 1))
+        .collect();
+    check(&radix, &retained);
+
+    for prefix in [&b""[..], b"a", b"ab", b"bca", b"ccc", b"cab"] {
+        let (mut radix, _) = build(&keys, 0);
+        let (inside, outside): (BTreeMap<_, _>, BTreeMap<_, _>) = map
+            .clone()
+            .into_iter()
+            .partition(|(k, _)| k.starts_with(prefix));
+
+        let split = radix.split_off_prefix(prefix);
+        check(&radix, &outside);
+        check(&split, &inside);
+
+        let (mut radix, _) = build(&keys, 0);
+        let drained = radix.drain_prefix(prefix);
+        assert_eq!(drained, inside.into_iter().collect::<Vec<_>>());
+        check(&radix, &outside);
+    }
+
+    for key in [&b""[..], b"a", b"abc", b"b", b"bb", b"cba", b"d"] {
+        let (mut radix, _) = build(&keys, 0);
+        let mut left = map.clone();
+        let right = left.split_off(key);
+
+        let split = radix.split_off(key);
+        check(&radix, &left);
+        check(&split, &right);
+    }
+}
+
+#[test]
+fn radix_collection_traits() {
+    let keys = words(4, 30, 0..=15, b'a'..=b'c');
+    let radix: RadixNode<u8, usize> = keys.iter().enumerate().map(|(i, k)| (k, i)).collect();
+    let mut reversed = RadixNode::new();
+    reversed.extend(keys.iter().enumerate().rev().map(|(i, k)| (k.clone(), i)));
+
+    let map: BTreeMap<_, _> = keys
+        .iter()
+        .enumerate()
+        .map(|(i, k)| (k.clone(), i))
+        .collect();
+    let mut last = RadixNode::default();
+    last.extend(map.iter().map(|(k, v)| (k, *v)));
+
+    assert_eq!(radix, last);
+    assert_eq!(radix.clone(), radix);
+    assert_ne!(radix, RadixNode::new());
+
+    let hash = |r: &RadixNode<u8, usize>| {
+        let mut hasher = DefaultHasher::new();
+        r.hash(&mut hasher);
+        hasher.finish()
+    };
+    assert_eq!(hash(&radix), hash(&last));
+
+    for (key, value) in &radix {
+        assert_eq!(radix[&key[..]], *value);
+        assert_eq!(map[&key], *value);
+    }
+
+    let owned: Vec<_> = radix.into_iter().collect();
+    assert_eq!(owned, map.into_iter().collect::<Vec<_>>());
+    assert_eq!(reversed.len(), owned.len());
+}
+
+#[test]
+fn arena_radix() {
+    fn nodes(radix: &RadixNode<u8, u32>) -> usize {
+        1 + radix.children().iter().map(nodes).sum::<usize>()
+    }
+
+    let keys = words(11, 400, 0..=15, b'a'..=b'c');
+    let mut arena = ArenaRadix::new();
+    let mut expected = BTreeMap::new();
+
+    for (i, key) in keys.iter().enumerate() {
+        assert_eq!(
+            arena.insert(key, i as u32).unwrap(),
+            expected.insert(key.clone(), i as u32)
+        );
+    }
+
+    let check = |arena: &ArenaRadix<u8, u32>, expected: &BTreeMap<Vec<u8>, u32>| {
+        let entries: Vec<_> = arena.iter().map(|(k, v)| (k, *v)).collect();
+        assert_eq!(entries, expected.clone().into_iter().collect::<Vec<_>>());
+        assert_eq!(arena.len(), expected.len());
+
+        // as many nodes as a compressed RadixNode holding the same keys
+        let boxed: RadixNode<u8, u32> = expected.iter().map(|(k, v)| (k, *v)).collect();
+        assert_eq!(arena.memory_usage().nodes, nodes(&boxed));
+    };
+    check(&arena, &expected);
+
+    let usage = arena.memory_usage();
+    assert!(usage.arena_bytes < usage.boxed_bytes);
+
+    for key in keys.iter().step_by(2) {
+        let removed = arena.remove(key, false).map(|r| r.get(&[]).copied());
+        assert_eq!(removed, expected.remove(key).map(Some));
+    }
+    check(&arena, &expected);
+
+    let capacity = arena.memory_usage().capacity;
+    for (i, key) in keys.iter().enumerate().step_by(4) {
+        arena.insert(key, i as u32).unwrap();
+        expected.insert(key.clone(), i as u32);
+    }
+    check(&arena, &expected);
+    assert_eq!(arena.memory_usage().capacity, capacity);
+
+    let pruned = arena.remove(b"ab", true).unwrap();
+    let below: Vec<_> = pruned.iter().map(|(k, v)| (k, *v)).collect();
+    let want: Vec<_> = expected
+        .iter()
+        .filter(|(k, _)| k.starts_with(b"ab"))
+        .map(|(k, v)| (k[2..].to_vec(), *v))
+        .collect();
+    assert_eq!(below, want);
+    expected.retain(|k, _| !k.starts_with(b"ab"));
+    check(&arena, &expected);
+
+    arena.shrink_to_fit();
+    let usage = arena.memory_usage();
+    assert_eq!(usage.capacity, usage.nodes);
+    check(&arena, &expected);
+
+    let mut small = ArenaRadix::with_max_nodes(3);
+    small.insert(b"abc", ()).unwrap();
+    assert_eq!(small.insert(b"abd", ()), Err(TrieError::CapacityExceeded));
+    assert_eq!(small.insert(b"b", ()), Ok(None));
+    assert_eq!(small.insert(b"abc", ()), Ok(Some(())));
+}
+
+#[test]
+fn persistent_radix() {
+    let keys = words(17, 300, 0..=15, b'a'..=b'c');
+    let mut radix = PersistentRadix::new();
+    let mut expected = BTreeMap::new();
+    let mut versions = vec![(radix.clone(), expected.clone())];
+
+    for (i, key) in keys.iter().enumerate() {
+        if i % 3 == 2 {
+            let removed = radix.remove(key, false).map(|r| r[key.as_slice()]);
+            assert_eq!(removed, expected.remove(key));
+        } else {
+            let old = radix.insert(key, i as u32).unwrap();
+            assert_eq!(old, expected.insert(key.clone(), i as u32));
+        }
+        versions.push((radix.clone(), expected.clone()));
+    }
+
+    // every snapshot still holds exactly what it held when it was taken
+    for (version, expected) in &versions {
+        let entries: Vec<_> = version.iter().map(|(k, v)| (k, *v)).collect();
+        assert_eq!(entries, expected.clone().into_iter().collect::<Vec<_>>());
+        assert_eq!(version.len(), expected.len());
+    }
+
+    let next = radix.update(b"abc", 1000);
+    assert_eq!(next.get(b"abc"), Some(&1000));
+    assert_eq!(radix.get(b"abc"), expected.get(b"abc".as_slice()));
+    assert!(next.without(b"zzz").ptr_eq(&next));
+    assert!(next.without(b"abc") != next);
+
+    *radix.get_mut(&keys[0]).unwrap() += 1;
+    assert_eq!(versions[1].0.get(&keys[0]), Some(&0));
+
+    let snapshot = radix.clone();
+    let pruned = radix.remove(b"ab", true).unwrap();
+    let below: Vec<_> = snapshot
+        .iter()
+        .filter(|(k, _)| k.starts_with(b"ab"))
+        .collect();
+    assert_eq!(pruned.iter().collect::<Vec<_>>(), below);
+    assert!(radix.iter().all(|(k, _)| !k.starts_with(b"ab")));
+    assert_eq!(radix.len() + pruned.len(), snapshot.len());
+
+    let boxed: RadixNode<u8, u32> = expected.iter().map(|(k, v)| (k, *v)).collect();
+    assert_eq!(PersistentRadix::from(boxed), versions.last().unwrap().0);
+}
+
+#[test]
+fn persistent_radix_path_copying() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static CLONES: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Debug, PartialEq)]
+    struct Counted(usize);
+
+    impl Clone for Counted {
+        fn clone(&self) -> Self {
+            CLONES.fetch_add(1, Ordering::Relaxed);
+            Counted(self.0)
+        }
+    }
+
+    let keys = words(23, 500, 0..=15, b'a'..=b'c');
+    let radix: PersistentRadix<u8, Counted> = keys
+        .iter()
+        .enumerate()
+        .map(|(i, k)| (k, Counted(i)))
+        .collect();
+    assert_eq!(CLONES.load(Ordering::Relaxed), 0);
+
+    // only the values on the path to the key are copied, never the whole trie
+    let key = keys.iter().max_by_key(|k| k.len()).unwrap();
+    let next = radix.update(key, Counted(0));
+    assert!(CLONES.load(Ordering::Relaxed) <= key.len());
+    assert_eq!(next.get(key), Some(&Counted(0)));
+    assert_ne!(radix.get(key), Some(&Counted(0)));
+
+    // a version that is no longer shared is updated in place
+    let mut next = next;
+    CLONES.store(0, Ordering::Relaxed);
+    drop(radix);
+    next.insert(key, Counted(1)).unwrap();
+    next.remove(key, false);
+    assert_eq!(CLONES.load(Ordering::Relaxed), 0);
+}
+
+#[test]
+fn persistent_radix_threads() {
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    let current = Arc::new(Mutex::new(PersistentRadix::<u8, usize>::new()));
+
+    let readers: Vec<_> = (0..4)
+        .map(|_| {
+            let current = Arc::clone(&current);
+            thread::spawn(move || {
+                for _ in 0..200 {
+                    // a snapshot is internally consistent while the writer
+                    // keeps publishing new versions
+                    let snapshot = current.lock().unwrap().clone();
+                    let n = snapshot.len();
+                    assert_eq!(snapshot.iter().count(), n);
+                    assert!(snapshot.iter().all(|(k, &v)| k == v.to_be_bytes()));
+                }
+            })
+        })
+        .collect();
+
+    for i in 0..1000usize {
+        let next = current.lock().unwrap().update(&i.to_be_bytes(), i);
+        *current.lock().unwrap() = next;
+    }
+
+    for reader in readers {
+        reader.join().unwrap();
+    }
+    assert_eq!(current.lock().unwrap().len(), 1000);
+}
+
+#[test]
+fn concurrent_radix() {
+    let keys = words(29, 400, 0..=15, b'a'..=b'c');
+    let radix = ConcurrentRadix::new();
+    let mut expected = BTreeMap::new();
+
+    for (i, key) in keys.iter().enumerate() {
+        if i % 3 == 2 {
+            assert_eq!(radix.remove(key), expected.remove(key));
+        } else {
+            assert_eq!(radix.insert(key, i), expected.insert(key.clone(), i));
+        }
+    }
+
+    assert_eq!(radix.len(), expected.len());
+    assert_eq!(
+        radix.iter().collect::<Vec<_>>(),
+        expected.clone().into_iter().collect::<Vec<_>>()
+    );
+    for key in &keys {
+        assert_eq!(radix.get(key), expected.get(key).copied());
+    }
+
+    for prefix in [&b""[..], b"a", b"ab", b"abc", b"cab", b"ccccc"] {
+        let want: Vec<_> = expected
+            .iter()
+            .filter(|(k, _)| k.starts_with(prefix))
+            .map(|(k, v)| (k.clone(), *v))
+            .collect();
+        assert_eq!(radix.iter_prefix(prefix).collect::<Vec<_>>(), want);
+    }
+
+    for key in &keys {
+        assert_eq!(radix.remove(key), expected.remove(key));
+    }
+    assert!(radix.is_empty());
+    assert_eq!(radix.iter().next(), None);
+}
+
+#[test]
+fn concurrent_radix_threads() {
+    use std::sync::Arc;
+    use std::thread;
+
+    const THREADS: usize = 8;
+    const KEYS: usize = 2000;
+
+    let key = |t: usize, i: usize| format!("{}/{}", t % 3, i * THREADS + t).into_bytes();
+    let radix = Arc::new(ConcurrentRadix::new());
+
+    // writers share prefixes, so they split and merge the same nodes
+    let writers: Vec<_> = (0..THREADS)
+        .map(|t| {
+            let radix = Arc::clone(&radix);
+            thread::spawn(move || {
+                for i in 0..KEYS {
+                    assert_eq!(radix.insert(&key(t, i), i), None);
+                    if i % 2 == 1 {
+                        assert_eq!(radix.remove(&key(t, i - 1)), Some(i - 1));
+                    }
+                    assert_eq!(radix.get(&key(t, i)), Some(i));
+                }
+            })
+        })
+        .collect();
+
+    let readers: Vec<_> = (0..4)
+        .map(|r| {
+            let radix = Arc::clone(&radix);
+            thread::spawn(move || {
+                for _ in 0..50 {
+                    let prefix = format!("{}/", r % 3);
+                    let entries: Vec<_> = radix.iter_prefix(prefix.as_bytes()).collect();
+                    assert!(entries.windows(2).all(|w| w[0].0 < w[1].0));
+                    assert!(entries
+                        .iter()
+                        .all(|(k, _)| k.starts_with(prefix.as_bytes())));
+                }
+            })
+        })
+        .collect();
+
+    for handle in writers.into_iter().chain(readers) {
+        handle.join().unwrap();
+    }
+
+    let mut expected = BTreeMap::new();
+    for t in 0..THREADS {
+        for i in (1..KEYS).step_by(2) {
+            expected.insert(key(t, i), i);
+        }
+    }
+    assert_eq!(radix.len(), expected.len());
+    assert_eq!(
+        radix.iter().collect::<Vec<_>>(),
+        expected.into_iter().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn concurrent_radix_snapshot() {
+    use std::sync::Arc;
+    use std::thread;
+
+    // one writer inserts keys in order, so any atomic snapshot of them holds
+    // exactly the first `n` keys for some `n`
+    let radix = Arc::new(ConcurrentRadix::new());
+    let key = |i: usize| format!("k{:05}", i).into_bytes();
+
+    let writer = {
+        let radix = Arc::clone(&radix);
+        thread::spawn(move || {
+            for i in 0..5000 {
+                radix.insert(&key(i), i);
+            }
+        })
+    };
+
+    let mut last = 0;
+    while last < 5000 {
+        let snapshot: Vec<_> = radix.iter_prefix(b"k").map(|(_, v)| v).collect();
+        assert!(snapshot.iter().copied().eq(0..snapshot.len()));
+        assert!(snapshot.len() >= last);
+        last = snapshot.len();
+    }
+    writer.join().unwrap();
+}
+
+/// Edit distance by the full table, with adjacent swaps if `transpose`.
+fn edit_distance(a: &[u8], b: &[u8], transpose: bool) -> usize {
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if transpose && i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+#[test]
+fn radix_fuzzy() {
+    let radix: RadixNode<u8, usize> = ["checkout", "cherry-pick", "clone", "commit", "config"]
+        .iter()
+        .zip(0..)
+        .map(|(k, v)| (k.as_bytes(), v))
+        .collect();
+
+    let found: Vec<_> = radix
+        .fuzzy(b"comit".as_slice(), 2)
+        .into_iter()
+        .map(|(k, v, d)| (String::from_utf8(k).unwrap(), *v, d))
+        .collect();
+    assert_eq!(found, vec![("commit".to_string(), 3, 1)]);
+
+    assert!(radix.fuzzy(b"chekcout".as_slice(), 1).is_empty());
+    assert_eq!(radix.fuzzy(b"chekcout".as_slice(), 2)[0].2, 2);
+    assert_eq!(radix.fuzzy_transposed(b"chekcout".as_slice(), 1)[0].2, 1);
+    assert_eq!(radix.fuzzy(b"clone".as_slice(), 0).len(), 1);
+
+    for seed in 0..5 {
+        let keys = words(seed, 300, 0..=15, b'a'..=b'c');
+        let (radix, expected) = build(&keys, 0);
+
+        for query in words(seed + 50, 20, 0..=15, b'a'..=b'c') {
+            for max in 0..4 {
+                for transpose in [false, true] {
+                    let found: Vec<_> = match transpose {
+                        true => radix.fuzzy_transposed(&query, max),
+                        false => radix.fuzzy(&query, max),
+                    };
+                    let found: Vec<_> = found.into_iter().map(|(k, v, d)| (k, *v, d)).collect();
+
+                    let want: Vec<_> = expected
+                        .iter()
+                        .map(|(k, v)| (k.clone(), *v, edit_distance(k, &query, transpose)))
+                        .filter(|&(_, _, d)| d <= max)
+                        .collect();
+                    assert_eq!(found, want);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn scored_radix() {
+    let mut radix = ScoredRadix::new();
+    for (word, score) in [
+        ("car", 5),
+        ("card", 9),
+        ("care", 2),
+        ("cart", 9),
+        ("cat", 7),
+        ("dog", 8),
+    ] {
+        radix.insert(word.as_bytes(), word.len(), score);
+    }
+
+    let top = |radix: &ScoredRadix<u8, usize, i32>, prefix: &str, k: usize| -> Vec<String> {
+        radix
+            .top_k(prefix.as_bytes(), k)
+            .into_iter()
+            .map(|(key, _, score)| format!("{}:{}", String::from_utf8(key).unwrap(), score))
+            .collect()
+    };
+
+    assert_eq!(top(&radix, "ca", 3), ["card:9", "cart:9", "cat:7"]);
+    assert_eq!(top(&radix, "c", 10).len(), 5);
+    assert_eq!(top(&radix, "", 1), ["card:9"]);
+    assert_eq!(top(&radix, "dogs", 1), Vec::<String>::new());
+    assert_eq!(top(&radix, "ca", 0), Vec::<String>::new());
+
+    assert_eq!(radix.set_score(b"care", 10), Some(2));
+    assert_eq!(radix.set_score(b"cab", 10), None);
+    assert_eq!(top(&radix, "car", 2), ["care:10", "card:9"]);
+
+    assert_eq!(radix.remove(b"care"), Some((4, 10)));
+    assert_eq!(radix.remove(b"card"), Some((4, 9)));
+    assert_eq!(top(&radix, "car", 2), ["cart:9", "car:5"]);
+    assert_eq!(radix.get(b"cat"), Some((&3, &7)));
+    assert_eq!(radix.len(), 4);
+
+    // random workload against sorting every matching entry
+    let keys = words(31, 400, 0..=15, b'a'..=b'c');
+    let mut radix = ScoredRadix::new();
+    let mut expected = BTreeMap::new();
+    let mut rng = Lcg::new(7);
+
+    for (i, key) in keys.iter().enumerate() {
+        let score = rng.range(0..=63) as u32;
+
+        match i % 4 {
+            3 => assert_eq!(radix.remove(key), expected.remove(key)),
+            2 => assert_eq!(
+                radix.set_score(key, score),
+                expected
+                    .get_mut(key)
+                    .map(|e: &mut (usize, u32)| std::mem::replace(&mut e.1, score))
+            ),
+            _ => assert_eq!(
+                radix.insert(key, i, score),
+                expected.insert(key.clone(), (i, score))
+            ),
+        }
+
+        if i % 20 == 0 {
+            for prefix in [&b""[..], b"a", b"ab", b"ca", b"bcb"] {
+                let mut want: Vec<_> = expected
+                    .iter()
+                    .filter(|(k, _)| k.starts_with(prefix))
+                    .map(|(k, &(v, s))| (k.clone(), v, s))
+                    .collect();
+                want.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| a.0.cmp(&b.0)));
+                want.truncate(10);
+
+                let found: Vec<_> = radix
+                    .top_k(prefix, 10)
+                    .into_iter()
+                    .map(|(k, &v, &s)| (k, v, s))
+                    .collect();
+                assert_eq!(found, want);
+            }
+        }
+    }
+
+    let entries: Vec<_> = radix.iter().map(|(k, &v, &s)| (k, (v, s))).collect();
+    assert_eq!(entries, expected.into_iter().collect::<Vec<_>>());
+}
+
+/// Matches `key` against a pattern of bytes, `?`, `*` and `[..]` classes by
+/// plain backtracking.
+fn glob_naive(pattern: &[u8], key: &[u8]) -> bool {
+    match pattern.first() {
+        None => key.is_empty(),
+        Some(b'*') => (0..=key.len()).any(|i| glob_naive(&pattern[1..], &key[i..])),
+        Some(b'?') => !key.is_empty() && glob_naive(&pattern[1..], &key[1..]),
+        Some(b'[') => {
+            let end = pattern.iter().position(|&b| b == b']').unwrap();
+            let (negated, class) = match pattern[1] {
+                b'!' => (true, &pattern[2..end]),
+                _ => (false, &pattern[1..end]),
+            };
+            !key.is_empty()
+                && class.contains(&key[0]) != negated
+                && glob_naive(&pattern[end + 1..], &key[1..])
+        }
+        Some(&b) => key.first() == Some(&b) && glob_naive(&pattern[1..], &key[1..]),
+    }
+}
+
+#[test]
+fn radix_glob() {
+    let radix: RadixNode<u8, usize> = [
+        "user:1:sessions",
+        "user:1:profile",
+        "user:42:sessions",
+        "user:",
+        "log-2023-01",
+        "log-2024-02",
+        "log-2031-03",
+        "a*b",
+        "a-z",
+    ]
+    .iter()
+    .zip(0..)
+    .map(|(k, v)| (k.as_bytes(), v))
+    .collect();
+
+    let keys = |pattern: &str| -> Vec<String> {
+        radix
+            .matching(pattern)
+            .unwrap()
+            .into_iter()
+            .map(|(k, _)| String::from_utf8(k).unwrap())
+            .collect()
+    };
+
+    assert_eq!(
+        keys("user:*:sessions"),
+        ["user:1:sessions", "user:42:sessions"]
+    );
+    assert_eq!(keys("log-202?-*"), ["log-2023-01", "log-2024-02"]);
+    assert_eq!(keys("log-20[2-3][!4]-*"), ["log-2023-01", "log-2031-03"]);
+    assert_eq!(keys("user:*"), keys("user:**"));
+    assert_eq!(keys("user:*").len(), 4);
+    assert_eq!(keys("a\\*b"), ["a*b"]);
+    assert_eq!(keys("a[-x]z"), ["a-z"]);
+    assert_eq!(keys("a[x-]z"), ["a-z"]);
+    assert_eq!(
+        keys("*"),
+        radix
+            .iter()
+            .map(|(k, _)| String::from_utf8(k).unwrap())
+            .collect::<Vec<_>>()
+    );
+    assert!(keys("nope*").is_empty());
+
+    for pattern in ["[abc", "[z-a]", "abc\\", "[!"] {
+        assert_eq!(radix.matching(pattern), Err(TrieError::InvalidKey));
+    }
+
+    // random patterns against backtracking over every key
+    let pieces = ["a", "b", "c", "?", "*", "[ab]", "[!a]", "[b-c]"];
+    let keys = words(37, 300, 0..=15, b'a'..=b'c');
+    let (radix, expected) = build(&keys, 0);
+
+    for choice in words(3, 300, 0..=7, 0..=7) {
+        let pattern: String = choice.iter().map(|&i| pieces[i as usize]).collect();
+
+        let found: Vec<_> = radix
+            .matching(&pattern)
+            .unwrap()
+            .into_iter()
+            .map(|(k, v)| (k, *v))
+            .collect();
+        let want: Vec<_> = expected
+            .iter()
+            .filter(|(k, _)| glob_naive(pattern.as_bytes(), k))
+            .map(|(k, v)| (k.clone(), *v))
+            .collect();
+        assert_eq!(found, want, "pattern {}", pattern);
+    }
+}
+
+/// Every substring of `docs` with its number of occurrences.
+fn substrings(docs: &[Vec<u8>]) -> BTreeMap<&[u8], usize> {
+    let mut counts = BTreeMap::new();
+    for doc in docs {
+        for i in 0..doc.len() {
+            for j in i + 1..=doc.len() {
+                *counts.entry(&doc[i..j]).or_insert(0) += 1;
+            }
+        }
+    }
+    counts
+}
+
+/// The longest of `subs`, the smallest first among equals.
+fn longest<'a>(subs: impl Iterator<Item = &'a [u8]>) -> Option<Vec<u8>> {
+    let mut best: Option<&[u8]> = None;
+    for sub in subs {
+        if best.map(<[u8]>::len) < Some(sub.len()) {
+            best = Some(sub);
+        }
+    }
+    best.map(<[u8]>::to_vec)
+}
+
+#[test]
+fn suffix_tree() {
+    let tree = SuffixTree::new(b"banana");
+    assert!(tree.contains(b"nan"));
+    assert!(tree.contains(b""));
+    assert!(!tree.contains(b"nab"));
+    assert_eq!(tree.occurrences(b"ana"), [(0, 1), (0, 3)]);
+    assert_eq!(tree.occurrences(b"a").len(), 3);
+    assert_eq!(tree.occurrences(b"").len(), 6);
+    assert_eq!(tree.longest_repeated().unwrap(), b"ana");
+    assert_eq!(tree.longest_common_substring().unwrap(), b"banana");
+
+    let tree = SuffixTree::from_documents(["xabxac", "abcabxabcd", "dxab"].map(str::as_bytes));
+    assert_eq!(tree.documents(), 3);
+    assert_eq!(tree.occurrences(b"xab"), [(0, 0), (1, 5), (2, 1)]);
+    assert_eq!(tree.longest_common_substring().unwrap(), b"xab");
+    assert_eq!(tree.longest_repeated().unwrap(), b"abxa");
+
+    let empty = SuffixTree::<u8>::new(b"");
+    assert_eq!(empty.occurrences(b""), []);
+    assert_eq!(empty.longest_repeated(), None);
+    assert_eq!(empty.longest_common_substring(), None);
+    let disjoint = SuffixTree::from_documents([b"ab", b"cd"]);
+    assert_eq!(disjoint.longest_repeated(), None);
+    assert_eq!(disjoint.longest_common_substring(), None);
+
+    for seed in 0..20 {
+        let n = 1 + seed as usize % 4 * 3;
+        let docs: Vec<Vec<u8>> = words(seed, n, 0..=15, b'a'..=b'c')
+            .chunks(3)
+            .map(|w| w.concat())
+            .collect();
+        let tree = SuffixTree::from_documents(&docs);
+        let counts = substrings(&docs);
+
+        for (sub, &count) in &counts {
+            let mut naive = Vec::new();
+            for (d, doc) in docs.iter().enumerate() {
+                for i in 0..doc.len() {
+                    if doc[i..].starts_with(sub) {
+                        naive.push((d, i));
+                    }
+                }
+            }
+            assert_eq!(tree.occurrences(*sub), naive);
+            assert_eq!(naive.len(), count);
+            assert!(tree.contains(*sub));
+        }
+        for pattern in words(seed + 100, 50, 0..=15, b'a'..=b'c') {
+            assert_eq!(
+                tree.contains(&pattern),
+                pattern.is_empty() || counts.contains_key(&pattern[..])
+            );
+        }
+
+        let repeated = counts.iter().filter(|(_, &n)| n > 1).map(|(s, _)| *s);
+        assert_eq!(tree.longest_repeated(), longest(repeated));
+
+        let common = counts
+            .keys()
+            .filter(|s| docs.iter().all(|d| d.windows(s.len()).any(|w| w == **s)));
+        assert_eq!(tree.longest_common_substring(), longest(common.copied()));
+    }
+}
+
+#[test]
+fn radix_stats() {
+    let radix: RadixNode<u8, ()> = ["romane", "romanus", "romulus", "rubens"]
+        .iter()
+        .map(|k| (k.as_bytes(), ()))
+        .collect();
+
+    let stats = radix.stats();
+    assert_eq!(stats.nodes, 8);
+    assert_eq!(stats.values, 4);
+    assert_eq!(stats.max_depth, 4);
+    assert_eq!(stats.avg_depth, 3.25);
+    assert_eq!(stats.fanout, vec![4, 1, 3]);
+    assert_eq!(stats.key_bytes, 17);
+    assert!(stats.heap_bytes >= 7 * std::mem::size_of::<RadixNode<u8, ()>>() + 17);
+
+    let shape = stats.shape();
+    assert!(shape
+        .starts_with("nodes=8 values=4 depth=4/3.25 fanout=0:4,1:1,2:3 key_bytes=17 heap_bytes="));
+
+    let empty = RadixNode::<u8, ()>::new().stats();
+    assert_eq!((empty.nodes, empty.values, empty.avg_depth), (1, 0, 0.0));
+}
+
+#[test]
+fn radix_render() {
+    let radix: RadixNode<char, u32> = [("rom", 1), ("romane", 2), ("rubens", 3), ("", 0)]
+        .iter()
+        .map(|(k, v)| (k.chars().collect::<Vec<_>>(), *v))
+        .collect();
+
+    let tree = "\
+. = 0
+└── ['r']
+    ├── ['o', 'm'] = 1
+    │   └── ['a', 'n', 'e'] = 2
+    └── ['u', 'b', 'e', 'n', 's'] = 3
+";
+    assert_eq!(radix.to_string(), tree);
+
+    let dot = radix.to_dot();
+    assert!(dot.starts_with("digraph trie {\n"));
+    assert!(dot.ends_with("}\n"));
+    assert!(dot.contains("    n0 [label=\"0\", peripheries=2];\n"));
+    assert!(dot.contains("    n0 -> n1 [label=\"['r']\"];\n"));
+    assert!(dot.contains("    n1 [label=\"\"];\n"));
+    assert!(dot.contains("    n2 -> n4 [label=\"['a', 'n', 'e']\"];\n"));
+    assert_eq!(dot.matches("peripheries=2").count(), 4);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn radix_serde() {
+    use serde_test::{assert_tokens, Token};
+
+    let radix: RadixNode<u8, u32> = [(&b"ab"[..], 1), (b"", 2), (b"a", 3)].into_iter().collect();
+
+    assert_tokens(
+        &radix,
+        &[
+            Token::Seq { len: Some(3) },
+            Token::Tuple { len: 2 },
+            Token::Seq { len: Some(0) },
+            Token::SeqEnd,
+            Token::U32(2),
+            Token::TupleEnd,
+            Token::Tuple { len: 2 },
+            Token::Seq { len: Some(1) },
+            Token::U8(b'a'),
+            Token::SeqEnd,
+            Token::U32(3),
+            Token::TupleEnd,
+            Token::Tuple { len: 2 },
+            Token::Seq { len: Some(2) },
+            Token::U8(b'a'),
+            Token::U8(b'b'),
+            Token::SeqEnd,
+            Token::U32(1),
+            Token::TupleEnd,
+            Token::SeqEnd,
+        ],
+    );
+
+    let json = serde_json::to_string(&radix).unwrap();
+    assert_eq!(json, "[[[],2],[[97],3],[[97,98],1]]");
+    let decoded: RadixNode<u8, u32> = serde_json::from_str(&json).unwrap();
+    assert_eq!(decoded, radix);
+    assert!(same_shape(&decoded, &radix));
+}
+
+#[test]
+fn radix_binary_format() {
+    use crate::codec::{FormatError, LeCodec, StrCodec};
+
+    let keys = words(6, 200, 0..=15, b'a'..=b'c');
+    let radix: RadixNode<u8, u32> = keys
+        .iter()
+        .enumerate()
+        .map(|(i, k)| (k, i as u32))
+        .collect();
+
+    let mut bytes = Vec::new();
+    radix.write_to(&mut bytes, &LeCodec).unwrap();
+    assert_eq!(&bytes[0..4], b"TRIX");
+
+    let decoded = RadixNode::read_from(&bytes[..], &LeCodec).unwrap();
+    assert_eq!(decoded, radix);
+    assert!(same_shape(&decoded, &radix));
+
+    let strings: RadixNode<u8, String> = [(&b""[..], "root"), (b"key", "value")]
+        .into_iter()
+        .map(|(k, v)| (k, v.to_string()))
+        .collect();
+    let mut buf = Vec::new();
+    strings.write_to(&mut buf, &StrCodec).unwrap();
+    assert_eq!(RadixNode::read_from(&buf[..], &StrCodec).unwrap(), strings);
+
+    let read = |bytes: &[u8]| RadixNode::<u8, u32>::read_from(bytes, &LeCodec);
+
+    assert!(matches!(read(&bytes[..10]), Err(FormatError::Truncated)));
+    assert!(matches!(
+        read(&bytes[..bytes.len() - 1]),
+        Err(FormatError::Truncated)
+    ));
+    assert!(matches!(
+        read(b"not a trie at all..."),
+        Err(FormatError::BadMagic)
+    ));
+
+    let mut bad = bytes.clone();
+    bad[4] = 9;
+    assert!(matches!(
+        read(&bad),
+        Err(FormatError::UnsupportedVersion(9))
+    ));
+
+    let mut bad = bytes.clone();
+    let last = bad.len() - 1;
+    bad[last] ^= 0xff;
+    assert!(matches!(
+        read(&bad),
+        Err(FormatError::ChecksumMismatch { .. })
+    ));
+
+    // corrupt payloads with a valid checksum must fail cleanly, never panic
+    let mut rng = Lcg::new(7);
+    for _ in 0..2000 {
+        let mut bad = bytes.clone();
+        let pos = rng.range(20..=bad.len() as u64 - 1) as usize;
+        bad[pos] = rng.next() as u8;
+        let crc = crate::codec::crc32(&bad[20..]);
+        bad[16..20].copy_from_slice(&crc.to_le_bytes());
+        if let Ok(radix) = read(&bad) {
+            assert!(radix.iter().count() <= keys.len());
+        }
+    }
+
+    // a value-less node below the root must branch, or it would have been
+    // merged into its child or pruned
+    let with_payload = |payload: &[u8]| {
+        let mut bytes = bytes[..8].to_vec();
+        bytes.extend_from_slice(&(payload.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(&crate::codec::crc32(payload).to_le_bytes());
+        bytes.extend_from_slice(payload);
+        bytes
+    };
+    let valid = with_payload(&[0, 0, 1, 2, b'a', b'b', 1, 4, 1, 0, 0, 0, 0]);
+    assert_eq!(read(&valid).unwrap().get(b"ab"), Some(&1));
+    for payload in [
+        &[0, 0, 1, 1, b'a', 0, 1, 1, b'b', 1, 4, 1, 0, 0, 0, 0][..],
+        &[0, 0, 1, 1, b'a', 0, 0],
+    ] {
+        assert!(matches!(
+            read(&with_payload(payload)),
+            Err(FormatError::Corrupt(
+                "value-less node with fewer than two children"
+            ))
+        ));
+    }
+}
+
+#[test]
+fn radix_view() {
+    use crate::codec::{FormatError, LeCodec};
+    use crate::radix::RadixView;
+
+    let keys = words(8, 300, 0..=15, b'a'..=b'c');
+    let mut radix: RadixNode<u8, u32> = keys
+        .iter()
+        .enumerate()
+        .map(|(i, k)| (k, i as u32))
+        .collect();
+    radix.insert(b"", 7).unwrap();
+
+    let mut bytes = Vec::new();
+    radix.write_view(&mut bytes, &LeCodec).unwrap();
+    let view = RadixView::new(&bytes).unwrap();
+    let decode = |v: &[u8]| u32::from_le_bytes(v.try_into().unwrap());
+
+    assert_eq!(view.len(), radix.len());
+    let all: Vec<_> = view.iter().map(|(k, v)| (k, decode(v))).collect();
+    let expected: Vec<_> = radix.iter().map(|(k, v)| (k, *v)).collect();
+    assert_eq!(all, expected);
+
+    for key in keys
+        .iter()
+        .chain([b"zz".to_vec(), b"abcabcabc".to_vec()].iter())
+    {
+        assert_eq!(view.get(key).map(decode), radix.get(key).copied());
+
+        let longest = (0..=key.len())
+            .rev()
+            .find_map(|n| radix.get(&&key[..n]).map(|v| (n, *v)));
+        assert_eq!(
+            view.longest_prefix(key).map(|(n, v)| (n, decode(v))),
+            longest
+        );
+
+        for n in 0..=key.len() {
+            let prefix = &key[..n];
+            let found: Vec<_> = view.iter_prefix(prefix).map(|(k, _)| k).collect();
+            let expected: Vec<_> = radix
+                .iter()
+                .map(|(k, _)| k)
+                .filter(|k| k.starts_with(prefix))
+                .collect();
+            assert_eq!(found, expected);
+        }
+    }
+
+    assert!(matches!(
+        RadixView::new(&bytes[..10]),
+        Err(FormatError::Truncated)
+    ));
+    assert!(matches!(
+        RadixView::new(&bytes[..40]),
+        Err(FormatError::Truncated)
+    ));
+
+    // every single-byte corruption is either rejected or still safe to read
+    for pos in 0..bytes.len() {
+        let mut bad = bytes.clone();
+        bad[pos] ^= 0x5a;
+        if let Ok(view) = RadixView::new(&bad) {
+            for key in &keys {
+                view.get(key);
+                view.longest_prefix(key);
+            }
+            assert!(view.iter().count() <= radix.len());
+        }
+    }
+}
@@ -0,0 +1,404 @@
+use super::RadixNode;
+use crate::codec::{FormatError, ValueCodec};
+use std::io::Write;
+
+const MAGIC: [u8; 4] = *b"TRVW";
+const VERSION: u16 = 1;
+const HEADER_LEN: usize = 16;
+const RECORD_LEN: usize = 24;
+const NO_VALUE: u32 = u32::MAX;
+
+/// Read-only radix trie over bytes in the layout written by
+/// [`RadixNode::write_view`], answering lookups without copying or
+/// deserializing, e.g. straight from a memory-mapped file.
+///
+/// The layout is a header, a fixed-size record per node and a blob holding
+/// edge and value bytes. All integers are little-endian `u32`s, except the
+/// version and flags which are `u16`s:
+///
+/// | bytes  | header field                         |
+/// |--------|--------------------------------------|
+/// | 0..4   | magic `TRVW`                         |
+/// | 4..6   | version, currently 1                 |
+/// | 6..8   | flags, currently 0                   |
+/// | 8..12  | node count                           |
+/// | 12..16 | value count                          |
+///
+/// Node records follow the header in breadth-first order, the root first, so
+/// the children of a node are contiguous and sorted by key:
+///
+/// | bytes  | record field                                  |
+/// |--------|-----------------------------------------------|
+/// | 0..4   | edge offset into the buffer                   |
+/// | 4..8   | edge length                                   |
+/// | 8..12  | value offset, or `u32::MAX` for no value      |
+/// | 12..16 | value length                                  |
+/// | 16..20 | index of the first child                      |
+/// | 20..24 | child count                                   |
+///
+/// The whole layout is validated once by [`RadixView::new`], so lookups
+/// never fail on malformed input.
+#[derive(Debug, Clone, Copy)]
+pub struct RadixView<'a> {
+    bytes: &'a [u8],
+    nodes: usize,
+    values: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Record {
+    edge: (usize, usize),
+    value: Option<(usize, usize)>,
+    first_child: usize,
+    children: usize,
+}
+
+impl<'a> RadixView<'a> {
+    pub fn new(bytes: &'a [u8]) -> Result<Self, FormatError> {
+        if bytes.len() < HEADER_LEN {
+            return Err(FormatError::Truncated);
+        }
+        if bytes[0..4] != MAGIC {
+            return Err(FormatError::BadMagic);
+        }
+
+        let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+        if version != VERSION {
+            return Err(FormatError::UnsupportedVersion(version));
+        }
+        if bytes[6..8] != [0, 0] {
+            return Err(FormatError::Corrupt("unknown header flags"));
+        }
+
+        let nodes = read_u32(bytes, 8) as usize;
+        let values = read_u32(bytes, 12) as usize;
+
+        let table = nodes
+            .checked_mul(RECORD_LEN)
+            .and_then(|n| n.checked_add(HEADER_LEN))
+            .ok_or(FormatError::Corrupt("node count too large"))?;
+        if nodes == 0 {
+            return Err(FormatError::Corrupt("missing root node"));
+        }
+        if table > bytes.len() {
+            return Err(FormatError::Truncated);
+        }
+
+        let view = Self {
+            bytes,
+            nodes,
+            values,
+        };
+        view.validate()?;
+        Ok(view)
+    }
+
+    /// Returns the encoded value stored for `key`.
+    pub fn get(&self, key: &[u8]) -> Option<&'a [u8]> {
+        let mut node = self.record(0);
+        let mut rem = key;
+
+        while !rem.is_empty() {
+            let idx = self.child(&node, rem[0])?;
+            node = self.record(idx);
+
+            let edge = self.slice(node.edge);
+            rem = rem.strip_prefix(edge)?;
+        }
+
+        node.value.map(|v| self.slice(v))
+    }
+
+    /// Finds the longest stored key that is a prefix of `key`, returning its
+    /// length and encoded value.
+    pub fn longest_prefix(&self, key: &[u8]) -> Option<(usize, &'a [u8])> {
+        let mut node = self.record(0);
+        let mut rem = key;
+        let mut best = node.value.map(|v| (0, self.slice(v)));
+
+        while !rem.is_empty() {
+            let idx = match self.child(&node, rem[0]) {
+                Some(idx) => idx,
+                None => break,
+            };
+            node = self.record(idx);
+
+            rem = match rem.strip_prefix(self.slice(node.edge)) {
+                Some(rem) => rem,
+                None => break,
+            };
+
+            if let Some(v) = node.value {
+                best = Some((key.len() - rem.len(), self.slice(v)));
+            }
+        }
+
+        best
+    }
+
+    /// Iterates over every key starting with `prefix` and its encoded value,
+    /// in key order.
+    pub fn iter_prefix(&self, prefix: &[u8]) -> ViewIter<'a> {
+        let mut iter = ViewIter {
+            view: *self,
+            key: Vec::new(),
+            stack: Vec::new(),
+        };
+
+        let mut idx = 0;
+        // length of the key leading up to the edge of `idx`
+        let mut base = 0;
+        let mut rem = prefix;
+
+        while !rem.is_empty() {
+            idx = match self.child(&self.record(idx), rem[0]) {
+                Some(idx) => idx,
+                None => return iter,
+            };
+
+            let edge = self.slice(self.record(idx).edge);
+            base = iter.key.len();
+
+            if let Some(r) = rem.strip_prefix(edge) {
+                iter.key.extend_from_slice(edge);
+                rem = r;
+                continue;
+            }
+
+            // a prefix ending inside the edge matches the whole subtree
+            match edge.starts_with(rem) {
+                true => break,
+                false => return iter,
+            }
+        }
+
+        iter.key.truncate(base);
+        iter.stack.push((idx, base));
+        iter
+    }
+
+    pub fn iter(&self) -> ViewIter<'a> {
+        self.iter_prefix(&[])
+    }
+
+    pub fn len(&self) -> usize {
+        self.values
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values == 0
+    }
+
+    fn validate(&self) -> Result<(), FormatError> {
+        let mut values = 0;
+        // breadth-first order hands out child ranges one after another
+        let mut next = 1;
+
+        for idx in 0..self.nodes {
+            let node = self.try_record(idx)?;
+
+            if idx != 0 && node.edge.1 == 0 {
+                return Err(FormatError::Corrupt("empty edge below the root"));
+            }
+
+            if node.value.is_some() {
+                values += 1;
+            }
+
+            if node.children == 0 {
+                continue;
+            }
+
+            if node.first_child != next || node.first_child <= idx {
+                return Err(FormatError::Corrupt("nodes not in breadth-first order"));
+            }
+
+            next = match next.checked_add(node.children) {
+                Some(end) if end <= self.nodes => end,
+                _ => return Err(FormatError::Corrupt("child index out of range")),
+            };
+
+            let mut prev = None;
+            for child in node.first_child..next {
+                let edge = self.try_record(child)?.edge;
+                if edge.1 == 0 {
+                    return Err(FormatError::Corrupt("empty edge below the root"));
+                }
+                let first = self.bytes[edge.0];
+                if prev.is_some_and(|p| p >= first) {
+                    return Err(FormatError::Corrupt("children out of order"));
+                }
+                prev = Some(first);
+            }
+        }
+
+        if next != self.nodes {
+            return Err(FormatError::Corrupt("unreachable nodes"));
+        }
+
+        match values == self.values {
+            true => Ok(()),
+            false => Err(FormatError::Corrupt("value count does not match header")),
+        }
+    }
+
+    fn try_record(&self, idx: usize) -> Result<Record, FormatError> {
+        let node = self.record(idx);
+
+        let in_bounds = |(offset, len): (usize, usize)| {
+            offset
+                .checked_add(len)
+                .is_some_and(|end| end <= self.bytes.len())
+        };
+
+        if !in_bounds(node.edge) || node.value.is_some_and(|value| !in_bounds(value)) {
+            return Err(FormatError::Corrupt(
+                "range runs past the end of the buffer",
+            ));
+        }
+
+        Ok(node)
+    }
+
+    fn record(&self, idx: usize) -> Record {
+        let base = HEADER_LEN + idx * RECORD_LEN;
+        let field = |n: usize| read_u32(self.bytes, base + n * 4) as usize;
+
+        let value = match read_u32(self.bytes, base + 8) {
+            NO_VALUE => None,
+            offset => Some((offset as usize, field(3))),
+        };
+
+        Record {
+            edge: (field(0), field(1)),
+            value,
+            first_child: field(4),
+            children: field(5),
+        }
+    }
+
+    fn child(&self, node: &Record, first: u8) -> Option<usize> {
+        let children = node.first_child..node.first_child + node.children;
+        let mut lo = children.start;
+        let mut hi = children.end;
+
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            let edge = self.record(mid).edge;
+            match self.bytes[edge.0].cmp(&first) {
+                std::cmp::Ordering::Equal => return Some(mid),
+                std::cmp::Ordering::Less => lo = mid + 1,
+                std::cmp::Ordering::Greater => hi = mid,
+            }
+        }
+
+        None
+    }
+
+    fn slice(&self, (offset, len): (usize, usize)) -> &'a [u8] {
+        &self.bytes[offset..offset + len]
+    }
+}
+
+/// Iterator over the keys and encoded values of a [`RadixView`].
+pub struct ViewIter<'a> {
+    view: RadixView<'a>,
+    key: Vec<u8>,
+    stack: Vec<(usize, usize)>,
+}
+
+impl<'a> Iterator for ViewIter<'a> {
+    type Item = (Vec<u8>, &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (idx, len) = self.stack.pop()?;
+            let node = self.view.record(idx);
+
+            self.key.truncate(len);
+            if idx != 0 {
+                self.key.extend_from_slice(self.view.slice(node.edge));
+            }
+
+            let len = self.key.len();
+            let children = node.first_child..node.first_child + node.children;
+            self.stack.extend(children.rev().map(|child| (child, len)));
+
+            if let Some(value) = node.value {
+                return Some((self.key.clone(), self.view.slice(value)));
+            }
+        }
+    }
+}
+
+impl<V> RadixNode<u8, V> {
+    /// Writes the trie in the zero-copy layout read by [`RadixView`],
+    /// encoding each value with `codec`.
+    pub fn write_view<W, C>(&self, mut writer: W, codec: &C) -> Result<(), FormatError>
+    where
+        W: Write,
+        C: ValueCodec<V>,
+    {
+        let mut order = vec![self];
+        let mut first_child = Vec::new();
+
+        let mut i = 0;
+        while i < order.len() {
+            first_child.push(order.len());
+            order.extend(order[i].children.iter());
+            i += 1;
+        }
+
+        let table = HEADER_LEN + order.len() * RECORD_LEN;
+        let mut records = Vec::with_capacity(table);
+        let mut blob = Vec::new();
+        let mut values = 0u32;
+
+        let offset = |blob: &Vec<u8>| -> Result<u32, FormatError> {
+            u32::try_from(table + blob.len()).map_err(|_| FormatError::TooLarge)
+        };
+
+        for (node, first) in order.iter().zip(first_child) {
+            let edge = offset(&blob)?;
+            blob.extend_from_slice(&node.key);
+
+            let value = match &node.value {
+                Some(value) => {
+                    let start = offset(&blob)?;
+                    let len = blob.len();
+                    codec.encode(value, &mut blob);
+                    values += 1;
+                    (start, (blob.len() - len) as u32)
+                }
+                None => (NO_VALUE, 0),
+            };
+
+            for field in [
+                edge,
+                node.key.len() as u32,
+                value.0,
+                value.1,
+                first as u32,
+                node.children.len() as u32,
+            ] {
+                records.extend_from_slice(&field.to_le_bytes());
+            }
+        }
+        offset(&blob)?;
+
+        let nodes = u32::try_from(order.len()).map_err(|_| FormatError::TooLarge)?;
+
+        writer.write_all(&MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        writer.write_all(&0u16.to_le_bytes())?;
+        writer.write_all(&nodes.to_le_bytes())?;
+        writer.write_all(&values.to_le_bytes())?;
+        writer.write_all(&records)?;
+        writer.write_all(&blob)?;
+        Ok(())
+    }
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+}
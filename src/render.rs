@@ -0,0 +1,130 @@
+use std::fmt::{self, Write};
+
+/// Builds a Graphviz digraph one node and edge at a time.
+pub(crate) struct Dot {
+    out: String,
+}
+
+impl Dot {
+    /// Starts a graph whose nodes are drawn with `shape` unless overridden.
+    pub(crate) fn new(shape: &str) -> Self {
+        let mut out = String::from("digraph trie {\n");
+        let _ = writeln!(out, "    node [shape={}, label=\"\"];", shape);
+        let _ = writeln!(out, "    edge [fontname=monospace];");
+        Self { out }
+    }
+
+    /// Adds node `id`; nodes holding a value get a double outline.
+    pub(crate) fn node(&mut self, id: usize, label: &str, value: bool) {
+        let peripheries = if value { ", peripheries=2" } else { "" };
+        let _ = writeln!(
+            self.out,
+            "    n{} [label=\"{}\"{}];",
+            id,
+            escape(label),
+            peripheries
+        );
+    }
+
+    /// Adds node `id` drawn as a record with one row per field; the graph
+    /// must have been started with `shape=record`.
+    pub(crate) fn record(&mut self, id: usize, fields: &[String], value: bool) {
+        let fields: Vec<_> = fields.iter().map(|field| escape(field)).collect();
+        let peripheries = if value { ", peripheries=2" } else { "" };
+        let _ = writeln!(
+            self.out,
+            "    n{} [label=\"{{{}}}\"{}];",
+            id,
+            fields.join(" | "),
+            peripheries
+        );
+    }
+
+    pub(crate) fn edge(&mut self, from: usize, to: usize, label: &str) {
+        let _ = writeln!(
+            self.out,
+            "    n{} -> n{} [label=\"{}\"];",
+            from,
+            to,
+            escape(label)
+        );
+    }
+
+    pub(crate) fn finish(mut self) -> String {
+        self.out.push_str("}\n");
+        self.out
+    }
+}
+
+/// Escapes a label for use inside a quoted DOT string. Record delimiters
+/// are escaped too, so labels are safe in `shape=record` nodes.
+fn escape(label: &str) -> String {
+    let mut out = String::with_capacity(label.len());
+    for c in label.chars() {
+        match c {
+            '"' | '\\' | '{' | '}' | '|' | '<' | '>' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '\n' => out.push_str("\\n"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Draws a tree in the style of `tree(1)`: one node per line, with branches
+/// showing each node's children below it.
+pub(crate) fn write_tree<N>(
+    f: &mut fmt::Formatter<'_>,
+    root: N,
+    label: &dyn Fn(N) -> String,
+    children: &dyn Fn(N) -> Vec<N>,
+) -> fmt::Result
+where
+    N: Copy,
+{
+    writeln!(f, "{}", label(root))?;
+    write_children(f, root, &mut String::new(), label, children)
+}
+
+fn write_children<N>(
+    f: &mut fmt::Formatter<'_>,
+    node: N,
+    prefix: &mut String,
+    label: &dyn Fn(N) -> String,
+    children: &dyn Fn(N) -> Vec<N>,
+) -> fmt::Result
+where
+    N: Copy,
+{
+    let nodes = children(node);
+
+    for (i, &child) in nodes.iter().enumerate() {
+        let last = i + 1 == nodes.len();
+        let (branch, indent) = match last {
+            true => ("└── ", "    "),
+            false => ("├── ", "│   "),
+        };
+
+        writeln!(f, "{}{}{}", prefix, branch, label(child))?;
+
+        let len = prefix.len();
+        prefix.push_str(indent);
+        write_children(f, child, prefix, label, children)?;
+        prefix.truncate(len);
+    }
+
+    Ok(())
+}
+
+/// Labels a tree line with the node's key, followed by its value if any.
+pub(crate) fn tree_label<V>(key: &str, value: Option<&V>) -> String
+where
+    V: fmt::Debug,
+{
+    match value {
+        Some(value) => format!("{} = {:?}", key, value),
+        None => key.to_string(),
+    }
+}
@@ -0,0 +1,91 @@
+use std::fmt::Write;
+
+/// Structural statistics of a trie, for sizing caches and spotting
+/// pathological inputs such as very deep or very wide nodes.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TrieStats {
+    /// Nodes in the tree, including the root.
+    pub nodes: usize,
+    /// Nodes holding a value.
+    pub values: usize,
+    /// Edges between the root and the deepest node.
+    pub max_depth: usize,
+    /// Mean depth of the nodes holding a value, zero for an empty trie.
+    pub avg_depth: f64,
+    /// `fanout[n]` is the number of nodes with exactly `n` children.
+    pub fanout: Vec<usize>,
+    /// Bytes of key material stored on the edges below the root.
+    pub key_bytes: usize,
+    /// Estimated bytes allocated by the trie itself, not counting heap memory
+    /// owned by keys or values.
+    pub heap_bytes: usize,
+}
+
+impl TrieStats {
+    /// Summarizes the statistics on one line, such as
+    /// `nodes=8 values=4 depth=4/3.25 fanout=0:4,1:1,2:3 key_bytes=17 heap_bytes=918`.
+    /// Depth is given as the maximum and the mean over values, and fan-out
+    /// lists `children:nodes` for each child count that occurs.
+    pub fn shape(&self) -> String {
+        let mut out = format!(
+            "nodes={} values={} depth={}/{:.2} fanout=",
+            self.nodes, self.values, self.max_depth, self.avg_depth
+        );
+
+        let buckets = self.fanout.iter().enumerate().filter(|(_, &n)| n > 0);
+        for (i, (children, count)) in buckets.enumerate() {
+            let sep = if i == 0 { "" } else { "," };
+            let _ = write!(out, "{}{}:{}", sep, children, count);
+        }
+
+        let _ = write!(
+            out,
+            " key_bytes={} heap_bytes={}",
+            self.key_bytes, self.heap_bytes
+        );
+        out
+    }
+}
+
+/// Accumulates [`TrieStats`] one node at a time.
+#[derive(Default)]
+pub(crate) struct Collector {
+    stats: TrieStats,
+    depth_sum: usize,
+}
+
+impl Collector {
+    pub(crate) fn visit(
+        &mut self,
+        depth: usize,
+        children: usize,
+        value: bool,
+        key_bytes: usize,
+        heap_bytes: usize,
+    ) {
+        let stats = &mut self.stats;
+
+        stats.nodes += 1;
+        stats.max_depth = stats.max_depth.max(depth);
+        stats.key_bytes += key_bytes;
+        stats.heap_bytes += heap_bytes;
+
+        if value {
+            stats.values += 1;
+            self.depth_sum += depth;
+        }
+
+        if stats.fanout.len() <= children {
+            stats.fanout.resize(children + 1, 0);
+        }
+        stats.fanout[children] += 1;
+    }
+
+    pub(crate) fn finish(mut self, heap_bytes: usize) -> TrieStats {
+        self.stats.heap_bytes += heap_bytes;
+        if self.stats.values > 0 {
+            self.stats.avg_depth = self.depth_sum as f64 / self.stats.values as f64;
+        }
+        self.stats
+    }
+}
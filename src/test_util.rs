@@ -0,0 +1,45 @@
+//! Seeded random data shared by the test modules, so every randomized test
+//! is reproducible.
+
+use std::ops::RangeInclusive;
+
+/// A 64-bit linear congruential generator. Its low bits repeat with short
+/// periods, so every draw is taken from the high half of the state.
+pub(crate) struct Lcg(u64);
+
+impl Lcg {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    /// Returns 32 random bits.
+    pub(crate) fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1);
+        self.0 >> 32
+    }
+
+    /// Returns a value in `range`.
+    pub(crate) fn range(&mut self, range: RangeInclusive<u64>) -> u64 {
+        range.start() + self.next() % (range.end() - range.start() + 1)
+    }
+}
+
+/// Returns `n` random words with lengths in `len` and elements in `alphabet`.
+/// A small alphabet makes words share prefixes and repeat.
+pub(crate) fn words(
+    seed: u64,
+    n: usize,
+    len: RangeInclusive<usize>,
+    alphabet: RangeInclusive<u8>,
+) -> Vec<Vec<u8>> {
+    let mut rng = Lcg::new(seed);
+    let (start, end) = (*alphabet.start() as u64, *alphabet.end() as u64);
+    let (min, max) = (*len.start() as u64, *len.end() as u64);
+
+    (0..n)
+        .map(|_| {
+            let len = rng.range(min..=max);
+            (0..len).map(|_| rng.range(start..=end) as u8).collect()
+        })
+        .collect()
+}
@@ -0,0 +1,448 @@
+use super::TrieNode;
+use crate::memory::{slot_size, MemoryUsage};
+use crate::{TrieError, TrieExt};
+use slab::Slab;
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::mem;
+use std::ops::Index;
+
+/// Marks the end of a child or sibling list.
+const NIL: u32 = u32::MAX;
+
+/// Largest number of nodes addressable by a `u32` index other than [`NIL`].
+const MAX_NODES: usize = u32::MAX as usize;
+
+/// A trie like [`TrieNode`] whose nodes live in one slab and refer to each
+/// other by `u32` index.
+///
+/// Each node links to its first child and its next sibling, with siblings
+/// kept in key order, so adding a node never allocates more than a slab slot.
+/// Slots freed by `remove` are reused by later inserts.
+#[derive(Debug, Clone)]
+pub struct ArenaTrie<K, V> {
+    nodes: Slab<Node<K, V>>,
+    len: usize,
+    max_nodes: usize,
+}
+
+#[derive(Debug, Clone)]
+struct Node<K, V> {
+    key: K,
+    value: Option<V>,
+    child: u32,
+    next: u32,
+}
+
+impl<K, V> Node<K, V> {
+    fn new(key: K, value: Option<V>) -> Self {
+        Self {
+            key,
+            value,
+            child: NIL,
+            next: NIL,
+        }
+    }
+}
+
+impl<K, V> Default for ArenaTrie<K, V>
+where
+    K: Clone + Ord + Default,
+{
+    fn default() -> Self {
+        Self::new(K::default())
+    }
+}
+
+impl<K, V> ArenaTrie<K, V>
+where
+    K: Clone + Ord,
+{
+    pub fn new<T>(key: T) -> Self
+    where
+        T: Borrow<K>,
+    {
+        Self::with_max_nodes(key, MAX_NODES)
+    }
+
+    /// Creates a trie that refuses to grow past `max_nodes` nodes, the root
+    /// included; `insert` returns [`TrieError::CapacityExceeded`] instead.
+    pub fn with_max_nodes<T>(key: T, max_nodes: usize) -> Self
+    where
+        T: Borrow<K>,
+    {
+        let mut nodes = Slab::new();
+        nodes.insert(Node::new(key.borrow().clone(), None));
+
+        Self {
+            nodes,
+            len: 0,
+            max_nodes: max_nodes.min(MAX_NODES),
+        }
+    }
+
+    pub fn key(&self) -> &K {
+        &self.nodes[0].key
+    }
+
+    /// Iterates over every stored key and value in key order, starting with
+    /// the empty key if it holds a value.
+    pub fn iter(&self) -> ArenaIter<'_, K, V> {
+        ArenaIter {
+            trie: self,
+            key: Vec::new(),
+            root: self.nodes[0].value.as_ref(),
+            stack: vec![(self.nodes[0].child, 0)],
+        }
+    }
+
+    /// Compacts the arena so that nodes occupy consecutive slots and no freed
+    /// slots remain, then releases the unused capacity.
+    pub fn shrink_to_fit(&mut self) {
+        let mut nodes = Slab::with_capacity(self.nodes.len());
+        move_tree(&mut self.nodes, 0, &mut nodes);
+        self.nodes = nodes;
+    }
+
+    pub fn memory_usage(&self) -> MemoryUsage {
+        let nodes = self.nodes.len();
+
+        MemoryUsage {
+            nodes,
+            capacity: self.nodes.capacity(),
+            arena_bytes: self.nodes.capacity() * slot_size::<Node<K, V>>(),
+            // every node but the root sits in its parent's `children` vector
+            boxed_bytes: (nodes - 1) * mem::size_of::<TrieNode<K, V>>(),
+        }
+    }
+
+    /// Looks for the child of `parent` holding `key`, returning it with the
+    /// sibling before it. On a miss, returns the sibling after which a node
+    /// for `key` belongs. [`NIL`] stands for "no previous sibling".
+    fn find(&self, parent: u32, key: &K) -> Result<(u32, u32), u32> {
+        let mut prev = NIL;
+        let mut cur = self.nodes[parent as usize].child;
+
+        while cur != NIL {
+            let node = &self.nodes[cur as usize];
+            match node.key.cmp(key) {
+                Ordering::Less => {
+                    prev = cur;
+                    cur = node.next;
+                }
+                Ordering::Equal => return Ok((prev, cur)),
+                Ordering::Greater => break,
+            }
+        }
+
+        Err(prev)
+    }
+
+    /// Returns the index of the node at `key`.
+    fn locate(&self, key: &[K]) -> Option<u32> {
+        key.iter()
+            .try_fold(0, |node, k| self.find(node, k).ok().map(|(_, idx)| idx))
+    }
+
+    fn link(&mut self, parent: u32, prev: u32, idx: u32) {
+        let next = match prev {
+            NIL => mem::replace(&mut self.nodes[parent as usize].child, idx),
+            prev => mem::replace(&mut self.nodes[prev as usize].next, idx),
+        };
+        self.nodes[idx as usize].next = next;
+    }
+
+    fn unlink(&mut self, parent: u32, prev: u32, idx: u32) {
+        let next = self.nodes[idx as usize].next;
+        match prev {
+            NIL => self.nodes[parent as usize].child = next,
+            prev => self.nodes[prev as usize].next = next,
+        }
+    }
+
+    /// Moves the subtree rooted at `idx`, which must already be unlinked,
+    /// into a trie of its own.
+    fn split_tree(&mut self, idx: u32) -> Self {
+        let mut nodes = Slab::new();
+        let len = move_tree(&mut self.nodes, idx, &mut nodes);
+        self.len -= len;
+
+        Self {
+            nodes,
+            len,
+            max_nodes: self.max_nodes,
+        }
+    }
+}
+
+/// Moves the subtree rooted at `root` from one slab into another, where it
+/// is placed starting at the next free slot. Returns the number of values
+/// moved.
+fn move_tree<K, V>(from: &mut Slab<Node<K, V>>, root: u32, into: &mut Slab<Node<K, V>>) -> usize {
+    let mut node = from.remove(root as usize);
+    let mut values = usize::from(node.value.is_some());
+
+    let first = mem::replace(&mut node.child, NIL);
+    node.next = NIL;
+    let new_root = into.insert(node) as u32;
+
+    // each entry is a sibling list still in `from` and its new parent
+    let mut stack = vec![(first, new_root)];
+
+    while let Some((mut cur, parent)) = stack.pop() {
+        let mut prev = NIL;
+
+        while cur != NIL {
+            let mut node = from.remove(cur as usize);
+            values += usize::from(node.value.is_some());
+
+            let child = mem::replace(&mut node.child, NIL);
+            cur = mem::replace(&mut node.next, NIL);
+
+            let idx = into.insert(node) as u32;
+            match prev {
+                NIL => into[parent as usize].child = idx,
+                prev => into[prev as usize].next = idx,
+            }
+
+            prev = idx;
+            stack.push((child, idx));
+        }
+    }
+
+    values
+}
+
+impl<K, V> TrieExt<K, V> for ArenaTrie<K, V>
+where
+    K: Clone + Ord,
+{
+    fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        Q: Borrow<[K]>,
+    {
+        let idx = self.locate(key.borrow())?;
+        self.nodes[idx as usize].value.as_ref()
+    }
+
+    fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        Q: Borrow<[K]>,
+    {
+        let idx = self.locate(key.borrow())?;
+        self.nodes[idx as usize].value.as_mut()
+    }
+
+    fn insert<Q>(&mut self, key: &Q, value: V) -> Result<Option<V>, TrieError>
+    where
+        Q: Borrow<[K]> + ?Sized,
+    {
+        let key = key.borrow();
+        let mut node = 0;
+
+        for (depth, k) in key.iter().enumerate() {
+            let prev = match self.find(node, k) {
+                Ok((_, child)) => {
+                    node = child;
+                    continue;
+                }
+                Err(prev) => prev,
+            };
+
+            let rest = &key[depth..];
+            if self.nodes.len() + rest.len() > self.max_nodes {
+                return Err(TrieError::CapacityExceeded);
+            }
+
+            // build the missing chain from the bottom up, then hook it in
+            let mut value = Some(value);
+            let mut below = NIL;
+            for k in rest.iter().rev() {
+                let mut chain = Node::new(k.clone(), value.take());
+                chain.child = below;
+                below = self.nodes.insert(chain) as u32;
+            }
+
+            self.link(node, prev, below);
+            self.len += 1;
+            return Ok(None);
+        }
+
+        let old = self.nodes[node as usize].value.replace(value);
+        if old.is_none() {
+            self.len += 1;
+        }
+        Ok(old)
+    }
+
+    fn remove<Q>(&mut self, key: &Q, prune: bool) -> Option<Self>
+    where
+        Q: Borrow<[K]>,
+    {
+        let key = key.borrow();
+
+        // the empty key lives on the root, which is never detached
+        if key.is_empty() {
+            let root = self.key().clone();
+            if prune {
+                let empty = Self::with_max_nodes(&root, self.max_nodes);
+                return match self.is_empty() {
+                    true => None,
+                    false => Some(mem::replace(self, empty)),
+                };
+            }
+
+            let value = self.nodes[0].value.take()?;
+            self.len -= 1;
+
+            let mut removed = Self::with_max_nodes(root, self.max_nodes);
+            removed.nodes[0].value = Some(value);
+            removed.len = 1;
+            return Some(removed);
+        }
+
+        // (parent, previous sibling, node) for each element of the key
+        let mut path = Vec::with_capacity(key.len());
+        let mut node = 0;
+
+        for k in key {
+            let (prev, idx) = self.find(node, k).ok()?;
+            path.push((node, prev, idx));
+            node = idx;
+        }
+
+        let removed = match prune {
+            true => {
+                let (parent, prev, idx) = path.pop()?;
+                self.unlink(parent, prev, idx);
+                self.split_tree(idx)
+            }
+            false => {
+                let value = self.nodes[node as usize].value.take()?;
+                self.len -= 1;
+
+                let mut removed = Self::with_max_nodes(key[key.len() - 1].clone(), self.max_nodes);
+                removed.nodes[0].value = Some(value);
+                removed.len = 1;
+                removed
+            }
+        };
+
+        // drop the nodes left with neither a value nor children
+        while let Some((parent, prev, idx)) = path.pop() {
+            let node = &self.nodes[idx as usize];
+            if node.value.is_some() || node.child != NIL {
+                break;
+            }
+
+            self.unlink(parent, prev, idx);
+            self.nodes.remove(idx as usize);
+        }
+
+        Some(removed)
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn clear(&mut self) {
+        let root = self.nodes.remove(0);
+        self.nodes.clear();
+        self.nodes.insert(Node::new(root.key, None));
+        self.len = 0;
+    }
+}
+
+impl<K, V, Q> FromIterator<(Q, V)> for ArenaTrie<K, V>
+where
+    K: Clone + Ord + Default,
+    Q: AsRef<[K]>,
+{
+    fn from_iter<I: IntoIterator<Item = (Q, V)>>(iter: I) -> Self {
+        let mut trie = ArenaTrie::default();
+        trie.extend(iter);
+        trie
+    }
+}
+
+impl<K, V, Q> Extend<(Q, V)> for ArenaTrie<K, V>
+where
+    K: Clone + Ord,
+    Q: AsRef<[K]>,
+{
+    /// # Panics
+    ///
+    /// Panics if the trie runs out of node capacity.
+    fn extend<I: IntoIterator<Item = (Q, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key.as_ref(), value)
+                .expect("arena trie capacity exceeded");
+        }
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a ArenaTrie<K, V>
+where
+    K: Clone + Ord,
+{
+    type Item = (Vec<K>, &'a V);
+    type IntoIter = ArenaIter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K, V> Index<&[K]> for ArenaTrie<K, V>
+where
+    K: Clone + Ord,
+{
+    type Output = V;
+
+    fn index(&self, key: &[K]) -> &V {
+        self.get(&key).expect("key not found in trie")
+    }
+}
+
+/// Borrowing iterator over the entries of an [`ArenaTrie`], in key order.
+pub struct ArenaIter<'a, K, V> {
+    trie: &'a ArenaTrie<K, V>,
+    key: Vec<K>,
+    root: Option<&'a V>,
+    /// Sibling lists still to visit, with the key length at their level.
+    stack: Vec<(u32, usize)>,
+}
+
+impl<'a, K, V> Iterator for ArenaIter<'a, K, V>
+where
+    K: Clone,
+{
+    type Item = (Vec<K>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(value) = self.root.take() {
+            return Some((Vec::new(), value));
+        }
+
+        loop {
+            let (idx, depth) = self.stack.pop()?;
+            if idx == NIL {
+                continue;
+            }
+
+            let node = &self.trie.nodes[idx as usize];
+
+            self.key.truncate(depth);
+            self.key.push(node.key.clone());
+
+            // descendants come before the next sibling
+            self.stack.push((node.next, depth));
+            self.stack.push((node.child, depth + 1));
+
+            if let Some(value) = &node.value {
+                return Some((self.key.clone(), value));
+            }
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use super::TrieNode;
+use std::{slice, vec};
+
+/// Borrowing iterator over the entries of a [`TrieNode`], in key order.
+///
+/// The root's own key is not part of any entry; each yielded key is the path
+/// of elements leading from the root to a value.
+pub struct Iter<'a, K, V> {
+    key: Vec<K>,
+    root: Option<&'a V>,
+    stack: Vec<slice::Iter<'a, TrieNode<K, V>>>,
+}
+
+impl<'a, K, V> Iter<'a, K, V> {
+    pub(crate) fn new(root: &'a TrieNode<K, V>) -> Self {
+        Self {
+            key: Vec::new(),
+            root: root.value.as_ref(),
+            stack: vec![root.children.iter()],
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V>
+where
+    K: Clone,
+{
+    type Item = (Vec<K>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(value) = self.root.take() {
+            return Some((Vec::new(), value));
+        }
+
+        loop {
+            let depth = self.stack.len();
+            let nodes = self.stack.last_mut()?;
+
+            let node = match nodes.next() {
+                Some(node) => node,
+                None => {
+                    self.stack.pop();
+                    continue;
+                }
+            };
+
+            self.key.truncate(depth - 1);
+            self.key.push(node.key.clone());
+            self.stack.push(node.children.iter());
+
+            if let Some(value) = &node.value {
+                return Some((self.key.clone(), value));
+            }
+        }
+    }
+}
+
+/// Owning iterator over the entries of a [`TrieNode`], in key order.
+pub struct IntoIter<K, V> {
+    key: Vec<K>,
+    root: Option<V>,
+    stack: Vec<vec::IntoIter<TrieNode<K, V>>>,
+}
+
+impl<K, V> IntoIter<K, V> {
+    pub(crate) fn new(root: TrieNode<K, V>) -> Self {
+        Self {
+            key: Vec::new(),
+            root: root.value,
+            stack: vec![root.children.into_iter()],
+        }
+    }
+}
+
+impl<K, V> Iterator for IntoIter<K, V>
+where
+    K: Clone,
+{
+    type Item = (Vec<K>, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(value) = self.root.take() {
+            return Some((Vec::new(), value));
+        }
+
+        loop {
+            let depth = self.stack.len();
+            let nodes = self.stack.last_mut()?;
+
+            let node = match nodes.next() {
+                Some(node) => node,
+                None => {
+                    self.stack.pop();
+                    continue;
+                }
+            };
+
+            self.key.truncate(depth - 1);
+            self.key.push(node.key);
+            self.stack.push(node.children.into_iter());
+
+            if let Some(value) = node.value {
+                return Some((self.key.clone(), value));
+            }
+        }
+    }
+}
@@ -1,10 +1,26 @@
 #[cfg(test)]
 mod tests;
 
-use crate::TrieExt;
+mod arena;
+mod iter;
+mod search;
+#[cfg(feature = "serde")]
+mod serde;
+
+pub use arena::{ArenaIter, ArenaTrie};
+pub use iter::{IntoIter, Iter};
+pub use search::Search;
+
+use crate::render::{tree_label, write_tree, Dot};
+use crate::stats::{Collector, TrieStats};
+use crate::{TrieError, TrieExt};
 use std::borrow::Borrow;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::mem;
+use std::ops::Index;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct TrieNode<K, V> {
     key: K,
     value: Option<V>,
@@ -37,11 +53,6 @@ where
         }
     }
 
-    pub fn clear(&mut self) {
-        self.children.clear();
-        self.value = None;
-    }
-
     pub fn key(&self) -> &K {
         &self.key
     }
@@ -53,6 +64,64 @@ where
     pub fn children(&self) -> &Vec<TrieNode<K, V>> {
         &self.children
     }
+
+    /// Removes the node at `key` below `self`, dropping any nodes left without
+    /// a value or children on the way back up.
+    fn detach(&mut self, key: &[K], prune: bool) -> Option<Self> {
+        let idx = self
+            .children
+            .binary_search_by(|e| e.key().cmp(&key[0]))
+            .ok()?;
+
+        let removed = match (key.len(), prune) {
+            (1, true) => return Some(self.children.remove(idx)),
+            (1, false) => {
+                let node = &mut self.children[idx];
+                let value = node.value.take()?;
+                TrieNode::from(&node.key, value)
+            }
+            _ => self.children[idx].detach(&key[1..], prune)?,
+        };
+
+        let node = &self.children[idx];
+        if node.value.is_none() && node.children.is_empty() {
+            self.children.remove(idx);
+        }
+
+        Some(removed)
+    }
+
+    /// Iterates over every stored key and value in key order, starting with
+    /// the empty key if it holds a value.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter::new(self)
+    }
+
+    /// Walks the whole tree to gather its [`TrieStats`]. Every node below the
+    /// root stores one key element of `size_of::<K>()` bytes.
+    pub fn stats(&self) -> TrieStats {
+        let mut stats = Collector::default();
+        let mut stack = vec![(self, 0)];
+
+        while let Some((node, depth)) = stack.pop() {
+            let key_bytes = match depth {
+                0 => 0,
+                _ => mem::size_of::<K>(),
+            };
+            let heap_bytes = node.children.capacity() * mem::size_of::<Self>();
+
+            stats.visit(
+                depth,
+                node.children.len(),
+                node.value.is_some(),
+                key_bytes,
+                heap_bytes,
+            );
+            stack.extend(node.children.iter().map(|n| (n, depth + 1)));
+        }
+
+        stats.finish(0)
+    }
 }
 
 impl<K, V> TrieExt<K, V> for TrieNode<K, V>
@@ -64,7 +133,10 @@ where
         Q: Borrow<[K]>,
     {
         let key = key.borrow();
-        assert!(key.len() > 0);
+
+        if key.is_empty() {
+            return self.value.as_ref();
+        }
 
         let mut node = self;
         let mut i = 0;
@@ -88,14 +160,28 @@ where
         }
     }
 
-    fn insert<Q>(&mut self, key: &Q, value: V) -> Result<Option<V>, ()>
+    fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        Q: Borrow<[K]>,
+    {
+        let mut node = self;
+
+        for k in key.borrow() {
+            let idx = node.children.binary_search_by(|e| e.key().cmp(k)).ok()?;
+            node = &mut node.children[idx];
+        }
+
+        node.value.as_mut()
+    }
+
+    fn insert<Q>(&mut self, key: &Q, value: V) -> Result<Option<V>, TrieError>
     where
         Q: Borrow<[K]> + ?Sized,
     {
         let key = key.borrow();
 
-        if key.len() == 0 {
-            let value = ::std::mem::replace(&mut self.value, Some(value));
+        if key.is_empty() {
+            let value = self.value.replace(value);
             return Ok(value);
         }
 
@@ -106,13 +192,10 @@ where
                 let node = &mut self.children[idx];
                 match key.len() {
                     1 => {
-                        let value = ::std::mem::replace(&mut node.value, Some(value));
-                        return Ok(value);
-                    }
-                    _ => {
-                        node.insert(&key[1..], value)?;
-                        return Ok(None);
+                        let value = node.value.replace(value);
+                        Ok(value)
                     }
+                    _ => node.insert(&key[1..], value),
                 }
             }
             Err(idx) => {
@@ -132,7 +215,7 @@ where
                 }
 
                 self.children.insert(idx, temp);
-                return Ok(None);
+                Ok(None)
             }
         }
     }
@@ -142,36 +225,176 @@ where
         Q: Borrow<[K]>,
     {
         let key = key.borrow();
-        assert!(key.len() > 0);
 
-        let mut nodes = &mut self.children;
-        let mut i = 0;
-        let mut rem = key;
+        // the empty key lives on the root, which is never detached
+        if key.is_empty() {
+            if prune {
+                let root = TrieNode::new(&self.key);
+                return match self.is_empty() {
+                    true => None,
+                    false => Some(std::mem::replace(self, root)),
+                };
+            }
+            let value = self.value.take()?;
+            return Some(TrieNode::from(&self.key, value));
+        }
 
-        loop {
-            let node_key = &nodes[i].key;
+        self.detach(key, prune)
+    }
 
-            if node_key == &rem[0] {
-                rem = &rem[1..];
-            }
-            match rem.len() {
-                0 => match prune {
-                    true => {
-                        let removed = nodes.remove(i);
-                        return Some(removed);
-                    }
-                    false => {
-                        let mut node = TrieNode::new(&nodes[i].key);
-                        node.value = nodes[i].value.take();
-                        return Some(node);
-                    }
-                },
-                _ => {
-                    nodes = &mut nodes[i].children;
-                    i = 0;
-                    continue;
-                }
+    fn len(&self) -> usize {
+        let count = usize::from(self.value.is_some());
+        self.children.iter().fold(count, |acc, n| acc + n.len())
+    }
+
+    fn clear(&mut self) {
+        self.children.clear();
+        self.value = None;
+    }
+}
+
+impl<K, V> TrieNode<K, V>
+where
+    K: Clone + Ord + fmt::Debug,
+    V: fmt::Debug,
+{
+    /// Renders the tree as a Graphviz digraph. Edges are labelled with the
+    /// key element of the node they lead to, and nodes holding a value show
+    /// it inside a double outline.
+    pub fn to_dot(&self) -> String {
+        let mut dot = Dot::new("circle");
+        let mut stack = vec![(self, 0)];
+        let mut next = 1;
+
+        while let Some((node, id)) = stack.pop() {
+            let label = match &node.value {
+                Some(value) => format!("{:?}", value),
+                None => String::new(),
+            };
+            dot.node(id, &label, node.value.is_some());
+
+            for child in &node.children {
+                dot.edge(id, next, &format!("{:?}", child.key));
+                stack.push((child, next));
+                next += 1;
             }
         }
+
+        dot.finish()
+    }
+}
+
+/// Draws the tree like `tree(1)`, one key element per line.
+impl<K, V> fmt::Display for TrieNode<K, V>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = |node: &Self| match std::ptr::eq(node, self) {
+            true => tree_label(".", node.value.as_ref()),
+            false => tree_label(&format!("{:?}", node.key), node.value.as_ref()),
+        };
+        write_tree(f, self, &label, &|node| node.children.iter().collect())
+    }
+}
+
+impl<K, V> Default for TrieNode<K, V>
+where
+    K: Clone + Ord + Default,
+{
+    fn default() -> Self {
+        Self::new(K::default())
+    }
+}
+
+/// Tries compare equal when they hold the same entries; the root's own key
+/// is not part of the contents.
+impl<K, V> PartialEq for TrieNode<K, V>
+where
+    K: Clone + Ord,
+    V: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl<K, V> Eq for TrieNode<K, V>
+where
+    K: Clone + Ord,
+    V: Eq,
+{
+}
+
+impl<K, V> Hash for TrieNode<K, V>
+where
+    K: Clone + Ord + Hash,
+    V: Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for entry in self.iter() {
+            entry.hash(state);
+        }
+    }
+}
+
+impl<K, V, Q> FromIterator<(Q, V)> for TrieNode<K, V>
+where
+    K: Clone + Ord + Default,
+    Q: AsRef<[K]>,
+{
+    fn from_iter<I: IntoIterator<Item = (Q, V)>>(iter: I) -> Self {
+        let mut trie = TrieNode::default();
+        trie.extend(iter);
+        trie
+    }
+}
+
+impl<K, V, Q> Extend<(Q, V)> for TrieNode<K, V>
+where
+    K: Clone + Ord,
+    Q: AsRef<[K]>,
+{
+    fn extend<I: IntoIterator<Item = (Q, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            // inserting into a trie cannot fail
+            let _ = self.insert(key.as_ref(), value);
+        }
+    }
+}
+
+impl<K, V> IntoIterator for TrieNode<K, V>
+where
+    K: Clone + Ord,
+{
+    type Item = (Vec<K>, V);
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter::new(self)
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a TrieNode<K, V>
+where
+    K: Clone + Ord,
+{
+    type Item = (Vec<K>, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K, V> Index<&[K]> for TrieNode<K, V>
+where
+    K: Clone + Ord,
+{
+    type Output = V;
+
+    fn index(&self, key: &[K]) -> &V {
+        self.get(&key).expect("key not found in trie")
     }
 }
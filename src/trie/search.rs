@@ -0,0 +1,106 @@
+use super::TrieNode;
+use crate::automaton::Automaton;
+use std::slice;
+
+impl<K, V> TrieNode<K, V>
+where
+    K: Clone,
+{
+    /// Streams every entry whose key `aut` matches, in key order.
+    ///
+    /// The automaton is stepped once per node, so keys sharing a prefix share
+    /// the work of matching it, and a subtree is skipped as soon as the
+    /// automaton reports that nothing in it can match.
+    pub fn search<A>(&self, aut: A) -> Search<'_, K, V, A>
+    where
+        A: Automaton<K>,
+    {
+        Search::new(self, aut)
+    }
+}
+
+/// Iterator over the entries of a [`TrieNode`] matched by an [`Automaton`],
+/// returned by [`TrieNode::search`].
+pub struct Search<'a, K, V, A>
+where
+    A: Automaton<K>,
+{
+    aut: A,
+    key: Vec<K>,
+    root: Option<&'a V>,
+    stack: Vec<Frame<'a, K, V, A::State>>,
+}
+
+/// The state after reading the key down to a node, and the node's children
+/// still to visit.
+type Frame<'a, K, V, S> = (S, slice::Iter<'a, TrieNode<K, V>>);
+
+impl<'a, K, V, A> Search<'a, K, V, A>
+where
+    A: Automaton<K>,
+{
+    fn new(root: &'a TrieNode<K, V>, aut: A) -> Self {
+        let state = aut.start();
+        let root_value = root.value.as_ref().filter(|_| aut.is_match(&state));
+        let stack = match aut.can_match(&state) {
+            true => vec![(state, root.children.iter())],
+            false => Vec::new(),
+        };
+
+        Self {
+            aut,
+            key: Vec::new(),
+            root: root_value,
+            stack,
+        }
+    }
+}
+
+impl<'a, K, V, A> Iterator for Search<'a, K, V, A>
+where
+    K: Clone,
+    A: Automaton<K>,
+{
+    type Item = (Vec<K>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(value) = self.root.take() {
+            return Some((Vec::new(), value));
+        }
+
+        loop {
+            let depth = self.stack.len();
+            let (state, nodes) = self.stack.last_mut()?;
+
+            let node = match nodes.next() {
+                Some(node) => node,
+                None => {
+                    self.stack.pop();
+                    continue;
+                }
+            };
+
+            // once everything matches the state no longer needs stepping
+            let always = self.aut.will_always_match(state);
+            let state = match always {
+                true => state.clone(),
+                false => self.aut.accept(state, &node.key),
+            };
+            if !always && !self.aut.can_match(&state) {
+                continue;
+            }
+            let value = node
+                .value
+                .as_ref()
+                .filter(|_| always || self.aut.is_match(&state));
+
+            self.key.truncate(depth - 1);
+            self.key.push(node.key.clone());
+            self.stack.push((state, node.children.iter()));
+
+            if let Some(value) = value {
+                return Some((self.key.clone(), value));
+            }
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use super::TrieNode;
+use crate::TrieExt;
+use serde::de::{Deserialize, Deserializer, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeSeq, Serializer};
+use std::fmt;
+use std::marker::PhantomData;
+
+/// Serializes as a sequence of `(key, value)` pairs in key order, each key a
+/// sequence of elements, so formats that only allow string map keys such as
+/// JSON can hold any key type. The root's own key is not written;
+/// deserializing uses `K::default()`.
+impl<K, V> Serialize for TrieNode<K, V>
+where
+    K: Clone + Ord + Serialize,
+    V: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut seq = serializer.serialize_seq(Some(self.len()))?;
+        for entry in self.iter() {
+            seq.serialize_element(&entry)?;
+        }
+        seq.end()
+    }
+}
+
+impl<'de, K, V> Deserialize<'de> for TrieNode<K, V>
+where
+    K: Clone + Ord + Default + Deserialize<'de>,
+    V: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(TrieVisitor(PhantomData))
+    }
+}
+
+struct TrieVisitor<K, V>(PhantomData<(K, V)>);
+
+impl<'de, K, V> Visitor<'de> for TrieVisitor<K, V>
+where
+    K: Clone + Ord + Default + Deserialize<'de>,
+    V: Deserialize<'de>,
+{
+    type Value = TrieNode<K, V>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a sequence of (key, value) pairs")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut trie = TrieNode::default();
+        while let Some((key, value)) = seq.next_element::<(Vec<K>, V)>()? {
+            // inserting into a trie cannot fail
+            let _ = trie.insert(&key, value);
+        }
+        Ok(trie)
+    }
+}
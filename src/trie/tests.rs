@@ -1,13 +1,253 @@
-use crate::trie::TrieNode;
-use crate::TrieExt;
+use crate::test_util::words;
+use crate::trie::{ArenaTrie, TrieNode};
+use crate::{TrieError, TrieExt};
+use std::collections::BTreeMap;
 
 #[test]
 fn trie_test() {
     let mut trie = TrieNode::new(0);
-    trie.insert(&[1, 2, 3], ());
-    trie.insert(&[1, 2, 3, 4], ());
-    trie.insert(&[1, 2, 4], ());
-    trie.insert(&[2, 2, 4], ());
+    trie.insert(&[1, 2, 3], ()).unwrap();
+    trie.insert(&[1, 2, 3, 4], ()).unwrap();
+    trie.insert(&[1, 2, 4], ()).unwrap();
+    trie.insert(&[2, 2, 4], ()).unwrap();
     let removed = trie.remove(&[1, 2, 3], true);
     assert!(removed.is_some());
 }
+
+#[test]
+fn trie_get_mut() {
+    let mut trie = TrieNode::new(0);
+    trie.insert(&[1, 2, 3], 1).unwrap();
+    trie.insert(&[1, 2], 2).unwrap();
+    assert_eq!(trie.len(), 2);
+
+    *trie.get_mut(&[1, 2]).unwrap() = 5;
+    assert_eq!(trie.get(&[1, 2]), Some(&5));
+    assert!(trie.get_mut(&[1]).is_none());
+    assert!(trie.contains_key(&[1, 2, 3]));
+
+    assert_eq!(trie.insert(&[1, 2, 3], 6), Ok(Some(1)));
+    assert_eq!(trie.insert(&[1, 2], 7), Ok(Some(5)));
+    assert_eq!(trie.get(&[1, 2, 3]), Some(&6));
+    assert_eq!(trie.len(), 2);
+
+    trie.clear();
+    assert!(trie.is_empty());
+}
+
+#[test]
+fn trie_empty_key() {
+    let mut trie = TrieNode::new(0);
+    let empty: [i32; 0] = [];
+
+    assert_eq!(trie.get(&empty), None);
+    assert!(trie.remove(&empty, false).is_none());
+
+    trie.insert(&empty, 1).unwrap();
+    trie.insert(&[2, 1], 2).unwrap();
+    trie.insert(&[1], 3).unwrap();
+    assert_eq!(trie.get(&empty), Some(&1));
+
+    let entries: Vec<_> = trie.iter().collect();
+    assert_eq!(entries, vec![(vec![], &1), (vec![1], &3), (vec![2, 1], &2)]);
+
+    let removed = trie.remove(&empty, false).unwrap();
+    assert_eq!(removed.value(), Some(&1));
+    assert_eq!(trie.get(&empty), None);
+    assert_eq!(trie.len(), 2);
+
+    let removed = trie.remove(&empty, true).unwrap();
+    assert_eq!(removed.len(), 2);
+    assert!(trie.is_empty());
+    assert_eq!(trie.key(), &0);
+}
+
+#[test]
+fn trie_remove_missing() {
+    let mut trie = TrieNode::new(0);
+    assert!(trie.remove(&[1, 2], false).is_none());
+
+    trie.insert(&[1, 2, 3], 1).unwrap();
+    trie.insert(&[1, 4], 2).unwrap();
+
+    assert!(trie.remove(&[1, 2], false).is_none());
+    assert!(trie.remove(&[1, 2, 3, 4], true).is_none());
+    assert!(trie.remove(&[5], true).is_none());
+
+    let removed = trie.remove(&[1, 2, 3], false).unwrap();
+    assert_eq!(removed.value(), Some(&1));
+    assert!(trie.remove(&[1, 2, 3], false).is_none());
+
+    // the now empty branch below [1] is dropped
+    assert_eq!(trie.children()[0].children().len(), 1);
+    assert_eq!(trie.get(&[1, 4]), Some(&2));
+}
+
+#[test]
+fn trie_collection_traits() {
+    let entries = [
+        (vec![3, 1], 'a'),
+        (vec![1], 'b'),
+        (vec![1, 2], 'c'),
+        (vec![], 'd'),
+    ];
+    let trie: TrieNode<u8, char> = entries.iter().cloned().collect();
+
+    let mut other = TrieNode::new(9);
+    other.extend(entries.iter().rev().cloned());
+
+    assert_eq!(trie, other);
+    assert_eq!(trie.clone(), trie);
+    assert_eq!(trie[&[1, 2][..]], 'c');
+    assert_eq!(trie[&[][..]], 'd');
+
+    let keys: Vec<_> = (&trie).into_iter().map(|(k, _)| k).collect();
+    assert_eq!(keys, vec![vec![], vec![1], vec![1, 2], vec![3, 1]]);
+
+    let owned: Vec<_> = trie.into_iter().map(|(_, v)| v).collect();
+    assert_eq!(owned, vec!['d', 'b', 'c', 'a']);
+
+    other.remove(&[1], false);
+    assert_ne!(other, TrieNode::default());
+    assert_eq!(other.len(), 3);
+}
+
+#[test]
+fn arena_trie() {
+    let keys = words(7, 500, 0..=7, 0..=3);
+
+    let mut arena = ArenaTrie::new(0);
+    let mut boxed = TrieNode::new(0);
+    let mut expected = BTreeMap::new();
+
+    for (i, key) in keys.iter().enumerate() {
+        assert_eq!(
+            arena.insert(key, i).unwrap(),
+            expected.insert(key.clone(), i)
+        );
+        boxed.insert(key, i).unwrap();
+    }
+
+    let entries: Vec<_> = arena.iter().map(|(k, v)| (k, *v)).collect();
+    assert_eq!(entries, expected.clone().into_iter().collect::<Vec<_>>());
+    assert_eq!(arena.len(), expected.len());
+
+    // freed slots are handed out again before the arena grows
+    let capacity = arena.memory_usage().capacity;
+    for key in keys.iter().step_by(3) {
+        let removed = arena.remove(key, false).map(|t| t.get(&[]).copied());
+        assert_eq!(removed, expected.remove(key).map(Some));
+    }
+    for (i, key) in keys.iter().enumerate().step_by(3) {
+        arena.insert(key, i).unwrap();
+        expected.insert(key.clone(), i);
+    }
+    assert_eq!(arena.memory_usage().capacity, capacity);
+
+    let pruned = arena.remove(&[1, 2], true).unwrap();
+    let below: Vec<_> = pruned.iter().map(|(k, v)| (k, *v)).collect();
+    let want: Vec<_> = expected
+        .iter()
+        .filter(|(k, _)| k.starts_with(&[1, 2]))
+        .map(|(k, v)| (k[2..].to_vec(), *v))
+        .collect();
+    assert_eq!(below, want);
+    assert_eq!(pruned.key(), &2);
+    expected.retain(|k, _| !k.starts_with(&[1, 2]));
+
+    arena.shrink_to_fit();
+    let usage = arena.memory_usage();
+    assert_eq!(usage.capacity, usage.nodes);
+    assert!(usage.arena_bytes < usage.boxed_bytes);
+
+    for (key, value) in &expected {
+        assert_eq!(arena[&key[..]], *value);
+    }
+    assert_eq!(arena.len(), expected.len());
+    assert!(arena.remove(&[1, 2], false).is_none());
+
+    let mut small = ArenaTrie::with_max_nodes(0, 5);
+    small.insert(&[1, 2, 3], ()).unwrap();
+    assert_eq!(small.insert(&[2, 2], ()), Err(TrieError::CapacityExceeded));
+    assert_eq!(small.insert(&[1, 2, 4], ()), Ok(None));
+    assert_eq!(small.len(), 2);
+}
+
+#[test]
+fn trie_stats() {
+    let mut trie = TrieNode::new(0);
+    trie.insert(&[1, 2], ()).unwrap();
+    trie.insert(&[1, 3], ()).unwrap();
+    trie.insert(&[2], ()).unwrap();
+
+    let stats = trie.stats();
+    assert_eq!(stats.nodes, 5);
+    assert_eq!(stats.values, 3);
+    assert_eq!(stats.max_depth, 2);
+    assert_eq!(stats.fanout, vec![3, 0, 2]);
+    assert_eq!(stats.key_bytes, 4 * std::mem::size_of::<i32>());
+    assert_eq!(
+        stats.shape(),
+        format!(
+            "nodes=5 values=3 depth=2/1.67 fanout=0:3,2:2 key_bytes=16 heap_bytes={}",
+            stats.heap_bytes
+        )
+    );
+}
+
+#[test]
+fn trie_render() {
+    let mut trie = TrieNode::new('-');
+    trie.insert(&['a', 'b'], 1).unwrap();
+    trie.insert(&['a', 'c'], 2).unwrap();
+    trie.insert(&['b'], 3).unwrap();
+
+    let tree = "\
+.
+├── 'a'
+│   ├── 'b' = 1
+│   └── 'c' = 2
+└── 'b' = 3
+";
+    assert_eq!(trie.to_string(), tree);
+
+    let dot = trie.to_dot();
+    assert!(dot.contains("    n0 -> n1 [label=\"'a'\"];\n"));
+    assert!(dot.contains("    n1 -> n4 [label=\"'c'\"];\n"));
+    assert!(dot.contains("    n4 [label=\"2\", peripheries=2];\n"));
+    assert_eq!(dot.matches(" -> ").count(), 4);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn trie_serde() {
+    use serde_test::{assert_tokens, Token};
+
+    let trie: TrieNode<u8, char> = [(vec![2], 'a'), (vec![1, 2], 'b')].into_iter().collect();
+
+    assert_tokens(
+        &trie,
+        &[
+            Token::Seq { len: Some(2) },
+            Token::Tuple { len: 2 },
+            Token::Seq { len: Some(2) },
+            Token::U8(1),
+            Token::U8(2),
+            Token::SeqEnd,
+            Token::Char('b'),
+            Token::TupleEnd,
+            Token::Tuple { len: 2 },
+            Token::Seq { len: Some(1) },
+            Token::U8(2),
+            Token::SeqEnd,
+            Token::Char('a'),
+            Token::TupleEnd,
+            Token::SeqEnd,
+        ],
+    );
+
+    let json = serde_json::to_string(&trie).unwrap();
+    let decoded: TrieNode<u8, char> = serde_json::from_str(&json).unwrap();
+    assert_eq!(decoded, trie);
+    assert_eq!(decoded.get(&[1, 2]), Some(&'b'));
+}
@@ -5,7 +5,7 @@ fn path_trie_test() {
     let mut trie = PathTrie::new();
 
     for (i, s) in ROUTES.iter().enumerate() {
-        trie.insert(s, i);
+        trie.insert(s, i).unwrap();
     }
 
     for (idx, url) in URLS.iter().enumerate() {